@@ -1,36 +1,392 @@
-use anyhow::Result;
+use crate::extracter::{BlockClassifier, DefaultClassifier, PostProcessor};
+use crate::models::EmphasisSpan;
+use anyhow::{Error, Result};
+#[cfg(feature = "native")]
 use rand::Rng;
 use std::collections::HashMap;
+#[cfg(feature = "native")]
+use std::ops::{Deref, DerefMut};
 use std::path::Path;
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization;
 
 pub type PageNumber = i8;
 
+/// `Normalization` selects the Unicode normalization form applied to the text of words.
+///
+/// # Variants
+///
+/// * `Nfc` - Canonical composition, merges base characters with their combining marks.
+/// * `Nfkc` - Compatibility composition, additionally folds full-width forms and ligatures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    Nfc,
+    Nfkc,
+}
+
+/// `LigatureMode` selects how typographic ligatures such as "ﬁ" and "ﬀ" are handled.
+///
+/// # Variants
+///
+/// * `Keep` - The ligatures are kept as extracted, for a faithful reproduction.
+/// * `Normalize` - The ligatures are replaced by their letters, for search and matching.
+/// * `Both` - The ligatures are kept in `Word::text` and the replaced text is stored in
+///   `Word::normalized_text`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LigatureMode {
+    #[default]
+    Keep,
+    Normalize,
+    Both,
+}
+
+/// `TextOptions` controls how the text extracted from the PDF document is post-processed.
+///
+/// # Fields
+///
+/// * `normalization` - The Unicode normalization applied to each word, `None` keeps the raw text.
+/// * `collapse_whitespace` - Whether runs of whitespace are collapsed into a single space.
+/// * `trim_lines` - Whether leading and trailing whitespace is removed from each line.
+/// * `strip_invisible` - Whether soft hyphens, zero-width characters and C0/C1 control
+///   characters are removed from each word.
+/// * `ligatures` - How ligatures are handled. `Normalization::Nfkc` already folds them, so
+///   `Keep` only preserves them with the other normalization settings.
+/// * `keep_word_spaces` - Whether the leading and trailing whitespace of each word is kept
+///   instead of trimmed, for a faithful reconstruction of code or monospace blocks.
+/// * `math_placeholder` - The token replacing each run of inline math, such as `"[MATH]"`, or
+///   `None` to keep the math as extracted. See `Line::inline_math` for the detected runs.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextOptions {
+    pub normalization: Option<Normalization>,
+    pub collapse_whitespace: bool,
+    pub trim_lines: bool,
+    pub strip_invisible: bool,
+    pub ligatures: LigatureMode,
+    pub keep_word_spaces: bool,
+    pub math_placeholder: Option<String>,
+}
+
+impl TextOptions {
+    /// Applies the configured sanitization and Unicode normalization to the given text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The raw text of a word.
+    ///
+    /// # Returns
+    ///
+    /// A `String` without invisible characters when `strip_invisible` is set, in the configured
+    /// normalization form, or the unchanged text when both are disabled.
+    pub fn normalize(&self, text: &str) -> String {
+        let text = if self.strip_invisible {
+            strip_invisible(text)
+        } else {
+            text.to_string()
+        };
+        match self.normalization {
+            Some(Normalization::Nfc) => text.nfc().collect::<String>(),
+            Some(Normalization::Nfkc) => text.nfkc().collect::<String>(),
+            None => text,
+        }
+    }
+
+    /// Applies the configured ligature handling to the text of a word.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The normalized text of a word.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the text to store in `Word::text` and the text to store in
+    /// `Word::normalized_text`, which is only set with `LigatureMode::Both`.
+    pub fn apply_ligatures(&self, text: &str) -> (String, Option<String>) {
+        match self.ligatures {
+            LigatureMode::Keep => (text.to_string(), None),
+            LigatureMode::Normalize => (expand_ligatures(text), None),
+            LigatureMode::Both => (text.to_string(), Some(expand_ligatures(text))),
+        }
+    }
+
+    /// Applies the configured whitespace handling to the text of a line.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of a line.
+    ///
+    /// # Returns
+    ///
+    /// A `String` with whitespace runs collapsed and/or the line trimmed, depending on the options.
+    pub fn clean_line(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        if self.collapse_whitespace {
+            text = collapse_whitespace(&text);
+        }
+        if self.trim_lines {
+            text = text.trim().to_string();
+        }
+        return text;
+    }
+}
+
+/// `TextAreaThresholds` sets how much of a block or line must lie in the text area to count as
+/// inside it.
+///
+/// Each threshold is the minimum fraction of the area inside the text area; anything without
+/// any overlap is always outside.
+///
+/// # Fields
+///
+/// * `font_size` - The threshold for the lines sampled to estimate the normal font size.
+/// * `text` - The threshold for keeping body blocks.
+/// * `title` - The threshold for keeping section headings, which often extend past the margins.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TextAreaThresholds {
+    pub font_size: f32,
+    pub text: f32,
+    pub title: f32,
+}
+
+/// `FullTextOptions` controls how the text of a whole document is assembled.
+///
+/// # Fields
+///
+/// * `block_separator` - The separator inserted between consecutive blocks of a page.
+/// * `page_separator` - The separator inserted between consecutive pages.
+/// * `page_marker` - An optional marker (e.g. `"\u{000C}"`) inserted at every page break.
+/// * `text_options` - The post-processing applied to the text of each block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FullTextOptions {
+    pub block_separator: String,
+    pub page_separator: String,
+    pub page_marker: Option<String>,
+    pub text_options: TextOptions,
+}
+
+impl Default for FullTextOptions {
+    fn default() -> Self {
+        FullTextOptions {
+            block_separator: "\n\n".to_string(),
+            page_separator: "\n\n".to_string(),
+            page_marker: None,
+            text_options: TextOptions::default(),
+        }
+    }
+}
+
+impl FullTextOptions {
+    /// Creates options keeping the page boundaries of the document.
+    ///
+    /// pdftotext runs with `-nopgbrk`, so page breaks are only restored when the pages are
+    /// joined. Pass `"\u{000C}"` to get form feeds as in the default pdftotext output.
+    ///
+    /// # Arguments
+    ///
+    /// * `marker` - The marker inserted at every page break.
+    ///
+    /// # Returns
+    ///
+    /// The default `FullTextOptions` with the given page marker.
+    pub fn with_page_marker(marker: &str) -> FullTextOptions {
+        return FullTextOptions {
+            page_marker: Some(marker.to_string()),
+            ..FullTextOptions::default()
+        };
+    }
+}
+
+/// Removes soft hyphens, zero-width characters, the byte order mark and C0/C1 control
+/// characters from the text.
+pub(crate) fn strip_invisible(text: &str) -> String {
+    return text
+        .chars()
+        .filter(|c| {
+            !c.is_control()
+                && !matches!(
+                    c,
+                    '\u{00AD}' | '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}'
+                )
+        })
+        .collect();
+}
+
+/// Replaces the Latin ligatures of the text (e.g. "ﬁ", "ﬃ") with their letters.
+pub(crate) fn expand_ligatures(text: &str) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\u{FB00}' => expanded.push_str("ff"),
+            '\u{FB01}' => expanded.push_str("fi"),
+            '\u{FB02}' => expanded.push_str("fl"),
+            '\u{FB03}' => expanded.push_str("ffi"),
+            '\u{FB04}' => expanded.push_str("ffl"),
+            '\u{FB05}' | '\u{FB06}' => expanded.push_str("st"),
+            _ => expanded.push(c),
+        }
+    }
+    return expanded;
+}
+
+/// Replaces every run of whitespace characters in the text with a single space.
+pub(crate) fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                collapsed.push(' ');
+            }
+            in_space = true;
+        } else {
+            collapsed.push(c);
+            in_space = false;
+        }
+    }
+    return collapsed;
+}
+
+/// The error returned when a document has more pages than `ParserConfig::max_pages`.
+///
+/// It can be told apart from other parse errors with `anyhow::Error::downcast_ref`.
+///
+/// # Fields
+///
+/// * `pages` - The number of pages in the document.
+/// * `limit` - The maximum number of pages allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyPages {
+    pub pages: usize,
+    pub limit: usize,
+}
+
+impl std::fmt::Display for TooManyPages {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(
+            f,
+            "Error: the document has {} pages, the limit is {}",
+            self.pages, self.limit
+        );
+    }
+}
+
+impl std::error::Error for TooManyPages {}
+
 /// `ParserConfig` is a configuration structure for parsing PDF documents.
 ///
 /// # Fields
 ///
 /// * `pdf_path` - The file path to the PDF document.
+/// * `pdf_source_path` - The local PDF read in place instead of a copy at `pdf_path`, if any.
 /// * `pdf_text_path` - The file path to the extracted text from the PDF document.
 /// * `pdf_figures` - A map of page numbers to file paths of extracted figures from the PDF document.
 /// * `pdf_xml_path` - The file path to the extracted XML data from the PDF document.
 /// * `sections` - A vector of tuples containing page numbers and section titles.
 /// * `pdf_info` - A map containing metadata information about the PDF document.
+/// * `detect_emphasis` - Whether to read font information to flag bold and italic words.
+/// * `pdf_emphasis` - A map of page numbers to the bold/italic regions found on the page.
+/// * `dedup_words` - Whether to drop words duplicated by an overlapping text layer.
+/// * `dedup_iou_threshold` - The IoU above which two words with the same text are taken for
+///   duplicates when `dedup_words` is set.
+/// * `merge_split_words` - Whether to join words that pdftotext split at a narrow gap.
+/// * `merge_blocks` - Whether to join the blocks of a paragraph that pdftotext split apart,
+///   see `extracter::merge_adjacent_blocks`.
+/// * `per_page_text_area` - Whether to estimate the main text area of each page separately.
+/// * `resolution` - The resolution in DPI at which pdftocairo renders the pages, recorded on
+///   each page. The coordinates read from pdftotext stay in PDF points.
+/// * `text_options` - The post-processing applied to the extracted text.
+/// * `max_pages` - The maximum number of pages a document may have, unlimited if `None`; longer
+///   documents fail with `TooManyPages`.
+/// * `classifier` - The classifier deciding the `BlockAttr` of each block.
+/// * `text_area_thresholds` - The fractions of a block or line inside the text area required by
+///   each pass.
+/// * `drop_empty` - Whether to skip the lines and blocks without any word while parsing.
+/// * `inherit_stdio` - Whether the Poppler tools print to the console of the host instead of
+///   having their output captured.
+/// * `keep_intermediates` - Whether the intermediate files are left in place when a parse fails,
+///   for inspection.
+/// * `post_processors` - The custom passes run on each page at the end of the parse, in order.
 ///
 /// # Methods
 ///
 /// * `new` - Creates a new instance of `ParserConfig` with default values.
 /// * `pdf_width` - Returns the width of the PDF document as an `i32`.
 /// * `pdf_height` - Returns the height of the PDF document as an `i32`.
+/// * `source_pdf_path` - Returns the path of the PDF passed to the Poppler tools.
+/// * `check_page_count` - Returns an error if a page count exceeds `max_pages`.
 /// * `clean_files` - Removes the PDF, text, XML, and figure files associated with the `ParserConfig`.
 //
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct ParserConfig {
     pub pdf_path: String,
+    pub pdf_source_path: Option<String>,
     pub pdf_text_path: String,
     pub pdf_figures: HashMap<PageNumber, String>,
     pub pdf_xml_path: String,
     pub sections: Vec<(PageNumber, String)>,
     pub pdf_info: HashMap<String, String>,
+    pub detect_emphasis: bool,
+    pub pdf_emphasis: HashMap<PageNumber, Vec<EmphasisSpan>>,
+    pub dedup_words: bool,
+    pub dedup_iou_threshold: f32,
+    pub merge_split_words: bool,
+    pub merge_blocks: bool,
+    pub per_page_text_area: bool,
+    pub resolution: u32,
+    pub text_options: TextOptions,
+    pub max_pages: Option<usize>,
+    pub classifier: Arc<dyn BlockClassifier>,
+    pub text_area_thresholds: TextAreaThresholds,
+    pub drop_empty: bool,
+    pub inherit_stdio: bool,
+    pub keep_intermediates: bool,
+    pub post_processors: Vec<Arc<dyn PostProcessor>>,
+}
+
+impl PartialEq for ParserConfig {
+    /// Compares the settings field by field, classifiers and post-processors being equal when
+    /// they are the same instances.
+    fn eq(&self, other: &Self) -> bool {
+        return self.pdf_path == other.pdf_path
+            && self.pdf_source_path == other.pdf_source_path
+            && self.pdf_text_path == other.pdf_text_path
+            && self.pdf_figures == other.pdf_figures
+            && self.pdf_xml_path == other.pdf_xml_path
+            && self.sections == other.sections
+            && self.pdf_info == other.pdf_info
+            && self.detect_emphasis == other.detect_emphasis
+            && self.pdf_emphasis == other.pdf_emphasis
+            && self.dedup_words == other.dedup_words
+            && self.dedup_iou_threshold == other.dedup_iou_threshold
+            && self.merge_split_words == other.merge_split_words
+            && self.merge_blocks == other.merge_blocks
+            && self.per_page_text_area == other.per_page_text_area
+            && self.resolution == other.resolution
+            && self.text_options == other.text_options
+            && self.max_pages == other.max_pages
+            && Arc::ptr_eq(&self.classifier, &other.classifier)
+            && self.text_area_thresholds == other.text_area_thresholds
+            && self.drop_empty == other.drop_empty
+            && self.inherit_stdio == other.inherit_stdio
+            && self.keep_intermediates == other.keep_intermediates
+            && self.post_processors.len() == other.post_processors.len()
+            && self
+                .post_processors
+                .iter()
+                .zip(other.post_processors.iter())
+                .all(|(a, b)| Arc::ptr_eq(a, b));
+    }
+}
+
+#[cfg(feature = "native")]
+pub(crate) fn random_file_id() -> u32 {
+    let mut rng = rand::rng();
+    return rng.random_range(10000..99999);
+}
+
+#[cfg(not(feature = "native"))]
+pub(crate) fn random_file_id() -> u32 {
+    static COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(10000);
+    return COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 }
 
 impl ParserConfig {
@@ -38,18 +394,35 @@ impl ParserConfig {
     ///
     /// This function initializes the following fields:
     /// - `pdf_path`: A randomly generated file path in the `/tmp` directory.
+    /// - `pdf_source_path`: `None`, set when a local file is parsed in place.
     /// - `pdf_text_path`: The path to the HTML text version of the PDF.
     /// - `pdf_figures`: An empty `HashMap` to store figures extracted from the PDF.
     /// - `pdf_xml_path`: The path to the raw XML version of the PDF.
     /// - `sections`: An empty vector to store sections of the parsed PDF.
     /// - `pdf_info`: An empty `HashMap` to store additional PDF information.
+    /// - `detect_emphasis`: `false`, bold and italic words are not detected by default.
+    /// - `pdf_emphasis`: An empty `HashMap` to store the emphasized regions of each page.
+    /// - `dedup_words`: `false`, duplicated words are kept by default.
+    /// - `dedup_iou_threshold`: `0.8`, only words nearly covering each other are duplicates.
+    /// - `merge_split_words`: `false`, words are kept as split by pdftotext by default.
+    /// - `merge_blocks`: `false`, blocks are kept as split by pdftotext by default.
+    /// - `per_page_text_area`: `false`, one text area is shared by all pages by default.
+    /// - `resolution`: `72`, one pixel per PDF point.
+    /// - `text_options`: The default `TextOptions`, which keep the raw text.
+    /// - `max_pages`: `None`, documents of any length are parsed by default.
+    /// - `classifier`: The `DefaultClassifier`, which applies the built-in heuristics.
+    /// - `text_area_thresholds`: All `0.0`, any overlap with the text area is enough.
+    /// - `drop_empty`: `true`, blank lines and blocks are left out of the pages.
+    /// - `inherit_stdio`: `false`, the warnings of the Poppler tools are captured, and only
+    ///   reported when a conversion fails.
+    /// - `keep_intermediates`: `false`, the intermediate files of a failed parse are removed.
+    /// - `post_processors`: Empty, no custom pass is run.
     ///
     /// # Returns
     ///
     /// A new `ParserConfig` instance with the initialized fields.
     pub fn new() -> ParserConfig {
-        let mut rng = rand::rng();
-        let random_value = rng.random_range(10000..99999);
+        let random_value = random_file_id();
         let mut pdf_path = String::new();
         pdf_path.push_str("/tmp/pdf_");
         pdf_path.push_str(&random_value.to_string());
@@ -61,12 +434,59 @@ impl ParserConfig {
         let sections = Vec::new();
         ParserConfig {
             pdf_path: pdf_path,
+            pdf_source_path: None,
             pdf_text_path: pdf_html_path,
             pdf_figures: pdf_figures,
             pdf_xml_path: pdf_raw_html_path,
             sections: sections,
             pdf_info: HashMap::new(),
+            detect_emphasis: false,
+            pdf_emphasis: HashMap::new(),
+            dedup_words: false,
+            dedup_iou_threshold: 0.8,
+            merge_split_words: false,
+            merge_blocks: false,
+            per_page_text_area: false,
+            resolution: 72,
+            text_options: TextOptions::default(),
+            max_pages: None,
+            classifier: Arc::new(DefaultClassifier::default()),
+            text_area_thresholds: TextAreaThresholds::default(),
+            drop_empty: true,
+            inherit_stdio: false,
+            keep_intermediates: false,
+            post_processors: Vec::new(),
+        }
+    }
+
+    /// Returns the path of the PDF passed to the Poppler tools.
+    ///
+    /// Local files are read in place from `pdf_source_path`, while downloaded documents are
+    /// stored at `pdf_path`.
+    ///
+    /// # Returns
+    ///
+    /// A `&str` holding the path of the PDF to convert.
+    pub fn source_pdf_path(&self) -> &str {
+        return self.pdf_source_path.as_deref().unwrap_or(&self.pdf_path);
+    }
+
+    /// Checks a page count against `max_pages`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pages` - The number of pages in the document.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which holds a `TooManyPages` error when the limit is exceeded.
+    pub fn check_page_count(&self, pages: usize) -> Result<()> {
+        if let Some(limit) = self.max_pages {
+            if pages > limit {
+                return Err(Error::new(TooManyPages { pages, limit }));
+            }
         }
+        return Ok(());
     }
 
     /// Returns the width of the PDF page.
@@ -136,3 +556,127 @@ impl ParserConfig {
         return Ok(());
     }
 }
+
+/// Removes the intermediate files of a `ParserConfig` when dropped, unless disarmed.
+///
+/// The guard dereferences to the borrowed configuration, so the files written through it are
+/// removed even when the conversion returns early, panics or is cancelled. Nothing is removed
+/// when `keep_intermediates` is set.
+#[cfg(feature = "native")]
+pub(crate) struct CleanupGuard<'a> {
+    config: &'a mut ParserConfig,
+    armed: bool,
+}
+
+#[cfg(feature = "native")]
+impl<'a> CleanupGuard<'a> {
+    /// Creates an armed guard over the configuration.
+    pub(crate) fn new(config: &'a mut ParserConfig) -> CleanupGuard<'a> {
+        return CleanupGuard {
+            config,
+            armed: true,
+        };
+    }
+
+    /// Keeps the intermediate files, once the conversion succeeded.
+    pub(crate) fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+#[cfg(feature = "native")]
+impl Deref for CleanupGuard<'_> {
+    type Target = ParserConfig;
+
+    fn deref(&self) -> &ParserConfig {
+        return self.config;
+    }
+}
+
+#[cfg(feature = "native")]
+impl DerefMut for CleanupGuard<'_> {
+    fn deref_mut(&mut self) -> &mut ParserConfig {
+        return self.config;
+    }
+}
+
+#[cfg(feature = "native")]
+impl Drop for CleanupGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed && !self.config.keep_intermediates {
+            let _ = self.config.clean_files();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_options_normalize() {
+        let decomposed = "Schro\u{0308}dinger caf\u{0065}\u{0301}";
+        let composed = "Schr\u{00f6}dinger caf\u{00e9}";
+
+        let options = TextOptions::default();
+        assert_eq!(options.normalize(decomposed), decomposed);
+
+        let options = TextOptions {
+            normalization: Some(Normalization::Nfc),
+            ..TextOptions::default()
+        };
+        assert_eq!(options.normalize(decomposed), composed);
+
+        let options = TextOptions {
+            normalization: Some(Normalization::Nfkc),
+            ..TextOptions::default()
+        };
+        assert_eq!(options.normalize("\u{FF11}\u{FF12}\u{FF13}"), "123");
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn test_cleanup_guard() {
+        let touch = |config: &ParserConfig| {
+            std::fs::write(&config.pdf_text_path, "text").unwrap();
+            std::fs::write(&config.pdf_xml_path, "xml").unwrap();
+        };
+        let exists = |config: &ParserConfig| {
+            Path::new(&config.pdf_text_path).exists() && Path::new(&config.pdf_xml_path).exists()
+        };
+
+        let mut config = ParserConfig::new();
+        touch(&CleanupGuard::new(&mut config));
+        assert!(!Path::new(&config.pdf_text_path).exists());
+        assert!(!Path::new(&config.pdf_xml_path).exists());
+
+        let guard = CleanupGuard::new(&mut config);
+        touch(&guard);
+        guard.disarm();
+        assert!(exists(&config));
+
+        config.keep_intermediates = true;
+        drop(CleanupGuard::new(&mut config));
+        assert!(exists(&config));
+
+        config.clean_files().unwrap();
+    }
+
+    #[test]
+    fn test_text_options_strip_invisible() {
+        let word = "hy\u{00AD}phen\u{200B}ation\u{0007}\u{0085}";
+
+        let options = TextOptions::default();
+        assert_eq!(options.normalize(word), word);
+
+        let options = TextOptions {
+            strip_invisible: true,
+            ..TextOptions::default()
+        };
+        assert_eq!(options.normalize(word), "hyphenation");
+        assert_eq!(
+            options.normalize("caf\u{00e9} \u{0645}\u{0631}"),
+            "caf\u{00e9} \u{0645}\u{0631}"
+        );
+    }
+}
@@ -1,26 +1,30 @@
-use crate::config::{PageNumber, ParserConfig};
+#[cfg(feature = "download")]
+use crate::config::random_file_id;
+use crate::config::{CleanupGuard, PageNumber, ParserConfig};
+use crate::models::{Coordinate, EmphasisSpan, Figure};
 use anyhow::{Error, Result};
+#[cfg(feature = "download")]
+use futures::stream::StreamExt;
 use glob::glob;
 use indicatif::ProgressBar;
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
+#[cfg(feature = "download")]
 use reqwest as request;
 use scraper::html;
 use std::{
-    collections::HashMap,
-    fs::File,
-    io::Read,
+    collections::{HashMap, HashSet},
     path::Path,
-    process::{Command, Stdio},
+    process::Stdio,
     time::Duration,
 };
 
-pub(crate) fn get_pdf_info(
+pub(crate) async fn get_pdf_info(
     config: &mut ParserConfig,
     verbose: bool,
     time: std::time::Instant,
 ) -> Result<()> {
-    let res =
-        Command::new("pdfinfo").args(&[config.pdf_path.clone()]).stdout(Stdio::piped()).output();
+    // The metadata is read from stdout, so the output of pdfinfo is always captured
+    let res = run_command("pdfinfo", &[config.source_pdf_path().to_string()], false).await;
     let text = String::from_utf8(res?.stdout)?;
 
     if text.is_empty() {
@@ -50,7 +54,7 @@ pub(crate) fn get_pdf_info(
     return Ok(());
 }
 
-pub(crate) fn save_pdf_as_figures(
+pub(crate) async fn save_pdf_as_figures(
     config: &mut ParserConfig,
     verbose: bool,
     time: std::time::Instant,
@@ -58,16 +62,18 @@ pub(crate) fn save_pdf_as_figures(
     let pdf_path = Path::new(config.pdf_path.as_str());
     let dst_path = pdf_path.parent().unwrap().join(pdf_path.file_stem().unwrap().to_str().unwrap());
 
-    let res = Command::new("pdftocairo")
-        .args(&[
+    let res = run_command(
+        "pdftocairo",
+        &[
             "-jpeg".to_string(),
             "-r".to_string(),
-            "72".to_string(),
-            pdf_path.to_str().unwrap().to_string(),
+            config.resolution.to_string(),
+            config.source_pdf_path().to_string(),
             dst_path.to_str().unwrap().to_string(),
-        ])
-        .stdout(Stdio::piped())
-        .output();
+        ],
+        config.inherit_stdio,
+    )
+    .await;
     if let Err(e) = res {
         return Err(Error::msg(format!("Error: {}", e)));
     }
@@ -84,7 +90,7 @@ pub(crate) fn save_pdf_as_figures(
         if retry_count == 0 {
             return Err(Error::msg("Error: Failed to save PDF as JPEG files"));
         } else {
-            std::thread::sleep(Duration::from_millis(100));
+            tokio::time::sleep(Duration::from_millis(100)).await;
             retry_count -= 1;
         }
     }
@@ -117,26 +123,31 @@ pub(crate) fn save_pdf_as_figures(
     return Ok(());
 }
 
-pub(crate) fn save_pdf_as_xml(
+pub(crate) async fn save_pdf_as_xml(
     config: &mut ParserConfig,
     verbose: bool,
     time: std::time::Instant,
 ) -> Result<()> {
     let xml_path = Path::new(&config.pdf_xml_path);
 
-    Command::new("pdftohtml")
-        .args(&[
+    let output = run_command(
+        "pdftohtml",
+        &[
             "-c".to_string(),
             "-s".to_string(),
             "-dataurls".to_string(),
             "-xml".to_string(),
             "-zoom".to_string(),
             "1.0".to_string(),
-            config.pdf_path.as_str().to_string(),
+            config.source_pdf_path().to_string(),
             xml_path.to_str().unwrap().to_string(),
-        ])
-        .stdout(Stdio::piped())
-        .output()?;
+        ],
+        config.inherit_stdio,
+    )
+    .await?;
+    if !output.status.success() && !xml_path.exists() {
+        return Err(command_error("pdftohtml", &output));
+    }
 
     let mut retry_count = 300;
     loop {
@@ -146,7 +157,7 @@ pub(crate) fn save_pdf_as_xml(
         if retry_count == 0 {
             return Err(Error::msg("Error: Failed to save PDF as XML file"));
         } else {
-            std::thread::sleep(Duration::from_secs(1));
+            tokio::time::sleep(Duration::from_secs(1)).await;
             retry_count -= 1;
 
             if verbose {
@@ -155,11 +166,18 @@ pub(crate) fn save_pdf_as_xml(
         }
     }
 
+    if config.detect_emphasis {
+        config.pdf_emphasis = extract_emphasis_spans(&tokio::fs::read_to_string(xml_path).await?)?;
+        if verbose {
+            tracing::info!("Extracted Emphasis in {:.2}s", time.elapsed().as_secs());
+        }
+    }
+
     // Detect title font size
     let mut current_font_number = 0;
     let mut current_text = String::new();
     let mut title_fonts = vec![];
-    let xml_text = std::fs::read_to_string(xml_path)?;
+    let xml_text = tokio::fs::read_to_string(xml_path).await?;
     let mut reader = quick_xml::Reader::from_str(&xml_text);
     reader.config_mut().trim_text(true);
     loop {
@@ -328,25 +346,203 @@ pub(crate) fn save_pdf_as_xml(
     return Ok(());
 }
 
-pub(crate) fn save_pdf_as_text(
+fn xml_attr(e: &BytesStart, key: &[u8]) -> Result<Option<String>> {
+    for attr in e.attributes() {
+        let attr = attr?;
+        if attr.key.as_ref() == key {
+            return Ok(Some(
+                String::from_utf8_lossy(attr.value.as_ref()).to_string(),
+            ));
+        }
+    }
+    return Ok(None);
+}
+
+fn xml_attr_f32(e: &BytesStart, key: &[u8]) -> Result<f32> {
+    match xml_attr(e, key)? {
+        Some(value) => Ok(value.parse::<f32>()?),
+        None => Err(Error::msg(format!(
+            "Error: missing attribute '{}'",
+            String::from_utf8_lossy(key)
+        ))),
+    }
+}
+
+/// Extracts the bold and italic regions of each page from the XML produced by `pdftohtml`.
+///
+/// A `<text>` element is emphasized either because its font family is a bold/italic face or
+/// because its content is wrapped in `<b>`/`<i>`. When only part of the element is emphasized,
+/// the region is apportioned by character count. Malformed XML is reported as an error rather
+/// than returning the spans read so far.
+pub(crate) fn extract_emphasis_spans(
+    xml_text: &str,
+) -> Result<HashMap<PageNumber, Vec<EmphasisSpan>>> {
+    let mut spans: HashMap<PageNumber, Vec<EmphasisSpan>> = HashMap::new();
+    let mut font_styles: HashMap<String, (bool, bool)> = HashMap::new();
+    let mut page_number: PageNumber = 0;
+    let mut text_box: Option<(f32, f32, f32, f32)> = None;
+    let mut font_style = (false, false);
+    let mut segments: Vec<(String, bool, bool)> = Vec::new();
+    let mut bold_depth = 0;
+    let mut italic_depth = 0;
+
+    let mut reader = quick_xml::Reader::from_str(xml_text);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => match e.name().as_ref() {
+                b"page" => {
+                    if let Some(number) = xml_attr(&e, b"number")? {
+                        page_number = number.parse::<PageNumber>()?;
+                    }
+                }
+                b"text" => {
+                    let left = xml_attr_f32(&e, b"left")?;
+                    let top = xml_attr_f32(&e, b"top")?;
+                    let width = xml_attr_f32(&e, b"width")?;
+                    let height = xml_attr_f32(&e, b"height")?;
+                    text_box = Some((left, top, width, height));
+                    font_style = xml_attr(&e, b"font")?
+                        .and_then(|font| font_styles.get(&font).cloned())
+                        .unwrap_or((false, false));
+                    segments.clear();
+                }
+                b"b" => bold_depth += 1,
+                b"i" => italic_depth += 1,
+                _ => {}
+            },
+            Ok(Event::Empty(e)) if e.name().as_ref() == b"fontspec" => {
+                if let (Some(id), Some(family)) = (xml_attr(&e, b"id")?, xml_attr(&e, b"family")?) {
+                    let family = family.to_lowercase();
+                    let bold = ["bold", "medi", "black", "heavy"]
+                        .iter()
+                        .any(|style| family.contains(style));
+                    let italic =
+                        ["italic", "oblique", "ital"].iter().any(|style| family.contains(style));
+                    font_styles.insert(id, (bold, italic));
+                }
+            }
+            Ok(Event::Text(e)) if text_box.is_some() => {
+                segments.push((
+                    String::from_utf8_lossy(e.as_ref()).to_string(),
+                    font_style.0 || bold_depth > 0,
+                    font_style.1 || italic_depth > 0,
+                ));
+            }
+            Ok(Event::End(e)) => match e.name().as_ref() {
+                b"b" => bold_depth = i32::max(bold_depth - 1, 0),
+                b"i" => italic_depth = i32::max(italic_depth - 1, 0),
+                b"text" => {
+                    if let Some((left, top, width, height)) = text_box.take() {
+                        let total =
+                            segments.iter().map(|(text, _, _)| text.chars().count()).sum::<usize>();
+                        let mut x = left;
+                        for (text, bold, italic) in segments.iter() {
+                            let w = width * text.chars().count() as f32 / total as f32;
+                            if *bold || *italic {
+                                spans.entry(page_number).or_default().push(EmphasisSpan {
+                                    coordinates: Coordinate::from_object(x, top, w, height),
+                                    bold: *bold,
+                                    italic: *italic,
+                                });
+                            }
+                            x += w;
+                        }
+                    }
+                    segments.clear();
+                    bold_depth = 0;
+                    italic_depth = 0;
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => {
+                break;
+            }
+            Err(e) => {
+                return Err(Error::msg(format!(
+                    "Error: malformed pdftohtml XML at position {}: {}",
+                    reader.error_position(),
+                    e
+                )));
+            }
+            _ => {}
+        }
+    }
+
+    return Ok(spans);
+}
+
+/// Runs an external command without blocking the runtime.
+///
+/// The child process is killed when the returned future is dropped, e.g. when the parse
+/// request that started it is cancelled. Its stdout and stderr are captured unless
+/// `inherit_stdio` is set, in which case they go to the console of the host and are left
+/// empty in the returned output.
+///
+/// # Arguments
+///
+/// * `program` - The command to run.
+/// * `args` - The arguments of the command.
+/// * `inherit_stdio` - Whether the command prints to the console instead of being captured.
+///
+/// # Returns
+///
+/// A `Result` containing the output of the finished command.
+pub(crate) async fn run_command(
+    program: &str,
+    args: &[String],
+    inherit_stdio: bool,
+) -> Result<std::process::Output> {
+    let stdio = || {
+        if inherit_stdio {
+            Stdio::inherit()
+        } else {
+            Stdio::piped()
+        }
+    };
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .stdout(stdio())
+        .stderr(stdio())
+        .kill_on_drop(true)
+        .spawn()?
+        .wait_with_output()
+        .await?;
+    return Ok(output);
+}
+
+/// Builds the error reported when an external command fails, with its captured stderr.
+fn command_error(program: &str, output: &std::process::Output) -> Error {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    return Error::msg(format!(
+        "Error: {} failed ({}): {}",
+        program,
+        output.status,
+        stderr.trim()
+    ));
+}
+
+pub(crate) async fn save_pdf_as_text(
     config: &mut ParserConfig,
     verbose: bool,
     time: std::time::Instant,
 ) -> Result<()> {
     let html_path = Path::new(config.pdf_text_path.as_str());
 
-    let _ = Command::new("pdftotext")
-        .args(&[
+    let output = run_command(
+        "pdftotext",
+        &[
             "-nopgbrk".to_string(),
             "-htmlmeta".to_string(),
             "-bbox-layout".to_string(),
-            "-r".to_string(),
-            "72".to_string(),
-            config.pdf_path.as_str().to_string(),
+            config.source_pdf_path().to_string(),
             html_path.to_str().unwrap().to_string(),
-        ])
-        .stdout(Stdio::piped())
-        .output()?;
+        ],
+        config.inherit_stdio,
+    )
+    .await?;
+    if !output.status.success() && !html_path.exists() {
+        return Err(command_error("pdftotext", &output));
+    }
 
     let mut retry_count = 300;
     loop {
@@ -355,7 +551,7 @@ pub(crate) fn save_pdf_as_text(
         } else if retry_count == 0 {
             return Err(Error::msg("Error: Failed to save PDF as text file"));
         } else {
-            std::thread::sleep(Duration::from_secs(1));
+            tokio::time::sleep(Duration::from_secs(1)).await;
             retry_count -= 1;
 
             if verbose {
@@ -374,58 +570,267 @@ pub(crate) fn save_pdf_as_text(
     return Ok(());
 }
 
+#[cfg(feature = "download")]
+async fn download_bytes(url: &str) -> Result<Vec<u8>> {
+    return download_bytes_with(&request::Client::new(), url).await;
+}
+
+/// Downloads the content at `url` with the given client, failing on HTTP error statuses.
+#[cfg(feature = "download")]
+async fn download_bytes_with(client: &request::Client, url: &str) -> Result<Vec<u8>> {
+    let res = client.get(url).send().await?.error_for_status()?;
+    return Ok(res.bytes().await?.to_vec());
+}
+
+#[cfg(not(feature = "download"))]
+async fn download_bytes(url: &str) -> Result<Vec<u8>> {
+    return Err(Error::msg(format!(
+        "Unsupported: cannot download {} because rsrpp was built without the `download` feature",
+        url
+    )));
+}
+
+pub(crate) async fn download_pdf(url: &str, save_path: &str) -> Result<()> {
+    let bytes = download_bytes(url).await?;
+    tokio::fs::write(save_path, bytes).await?;
+    return Ok(());
+}
+
+/// Downloads several PDFs in parallel.
+///
+/// At most `concurrency` downloads run at the same time, all sharing a single HTTP client.
+/// The PDFs are saved in `/tmp` under a random name shared by the batch, followed by the index
+/// of each URL. HTTP error statuses are reported as errors. The files are left in place so
+/// that they can be parsed afterwards, for example with `parser::parse_batch`, and must be
+/// removed by the caller.
+///
+/// # Arguments
+///
+/// * `urls` - The URLs of the PDFs.
+/// * `concurrency` - The maximum number of simultaneous downloads, at least `1`.
+///
+/// # Returns
+///
+/// For each URL, in the same order, the URL and the path of the downloaded file or the error
+/// met while downloading it.
+#[cfg(feature = "download")]
+pub async fn download_many(urls: &[&str], concurrency: usize) -> Vec<(String, Result<String>)> {
+    let client = request::Client::new();
+    let batch_id = random_file_id();
+    let downloads = urls.iter().enumerate().map(|(i, url)| {
+        let client = &client;
+        let path = format!("/tmp/pdf_{}_{}.pdf", batch_id, i);
+        async move {
+            let result = match download_bytes_with(client, url).await {
+                Ok(bytes) => {
+                    tokio::fs::write(&path, bytes).await.map(|_| path).map_err(Error::from)
+                }
+                Err(e) => Err(e),
+            };
+            (url.to_string(), result)
+        }
+    });
+    return futures::stream::iter(downloads).buffered(concurrency.max(1)).collect().await;
+}
+
+/// Returns a stable hash of the given content, such as the bytes of a PDF.
+///
+/// The hash is the 128-bit FNV-1a digest written as 32 hexadecimal digits. It identifies
+/// identical documents fetched from different locations, but is not meant to resist
+/// deliberate collisions.
+///
+/// # Arguments
+///
+/// * `bytes` - The content to hash.
+///
+/// # Returns
+///
+/// A `String` holding the hexadecimal digest.
+pub fn content_hash(bytes: &[u8]) -> String {
+    const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+    let mut hash = FNV_OFFSET_BASIS;
+    for byte in bytes {
+        hash ^= *byte as u128;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    return format!("{:032x}", hash);
+}
+
+/// Removes the inputs whose content duplicates an earlier input.
+///
+/// URLs are downloaded and local files are read to compute their `content_hash`; the first
+/// input of each distinct content is kept.
+///
+/// # Arguments
+///
+/// * `inputs` - The paths or URLs of the PDFs.
+///
+/// # Returns
+///
+/// A `Result` containing the distinct inputs, in their original order.
+pub async fn dedup_by_content<'a>(inputs: &[&'a str]) -> Result<Vec<&'a str>> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut unique: Vec<&str> = Vec::new();
+    for input in inputs {
+        let bytes = if input.starts_with("http") {
+            download_bytes(input).await?
+        } else {
+            tokio::fs::read(input).await?
+        };
+        if seen.insert(content_hash(&bytes)) {
+            unique.push(input);
+        }
+    }
+    return Ok(unique);
+}
+
 pub(crate) async fn save_pdf(
     path_or_url: &str,
     config: &mut ParserConfig,
     verbose: bool,
     time: std::time::Instant,
 ) -> Result<()> {
-    let save_path = config.pdf_path.as_str();
     if path_or_url.starts_with("http") {
-        let res = request::get(path_or_url).await;
-        let bytes = res?.bytes().await;
-        let out = File::create(save_path);
-        std::io::copy(&mut bytes?.as_ref(), &mut out?)?;
+        config.pdf_source_path = None;
+        download_pdf(path_or_url, &config.pdf_path).await?;
+    } else if Path::new(path_or_url).is_file() {
+        // Local files are read in place, only the intermediates are written to `/tmp`
+        config.pdf_source_path = Some(path_or_url.to_string());
     } else {
-        let path = Path::new(path_or_url);
-        let _ = std::fs::copy(path.as_os_str(), save_path);
+        return Err(Error::msg(format!(
+            "Error: {} is not a readable file",
+            path_or_url
+        )));
     }
 
-    get_pdf_info(config, verbose, time)?;
+    get_pdf_info(config, verbose, time).await?;
+    if let Some(pages) = config.pdf_info.get("pages").and_then(|p| p.parse::<usize>().ok()) {
+        config.check_page_count(pages)?;
+    }
 
-    save_pdf_as_figures(config, verbose, time)?;
+    save_pdf_as_figures(config, verbose, time).await?;
 
-    save_pdf_as_xml(config, verbose, time)?;
+    save_pdf_as_xml(config, verbose, time).await?;
 
-    save_pdf_as_text(config, verbose, time)?;
+    save_pdf_as_text(config, verbose, time).await?;
 
     return Ok(());
 }
 
-pub async fn pdf2html(
+/// Converts a PDF into the raw pdftotext `-bbox-layout` HTML.
+///
+/// The returned text can be kept for debugging or custom processing and later parsed into
+/// pages with `parser::parse_html_str`. The intermediate files are removed when the conversion
+/// fails, unless `config.keep_intermediates` is set.
+///
+/// # Arguments
+///
+/// * `path_or_url` - The path or URL of the PDF.
+/// * `config` - The parser configuration, filled with the paths of the generated files.
+/// * `verbose` - Whether to log the progress of each step.
+/// * `time` - The start time of the conversion, used in the logs.
+///
+/// # Returns
+///
+/// A `Result` containing the HTML source produced by pdftotext.
+pub async fn pdf2html_string(
     path_or_url: &str,
     config: &mut ParserConfig,
     verbose: bool,
     time: std::time::Instant,
-) -> Result<html::Html> {
+) -> Result<String> {
+    let mut guard = CleanupGuard::new(config);
+    let config = &mut *guard;
     save_pdf(path_or_url, config, verbose, time).await?;
 
     let html_path = Path::new(config.pdf_text_path.as_str());
 
-    let mut html = String::new();
-    let mut f = File::open(html_path).expect("file not found");
-    f.read_to_string(&mut html).expect("something went wrong reading the file");
+    let html = tokio::fs::read_to_string(html_path).await?;
+    guard.disarm();
+
+    return Ok(html);
+}
+
+pub async fn pdf2html(
+    path_or_url: &str,
+    config: &mut ParserConfig,
+    verbose: bool,
+    time: std::time::Instant,
+) -> Result<html::Html> {
+    let html = pdf2html_string(path_or_url, config, verbose, time).await?;
     let html = scraper::Html::parse_document(&html);
 
     return Ok(html);
 }
 
+/// Renders the figures of a converted PDF as PNG crops with pdftoppm.
+///
+/// Each crop is written next to `config.pdf_path`, named after the page and the index of the
+/// figure, and its path is stored in `Figure::image_path`. The crops are not removed by
+/// `ParserConfig::clean_files`.
+///
+/// # Arguments
+///
+/// * `config` - The parser configuration used to convert the PDF.
+/// * `figures` - The figures to render, as returned by `Document::figures`.
+/// * `resolution` - The resolution of the crops in DPI.
+///
+/// # Returns
+///
+/// A `Result` indicating whether every figure was rendered.
+pub async fn render_figures(
+    config: &ParserConfig,
+    figures: &mut [Figure],
+    resolution: u32,
+) -> Result<()> {
+    let scale = resolution as f32 / 72.0;
+    for (i, figure) in figures.iter_mut().enumerate() {
+        let prefix =
+            config.pdf_path.replace(".pdf", &format!(".figure-{}-{}", figure.page_number, i));
+        let region = figure.region.scale(scale, scale);
+        let (x1, y1, x2, y2) = region.as_rect();
+        let args = [
+            "-png".to_string(),
+            "-singlefile".to_string(),
+            "-r".to_string(),
+            resolution.to_string(),
+            "-f".to_string(),
+            figure.page_number.to_string(),
+            "-l".to_string(),
+            figure.page_number.to_string(),
+            "-x".to_string(),
+            (x1.round() as i32).to_string(),
+            "-y".to_string(),
+            (y1.round() as i32).to_string(),
+            "-W".to_string(),
+            ((x2 - x1).round() as i32).to_string(),
+            "-H".to_string(),
+            ((y2 - y1).round() as i32).to_string(),
+            config.source_pdf_path().to_string(),
+            prefix.clone(),
+        ];
+        let output = run_command("pdftoppm", &args, config.inherit_stdio).await?;
+        let image_path = format!("{}.png", prefix);
+        if !output.status.success() || !Path::new(&image_path).exists() {
+            return Err(Error::msg(format!(
+                "Error: Failed to render the figure of page {}",
+                figure.page_number
+            )));
+        }
+        figure.image_path = Some(image_path);
+    }
+    return Ok(());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::ParserConfig;
+    #[cfg(feature = "download")]
     use crate::test_utils::{BuiltinPaper, TestPapers};
 
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_pdf2html_url() {
         let time = std::time::Instant::now();
@@ -437,6 +842,22 @@ mod tests {
         let _ = config.clean_files();
     }
 
+    #[cfg(feature = "download")]
+    #[test_log::test(tokio::test)]
+    async fn test_pdf2html_string() {
+        let time = std::time::Instant::now();
+        let mut config = ParserConfig::new();
+        let url = "https://arxiv.org/pdf/1706.03762";
+        let html = pdf2html_string(url, &mut config, true, time).await.unwrap();
+        assert_eq!(
+            html.matches("<page ").count().to_string(),
+            config.pdf_info["pages"]
+        );
+        assert!(html.contains("<word "));
+        let _ = config.clean_files();
+    }
+
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_pdf2html_file() {
         let time = std::time::Instant::now();
@@ -445,7 +866,7 @@ mod tests {
         let response = request::get(url).await.unwrap();
         let bytes = response.bytes().await.unwrap();
         let path = "/tmp/test.pdf";
-        let mut file = File::create(path).unwrap();
+        let mut file = std::fs::File::create(path).unwrap();
         std::io::copy(&mut bytes.as_ref(), &mut file).unwrap();
 
         let res = pdf2html("/tmp/test.pdf", &mut config, true, time).await;
@@ -455,6 +876,7 @@ mod tests {
         let _ = config.clean_files();
     }
 
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_save_pdf_check_commands() {
         // 必要コマンド存在チェック (簡易)
@@ -489,6 +911,186 @@ mod tests {
         let _ = tp.cleanup();
     }
 
+    #[tokio::test]
+    async fn test_content_hash() {
+        assert_eq!(
+            content_hash(b"%PDF-1.4 paper"),
+            content_hash(b"%PDF-1.4 paper")
+        );
+        assert_ne!(
+            content_hash(b"%PDF-1.4 paper"),
+            content_hash(b"%PDF-1.4 Paper")
+        );
+        assert_eq!(content_hash(b""), "6c62272e07bb014262b821756295c58d");
+
+        let paths = [
+            "/tmp/rsrpp_dedup_a.pdf",
+            "/tmp/rsrpp_dedup_b.pdf",
+            "/tmp/rsrpp_dedup_c.pdf",
+        ];
+        std::fs::write(paths[0], b"%PDF-1.4 first").unwrap();
+        std::fs::write(paths[1], b"%PDF-1.4 second").unwrap();
+        std::fs::write(paths[2], b"%PDF-1.4 first").unwrap();
+        let unique = dedup_by_content(&paths).await.unwrap();
+        assert_eq!(unique, vec![paths[0], paths[1]]);
+        for path in paths {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[cfg(feature = "download")]
+    #[test_log::test(tokio::test)]
+    async fn test_download_many() {
+        let urls = [
+            "https://arxiv.org/pdf/1706.03762",
+            "https://arxiv.org/pdf/2308.10379",
+        ];
+        let results = download_many(&urls, 2).await;
+
+        assert_eq!(results.len(), 2);
+        let paths = results
+            .into_iter()
+            .zip(urls)
+            .map(|((url, path), expected)| {
+                assert_eq!(url, expected);
+                path.unwrap()
+            })
+            .collect::<Vec<String>>();
+        assert_ne!(paths[0], paths[1]);
+        for path in paths.iter() {
+            assert!(Path::new(path).exists());
+            assert!(std::fs::read(path).unwrap().starts_with(b"%PDF"));
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[cfg(feature = "download")]
+    #[tokio::test]
+    async fn test_download_many_errors() {
+        // Nothing listens on port 1, so the downloads fail without reaching the network
+        let urls = ["http://127.0.0.1:1/a.pdf", "http://127.0.0.1:1/b.pdf"];
+        let results = download_many(&urls, 0).await;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, urls[0]);
+        assert_eq!(results[1].0, urls[1]);
+        assert!(results.iter().all(|(_, path)| path.is_err()));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_command_concurrently() {
+        // Both commands run on a single-threaded runtime: they only overlap if neither blocks it
+        let time = std::time::Instant::now();
+        let args = ["1".to_string()];
+        let (first, second) = tokio::join!(
+            run_command("sleep", &args, false),
+            run_command("sleep", &args, false)
+        );
+        assert!(first.unwrap().status.success());
+        assert!(second.unwrap().status.success());
+        assert!(
+            time.elapsed() < Duration::from_millis(1800),
+            "{:?}",
+            time.elapsed()
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_command_captures_stdio() {
+        let args = [
+            "-c".to_string(),
+            "echo out; echo 'Syntax Warning: bad font' >&2".to_string(),
+        ];
+        let config = ParserConfig::new();
+        let output = run_command("sh", &args, config.inherit_stdio).await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "out\n");
+        assert_eq!(
+            String::from_utf8_lossy(&output.stderr),
+            "Syntax Warning: bad font\n"
+        );
+
+        let output = run_command("sh", &args, true).await.unwrap();
+        assert!(output.stdout.is_empty());
+        assert!(output.stderr.is_empty());
+
+        let args = [
+            "-c".to_string(),
+            "echo 'Syntax Error: broken' >&2; exit 1".to_string(),
+        ];
+        let output = run_command("sh", &args, false).await.unwrap();
+        let err = command_error("sh", &output).to_string();
+        assert!(err.contains("Syntax Error: broken"), "{}", err);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_run_command_kill_on_drop() {
+        let pid_path = format!("/tmp/rsrpp_kill_on_drop_{}.pid", std::process::id());
+        let script = format!("echo $$ > {}; exec sleep 30", pid_path);
+        let args = ["-c".to_string(), script];
+        let command = run_command("sh", &args, false);
+        let res = tokio::time::timeout(Duration::from_millis(500), command).await;
+        assert!(res.is_err(), "the slow command should not have finished");
+
+        let pid = std::fs::read_to_string(&pid_path).unwrap().trim().to_string();
+        let _ = std::fs::remove_file(&pid_path);
+        let mut terminated = false;
+        for _ in 0..50 {
+            // A killed child is either reaped or left as a zombie
+            let status = std::fs::read_to_string(format!("/proc/{}/status", pid));
+            if status.map_or(true, |status| status.contains("State:\tZ")) {
+                terminated = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(terminated, "the child process {} is still running", pid);
+    }
+
+    #[test]
+    fn test_extract_emphasis_spans() {
+        let xml = r##"<?xml version="1.0" encoding="UTF-8"?>
+<pdf2xml producer="poppler" version="22.12.0">
+<page number="1" position="absolute" top="0" left="0" height="792" width="612">
+    <fontspec id="0" size="10" family="Times" color="#000000"/>
+    <fontspec id="1" size="10" family="NimbusRomNo9L-Medi" color="#000000"/>
+<text top="100" left="100" width="200" height="10" font="0">We <b>strongly</b> disagree</text>
+<text top="120" left="100" width="100" height="10" font="1">Bold Heading</text>
+</page>
+</pdf2xml>"##;
+        let spans = extract_emphasis_spans(xml).unwrap();
+        let spans = spans.get(&1).unwrap();
+        assert_eq!(spans.len(), 2);
+        assert!(spans.iter().all(|span| span.bold && !span.italic));
+
+        let mut line = crate::models::Line::new(100.0, 100.0, 200.0, 10.0);
+        line.add_word("We".to_string(), 100.0, 100.0, 20.0, 10.0);
+        line.add_word("strongly".to_string(), 130.0, 100.0, 75.0, 10.0);
+        line.add_word("disagree".to_string(), 215.0, 100.0, 80.0, 10.0);
+        line.apply_emphasis(spans);
+        assert!(!line.words[0].bold);
+        assert!(line.words[1].bold);
+        assert!(!line.words[2].bold);
+
+        let mismatched = xml.replace("</b>", "</i>");
+        let err = extract_emphasis_spans(&mismatched).unwrap_err();
+        assert!(err.to_string().starts_with("Error: malformed pdftohtml XML"));
+    }
+
+    #[cfg(not(feature = "download"))]
+    #[test_log::test(tokio::test)]
+    async fn test_save_pdf_url_unsupported() {
+        let time = std::time::Instant::now();
+        let mut config = ParserConfig::new();
+        let url = "https://arxiv.org/pdf/1706.03762";
+        let res = save_pdf(url, &mut config, true, time).await;
+        assert!(res.unwrap_err().to_string().starts_with("Unsupported"));
+        let _ = config.clean_files();
+    }
+
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_invalid_pdf_url() {
         let time = std::time::Instant::now();
@@ -505,6 +1107,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_save_pdf_1() {
         let tp = TestPapers::setup().await.expect("setup test papers");
@@ -514,7 +1117,7 @@ mod tests {
         let mut config = ParserConfig::new();
         save_pdf(local_path.to_str().unwrap(), &mut config, true, time).await.unwrap();
 
-        assert!(Path::new(&config.pdf_path).exists());
+        assert!(Path::new(config.source_pdf_path()).exists());
 
         for (_, path) in config.pdf_figures.iter() {
             tracing::info!("path: {}", path);
@@ -539,6 +1142,7 @@ mod tests {
         let _ = tp.cleanup();
     }
 
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_save_pdf_2() {
         let tp = TestPapers::setup().await.expect("setup papers");
@@ -549,7 +1153,7 @@ mod tests {
         let mut config = ParserConfig::new();
         save_pdf(local_path.to_str().unwrap(), &mut config, true, time).await.unwrap();
 
-        assert!(Path::new(&config.pdf_path).exists());
+        assert!(Path::new(config.source_pdf_path()).exists());
 
         for (_, path) in config.pdf_figures.iter() {
             tracing::info!("path: {}", path);
@@ -576,6 +1180,7 @@ mod tests {
         let _ = tp.cleanup();
     }
 
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_save_pdf_3() {
         let tp = TestPapers::setup().await.expect("setup papers");
@@ -587,7 +1192,7 @@ mod tests {
         let mut config = ParserConfig::new();
         save_pdf(local_path.to_str().unwrap(), &mut config, true, time).await.unwrap();
 
-        assert!(Path::new(&config.pdf_path).exists());
+        assert!(Path::new(config.source_pdf_path()).exists());
 
         for (_, path) in config.pdf_figures.iter() {
             tracing::info!("path: {}", path);
@@ -612,6 +1217,7 @@ mod tests {
         let _ = config.clean_files();
         let _ = tp.cleanup();
     }
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_save_pdf_4() {
         let tp = TestPapers::setup().await.expect("setup papers");
@@ -623,7 +1229,7 @@ mod tests {
         let mut config = ParserConfig::new();
         save_pdf(local_path.to_str().unwrap(), &mut config, true, time).await.unwrap();
 
-        assert!(Path::new(&config.pdf_path).exists());
+        assert!(Path::new(config.source_pdf_path()).exists());
 
         for (_, path) in config.pdf_figures.iter() {
             tracing::info!("path: {}", path);
@@ -0,0 +1,619 @@
+use crate::config::FullTextOptions;
+use crate::models::{Block, BlockAttr, Coordinate, CoordinateOrigin, Document, Page};
+use crate::parser::{pages2json, pages2markdown};
+use quick_xml::escape::escape;
+use serde_json::{json, Value};
+
+/// Formats a coordinate as an hOCR `bbox` property (`bbox x0 y0 x1 y1`).
+fn hocr_bbox(coord: &Coordinate) -> String {
+    return format!(
+        "bbox {} {} {} {}",
+        coord.top_left.x.round() as i32,
+        coord.top_left.y.round() as i32,
+        coord.bottom_right.x.round() as i32,
+        coord.bottom_right.y.round() as i32
+    );
+}
+
+/// Converts the parsed pages into an hOCR document.
+///
+/// Pages, blocks, lines and words are emitted as `ocr_page`, `ocr_carea` (wrapping a single
+/// `ocr_par`), `ocr_line` and `ocrx_word` elements, each carrying the `bbox` of the object in
+/// PDF points.
+///
+/// # Arguments
+///
+/// * `pages` - The pages to export.
+///
+/// # Returns
+///
+/// A `String` containing the hOCR (XHTML) markup.
+pub fn to_hocr(pages: &[Page]) -> String {
+    let mut hocr = String::new();
+    hocr.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    hocr.push_str(
+        "<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\" \
+         \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd\">\n",
+    );
+    hocr.push_str("<html xmlns=\"http://www.w3.org/1999/xhtml\">\n<head>\n<title></title>\n");
+    hocr.push_str("<meta http-equiv=\"Content-Type\" content=\"text/html; charset=utf-8\"/>\n");
+    hocr.push_str("<meta name=\"ocr-system\" content=\"rsrpp\"/>\n");
+    hocr.push_str(
+        "<meta name=\"ocr-capabilities\" content=\"ocr_page ocr_carea ocr_par ocr_line ocrx_word\"/>\n",
+    );
+    hocr.push_str("</head>\n<body>\n");
+
+    for (page_index, page) in pages.iter().enumerate() {
+        let p = page.page_number;
+        let page_coord = Coordinate::from_object(0.0, 0.0, page.width, page.height);
+        hocr.push_str(&format!(
+            "<div class=\"ocr_page\" id=\"page_{}\" title=\"{}; ppageno {}\">\n",
+            p,
+            hocr_bbox(&page_coord),
+            page_index
+        ));
+        for (b, block) in page.blocks.iter().enumerate() {
+            let bbox = hocr_bbox(&block.coordinate());
+            hocr.push_str(&format!(
+                "<div class=\"ocr_carea\" id=\"block_{}_{}\" title=\"{}\">\n",
+                p, b, bbox
+            ));
+            hocr.push_str(&format!(
+                "<p class=\"ocr_par\" id=\"par_{}_{}\" title=\"{}\">\n",
+                p, b, bbox
+            ));
+            for (l, line) in block.lines.iter().enumerate() {
+                hocr.push_str(&format!(
+                    "<span class=\"ocr_line\" id=\"line_{}_{}_{}\" title=\"{}\">",
+                    p,
+                    b,
+                    l,
+                    hocr_bbox(&Coordinate::from(line))
+                ));
+                for (w, word) in line.logical_words().into_iter().enumerate() {
+                    if w > 0 {
+                        hocr.push(' ');
+                    }
+                    hocr.push_str(&format!(
+                        "<span class=\"ocrx_word\" id=\"word_{}_{}_{}_{}\" title=\"{}\">{}</span>",
+                        p,
+                        b,
+                        l,
+                        w,
+                        hocr_bbox(&Coordinate::from(word)),
+                        escape(word.text.as_str())
+                    ));
+                }
+                hocr.push_str("</span>\n");
+            }
+            hocr.push_str("</p>\n</div>\n");
+        }
+        hocr.push_str("</div>\n");
+    }
+    hocr.push_str("</body>\n</html>\n");
+    return hocr;
+}
+
+/// Formats a coordinate as ALTO `HPOS`/`VPOS`/`WIDTH`/`HEIGHT` attributes.
+fn alto_position(coord: &Coordinate) -> String {
+    return format!(
+        "HPOS=\"{:.2}\" VPOS=\"{:.2}\" WIDTH=\"{:.2}\" HEIGHT=\"{:.2}\"",
+        coord.top_left.x,
+        coord.top_left.y,
+        coord.width(),
+        coord.height()
+    );
+}
+
+/// Converts the parsed pages into an ALTO v4 XML document.
+///
+/// Pages, blocks, lines and words are emitted as `Page`, `TextBlock`, `TextLine` and `String`
+/// elements inside a `PrintSpace` covering the whole page. Positions are given in PDF points,
+/// declared as `pixel` at 72 DPI in the `MeasurementUnit`.
+///
+/// # Arguments
+///
+/// * `pages` - The pages to export.
+///
+/// # Returns
+///
+/// A `String` containing the ALTO XML markup.
+pub fn to_alto(pages: &[Page]) -> String {
+    let mut alto = String::new();
+    alto.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    alto.push_str(
+        "<alto xmlns=\"http://www.loc.gov/standards/alto/ns-v4#\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://www.loc.gov/standards/alto/ns-v4# \
+         http://www.loc.gov/alto/v4/alto-4-2.xsd\">\n",
+    );
+    alto.push_str("<Description>\n<MeasurementUnit>pixel</MeasurementUnit>\n");
+    alto.push_str("<OCRProcessing ID=\"OCR_0\">\n<ocrProcessingStep>\n<processingSoftware>\n");
+    alto.push_str("<softwareName>rsrpp</softwareName>\n</processingSoftware>\n");
+    alto.push_str("</ocrProcessingStep>\n</OCRProcessing>\n</Description>\n<Layout>\n");
+
+    for page in pages.iter() {
+        let p = page.page_number;
+        alto.push_str(&format!(
+            "<Page ID=\"page_{}\" PHYSICAL_IMG_NR=\"{}\" WIDTH=\"{:.2}\" HEIGHT=\"{:.2}\">\n",
+            p, p, page.width, page.height
+        ));
+        let page_coord = Coordinate::from_object(0.0, 0.0, page.width, page.height);
+        alto.push_str(&format!("<PrintSpace {}>\n", alto_position(&page_coord)));
+        for (b, block) in page.blocks.iter().enumerate() {
+            alto.push_str(&format!(
+                "<TextBlock ID=\"block_{}_{}\" {}>\n",
+                p,
+                b,
+                alto_position(&block.coordinate())
+            ));
+            for (l, line) in block.lines.iter().enumerate() {
+                alto.push_str(&format!(
+                    "<TextLine ID=\"line_{}_{}_{}\" {}>\n",
+                    p,
+                    b,
+                    l,
+                    alto_position(&Coordinate::from(line))
+                ));
+                for (w, word) in line.logical_words().into_iter().enumerate() {
+                    if w > 0 {
+                        alto.push_str("<SP/>\n");
+                    }
+                    alto.push_str(&format!(
+                        "<String ID=\"word_{}_{}_{}_{}\" CONTENT=\"{}\" {}/>\n",
+                        p,
+                        b,
+                        l,
+                        w,
+                        escape(word.text.as_str()),
+                        alto_position(&Coordinate::from(word))
+                    ));
+                }
+                alto.push_str("</TextLine>\n");
+            }
+            alto.push_str("</TextBlock>\n");
+        }
+        alto.push_str("</PrintSpace>\n</Page>\n");
+    }
+    alto.push_str("</Layout>\n</alto>\n");
+    return alto;
+}
+
+const PANDOC_API_VERSION: [u32; 3] = [1, 23, 1];
+const BULLETS: [&str; 6] = ["•", "◦", "▪", "‣", "-", "–"];
+
+/// Converts a text into Pandoc inlines (`Str` elements separated by `Space`).
+fn pandoc_inlines(text: &str) -> Vec<Value> {
+    let mut inlines = Vec::new();
+    for (i, word) in text.split_whitespace().enumerate() {
+        if i > 0 {
+            inlines.push(json!({"t": "Space"}));
+        }
+        inlines.push(json!({"t": "Str", "c": word}));
+    }
+    return inlines;
+}
+
+/// Builds a Pandoc `Header` block with an identifier derived from the text.
+fn pandoc_header(level: u32, text: &str) -> Value {
+    let identifier = text
+        .to_lowercase()
+        .split_whitespace()
+        .map(|word| word.chars().filter(|c| c.is_alphanumeric() || *c == '-').collect::<String>())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<String>>()
+        .join("-");
+    return json!({"t": "Header", "c": [level, [identifier, [], []], pandoc_inlines(text)]});
+}
+
+/// Splits a block whose lines start with bullet markers into list items.
+///
+/// Lines without a marker continue the previous item. Returns `None` when the first line is
+/// not a bullet.
+fn bullet_items(block: &Block) -> Option<Vec<String>> {
+    let mut items: Vec<String> = Vec::new();
+    for line in block.lines.iter() {
+        let text = line.get_text();
+        let text = text.trim();
+        let item = BULLETS.iter().find_map(|bullet| {
+            text.strip_prefix(bullet).filter(|rest| rest.starts_with(char::is_whitespace))
+        });
+        match (item, items.last_mut()) {
+            (Some(item), _) => items.push(item.trim().to_string()),
+            (None, Some(last)) => {
+                last.push(' ');
+                last.push_str(text);
+            }
+            (None, None) => return None,
+        }
+    }
+    return Some(items);
+}
+
+/// Converts the parsed pages into a Pandoc JSON AST.
+///
+/// The title and authors of the document are stored in the metadata, and the title is also
+/// emitted as a level-1 `Header`. Each section starts with a level-2 `Header`. Blocks become
+/// `Para` elements, except bulleted blocks (`BulletList`), quotes (`BlockQuote`) and
+/// equations (`Para` holding a display `Math` inline). The header blocks of the first page
+/// (title, authors, affiliations) are not repeated in the body.
+///
+/// # Arguments
+///
+/// * `pages` - The pages to export.
+///
+/// # Returns
+///
+/// A `String` containing the Pandoc JSON document, readable with `pandoc -f json`.
+pub fn to_pandoc_json(pages: &[Page]) -> String {
+    let document = Document::from_slice(pages);
+    let title = document.title();
+    let authors = document.authors();
+    let header_len = document.header_blocks().len();
+    let title_index_regex = regex::Regex::new(r"^\d+(\.\d+)*\.?\s*").unwrap();
+
+    let mut meta = serde_json::Map::new();
+    let mut blocks: Vec<Value> = Vec::new();
+    if let Some(title) = &title {
+        meta.insert(
+            "title".to_string(),
+            json!({"t": "MetaInlines", "c": pandoc_inlines(title)}),
+        );
+        blocks.push(pandoc_header(1, title));
+    }
+    if !authors.is_empty() {
+        let authors = authors
+            .iter()
+            .map(|author| json!({"t": "MetaInlines", "c": pandoc_inlines(author)}))
+            .collect::<Vec<Value>>();
+        meta.insert("author".to_string(), json!({"t": "MetaList", "c": authors}));
+    }
+
+    let mut current_section = String::new();
+    for (page_index, page) in pages.iter().enumerate() {
+        for (block_index, block) in page.blocks.iter().enumerate() {
+            if page_index == 0 && block_index < header_len {
+                continue;
+            }
+            if block.section != current_section {
+                current_section = block.section.clone();
+                if !current_section.is_empty() {
+                    blocks.push(pandoc_header(2, &current_section));
+                }
+            }
+            let text = block.get_text();
+            let heading = title_index_regex.replace(&text, "");
+            if heading.trim().to_lowercase() == current_section.to_lowercase() {
+                continue;
+            }
+            let element = match block.attr {
+                BlockAttr::Equation => json!({"t": "Para", "c": [
+                    {"t": "Math", "c": [{"t": "DisplayMath"}, text]}
+                ]}),
+                BlockAttr::Quote => {
+                    json!({"t": "BlockQuote", "c": [{"t": "Para", "c": pandoc_inlines(&text)}]})
+                }
+                _ => match bullet_items(block) {
+                    Some(items) => {
+                        let items = items
+                            .iter()
+                            .map(|item| json!([{"t": "Plain", "c": pandoc_inlines(item)}]))
+                            .collect::<Vec<Value>>();
+                        json!({"t": "BulletList", "c": items})
+                    }
+                    None => json!({"t": "Para", "c": pandoc_inlines(&text)}),
+                },
+            };
+            blocks.push(element);
+        }
+    }
+
+    let pandoc = json!({
+        "pandoc-api-version": PANDOC_API_VERSION,
+        "meta": meta,
+        "blocks": blocks,
+    });
+    return serde_json::to_string(&pandoc).unwrap();
+}
+
+/// Converts the parsed pages into JSON Lines, one record per block.
+///
+/// Each record holds the page number, the index of the block in the page, its `attr`, its
+/// section, its `tags`, its bounding box as `[x1, y1, x2, y2]` in PDF points and its text.
+///
+/// # Arguments
+///
+/// * `pages` - The pages to export.
+///
+/// # Returns
+///
+/// A `String` with one JSON object per line.
+pub fn to_jsonl(pages: &[Page]) -> String {
+    return to_jsonl_with(pages, CoordinateOrigin::TopLeft);
+}
+
+/// Converts the parsed pages into JSON Lines like `to_jsonl`, with the bounding boxes measured
+/// from the given origin.
+///
+/// With `CoordinateOrigin::BottomLeft`, `y1` is the distance from the bottom of the page to the
+/// bottom edge of the block and `y2` to its top edge, as in PDF user space.
+///
+/// # Arguments
+///
+/// * `pages` - The pages to export.
+/// * `origin` - The corner of the page the bounding boxes are measured from.
+///
+/// # Returns
+///
+/// A `String` with one JSON object per line.
+pub fn to_jsonl_with(pages: &[Page], origin: CoordinateOrigin) -> String {
+    let mut jsonl = String::new();
+    for page in pages {
+        for (index, block) in page.blocks.iter().enumerate() {
+            let (x1, y1, x2, y2) = block.coordinate().to_origin(origin, page.height).as_rect();
+            let record = json!({
+                "page": page.page_number,
+                "block": index,
+                "attr": block.attr,
+                "section": block.section,
+                "tags": block.tags,
+                "bbox": [x1, y1, x2, y2],
+                "text": block.get_text(),
+            });
+            jsonl.push_str(&record.to_string());
+            jsonl.push('\n');
+        }
+    }
+    return jsonl;
+}
+
+/// The `OutputFormat` enum represents the formats the parsed pages can be exported to.
+///
+/// # Variants
+///
+/// * `Text` - The plain text of the document, as returned by `Document::full_text`.
+/// * `Markdown` - Markdown with one heading per section, as returned by `pages2markdown`.
+/// * `Json` - The sections of the document as JSON, as returned by `pages2json`.
+/// * `Hocr` - An hOCR document, as returned by `to_hocr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    Text,
+    Markdown,
+    Json,
+    Hocr,
+}
+
+/// Converts the parsed pages into the given format.
+///
+/// # Arguments
+///
+/// * `pages` - The pages to export.
+/// * `format` - The output format.
+///
+/// # Returns
+///
+/// A `String` containing the exported document.
+pub fn export(pages: &[Page], format: OutputFormat) -> String {
+    return match format {
+        OutputFormat::Text => Document::from_slice(pages).full_text(&FullTextOptions::default()),
+        OutputFormat::Markdown => pages2markdown(pages),
+        OutputFormat::Json => pages2json(pages),
+        OutputFormat::Hocr => to_hocr(pages),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::block_with_lines;
+    use quick_xml::events::Event;
+
+    fn sample_pages() -> Vec<Page> {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.add_block(72.0, 100.0, 200.0, 24.0);
+        page.blocks[0].add_line(72.0, 100.0, 200.0, 10.0);
+        page.blocks[0].lines[0].add_word("Attention".to_string(), 72.0, 100.0, 50.0, 10.0);
+        page.blocks[0].lines[0].add_word("&".to_string(), 126.0, 100.0, 8.0, 10.0);
+        page.blocks[0].lines[0].add_word("<Transformers>".to_string(), 138.0, 100.0, 80.0, 10.0);
+        page.blocks[0].add_line(72.0, 114.0, 120.0, 10.0);
+        page.blocks[0].lines[1].add_word("rock.".to_string(), 72.0, 114.0, 30.0, 10.0);
+        return vec![page];
+    }
+
+    #[test]
+    fn test_to_jsonl() {
+        let mut pages = sample_pages();
+        pages.push(pages[0].clone());
+        pages[1].page_number = 2;
+        pages[1].add_block(72.0, 300.0, 200.0, 10.0);
+        pages[1].blocks[1].add_line(72.0, 300.0, 200.0, 10.0);
+        pages[1].blocks[1].lines[0].add_word("x = 1".to_string(), 72.0, 300.0, 200.0, 10.0);
+        pages[1].blocks[1].attr = BlockAttr::Equation;
+
+        let jsonl = to_jsonl(&pages);
+        let records = jsonl
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<Value>>();
+        let blocks = pages.iter().map(|page| page.blocks.len()).sum::<usize>();
+        assert_eq!(records.len(), blocks);
+        assert_eq!(records[0]["page"], 1);
+        assert_eq!(records[0]["text"], "Attention & <Transformers> rock.");
+        assert_eq!(records[0]["bbox"], json!([72.0, 100.0, 272.0, 124.0]));
+        assert_eq!(records[2]["page"], 2);
+        assert_eq!(records[2]["block"], 1);
+        assert_eq!(records[2]["attr"], "Equation");
+    }
+
+    #[test]
+    fn test_export() {
+        let mut pages = sample_pages();
+        pages[0].blocks[0].section = "Abstract".to_string();
+
+        let text = export(&pages, OutputFormat::Text);
+        assert_eq!(text, "Attention & <Transformers> rock.");
+        let markdown = export(&pages, OutputFormat::Markdown);
+        assert!(markdown.starts_with("## Abstract\n\nAttention & <Transformers> rock."));
+        let json: Value = serde_json::from_str(&export(&pages, OutputFormat::Json)).unwrap();
+        assert_eq!(json[0]["title"], "Abstract");
+        let hocr = export(&pages, OutputFormat::Hocr);
+        assert_eq!(hocr, to_hocr(&pages));
+    }
+
+    #[test]
+    fn test_to_hocr() {
+        let hocr = to_hocr(&sample_pages());
+        let mut reader = quick_xml::Reader::from_str(&hocr);
+        let mut classes = Vec::new();
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e)) => {
+                    if let Some(class) = e.try_get_attribute("class").unwrap() {
+                        classes.push(String::from_utf8_lossy(&class.value).to_string());
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => panic!("invalid hOCR: {}", e),
+                _ => {}
+            }
+        }
+        for class in ["ocr_page", "ocr_carea", "ocr_par", "ocr_line", "ocrx_word"] {
+            assert!(classes.contains(&class.to_string()), "missing {}", class);
+        }
+        assert_eq!(
+            classes.iter().filter(|class| *class == "ocrx_word").count(),
+            4
+        );
+        assert!(hocr.contains("title=\"bbox 72 100 122 110\">Attention</span>"));
+        assert!(hocr.contains("&lt;Transformers&gt;"));
+        assert!(hocr.contains("title=\"bbox 0 0 612 792; ppageno 0\""));
+    }
+
+    #[test]
+    fn test_to_pandoc_json() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.blocks = vec![
+            block_with_lines(
+                72.0,
+                90.0,
+                468.0,
+                17.0,
+                "Abstract",
+                &["Attention Is All You Need"],
+            ),
+            block_with_lines(
+                72.0,
+                140.0,
+                468.0,
+                10.0,
+                "Abstract",
+                &["Ashish Vaswani and Noam Shazeer"],
+            ),
+            block_with_lines(72.0, 200.0, 468.0, 12.0, "Abstract", &["Abstract"]),
+            block_with_lines(
+                72.0,
+                220.0,
+                468.0,
+                10.0,
+                "Abstract",
+                &["We propose the Transformer."],
+            ),
+            block_with_lines(
+                72.0,
+                300.0,
+                468.0,
+                12.0,
+                "Introduction",
+                &["1 Introduction"],
+            ),
+            block_with_lines(
+                72.0,
+                320.0,
+                468.0,
+                10.0,
+                "Introduction",
+                &["• no recurrence,", "• no convolutions", "at all."],
+            ),
+            block_with_lines(
+                72.0,
+                360.0,
+                468.0,
+                10.0,
+                "Introduction",
+                &["y = softmax(x) (1)"],
+            ),
+        ];
+        page.blocks[6].attr = BlockAttr::Equation;
+        let pandoc: Value = serde_json::from_str(&to_pandoc_json(&[page])).unwrap();
+
+        assert_eq!(pandoc["pandoc-api-version"], json!([1, 23, 1]));
+        assert_eq!(pandoc["meta"]["title"]["t"], "MetaInlines");
+        assert_eq!(pandoc["meta"]["author"]["c"].as_array().unwrap().len(), 2);
+
+        let blocks = pandoc["blocks"].as_array().unwrap();
+        let types = blocks.iter().map(|block| block["t"].as_str().unwrap()).collect::<Vec<&str>>();
+        assert_eq!(
+            types,
+            vec!["Header", "Header", "Para", "Header", "BulletList", "Para"]
+        );
+        assert_eq!(blocks[0]["c"][0], 1);
+        assert_eq!(blocks[0]["c"][1][0], "attention-is-all-you-need");
+        let title = blocks[0]["c"][2]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|inline| inline["c"].as_str().unwrap_or(" "))
+            .collect::<String>();
+        assert_eq!(title, "Attention Is All You Need");
+        assert_eq!(blocks[3]["c"][2][0]["c"], "Introduction");
+        assert_eq!(blocks[4]["c"].as_array().unwrap().len(), 2);
+        assert_eq!(blocks[4]["c"][1][0]["c"][6]["c"], "all.");
+        assert_eq!(blocks[5]["c"][0]["t"], "Math");
+    }
+
+    #[test]
+    fn test_to_alto() {
+        let alto = to_alto(&sample_pages());
+        let mut reader = quick_xml::Reader::from_str(&alto);
+        let mut elements = Vec::new();
+        let mut contents = Vec::new();
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                    let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                    if name == "String" {
+                        let content = e.try_get_attribute("CONTENT").unwrap().unwrap();
+                        contents.push(content.unescape_value().unwrap().to_string());
+                    }
+                    elements.push(name);
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => panic!("invalid ALTO: {}", e),
+                _ => {}
+            }
+        }
+        for element in [
+            "alto",
+            "Layout",
+            "Page",
+            "PrintSpace",
+            "TextBlock",
+            "TextLine",
+            "String",
+        ] {
+            assert!(
+                elements.contains(&element.to_string()),
+                "missing {}",
+                element
+            );
+        }
+        assert_eq!(contents, vec!["Attention", "&", "<Transformers>", "rock."]);
+        assert_eq!(
+            elements.iter().filter(|element| *element == "SP").count(),
+            2
+        );
+        assert!(alto.contains(
+            "CONTENT=\"Attention\" HPOS=\"72.00\" VPOS=\"100.00\" WIDTH=\"50.00\" HEIGHT=\"10.00\""
+        ));
+    }
+}
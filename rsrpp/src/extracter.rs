@@ -1,12 +1,19 @@
-use crate::config::ParserConfig;
+use crate::config::{PageNumber, ParserConfig};
 use crate::models::*;
+#[cfg(feature = "native")]
 use opencv::core::{Vec4f, Vector};
+#[cfg(feature = "native")]
 use opencv::imgcodecs;
+#[cfg(feature = "native")]
 use opencv::imgproc;
+#[cfg(feature = "native")]
 use opencv::prelude::*;
 use std::collections::HashMap;
+#[cfg(feature = "native")]
 use std::f64::consts::PI;
+use std::sync::LazyLock;
 
+#[cfg(feature = "native")]
 pub fn extract_tables(image_path: &str, tables: &mut Vec<Coordinate>, width: i32, height: i32) {
     let _src = imgcodecs::imread(image_path, imgcodecs::IMREAD_COLOR).unwrap();
     let mut src = Mat::zeros(width, height, _src.typ()).unwrap().to_mat().unwrap();
@@ -76,8 +83,8 @@ pub fn extract_tables(image_path: &str, tables: &mut Vec<Coordinate>, width: i32
             y_values.push(l.0.y);
             y_values.push(l.1.y);
         }
-        x_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        y_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        x_values.sort_by(|a, b| a.total_cmp(b));
+        y_values.sort_by(|a, b| a.total_cmp(b));
         let x1 = x_values.first().unwrap().clone();
         let x2 = x_values.last().unwrap().clone();
         let y1 = y_values.first().unwrap().clone();
@@ -86,25 +93,137 @@ pub fn extract_tables(image_path: &str, tables: &mut Vec<Coordinate>, width: i32
     }
 }
 
+/// Returns the median of a sample.
+///
+/// The values are sorted, and the median of an even-length sample is the average of the two
+/// middle values.
+///
+/// # Arguments
+///
+/// * `values` - The sample.
+///
+/// # Returns
+///
+/// A `f32` representing the median, or `0.0` for an empty sample.
+pub(crate) fn median(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let middle = sorted.len() / 2;
+    if sorted.len() % 2 == 1 {
+        return sorted[middle];
+    }
+    return (sorted[middle - 1] + sorted[middle]) / 2.0;
+}
+
+/// Returns the given area, or the page bounds when the area has degenerated to a zero width
+/// or height (e.g. when every line shares one x or y), so that overlap tests against it do not
+/// reject every block.
+fn widen_degenerate_area(area: Coordinate, page_width: f32, page_height: f32) -> Coordinate {
+    if area.width() > f32::EPSILON && area.height() > f32::EPSILON {
+        return area;
+    }
+    return Coordinate::from_rect(0.0, 0.0, page_width, page_height);
+}
+
+/// Returns the most common size of the pages of a document.
+///
+/// # Arguments
+///
+/// * `pages` - The pages of the document.
+///
+/// # Returns
+///
+/// A tuple of the width and height shared by most pages, the first such size on a tie, or
+/// `(0.0, 0.0)` for a document without pages.
+pub fn reference_page_size(pages: &[Page]) -> (f32, f32) {
+    let mut counts: Vec<((f32, f32), usize)> = Vec::new();
+    for page in pages {
+        match counts.iter_mut().find(|(size, _)| *size == (page.width, page.height)) {
+            Some((_, count)) => *count += 1,
+            None => counts.push(((page.width, page.height), 1)),
+        }
+    }
+    let mut reference = (0.0, 0.0);
+    let mut max_count = 0;
+    for (size, count) in counts {
+        if count > max_count {
+            reference = size;
+            max_count = count;
+        }
+    }
+    return reference;
+}
+
+/// Scales the text area of a document to a page of another size.
+///
+/// # Arguments
+///
+/// * `text_area` - The text area returned by `get_text_area`, in the coordinates of the
+///   reference page size.
+/// * `reference` - The size returned by `reference_page_size`.
+/// * `page` - The page to fit the text area to.
+///
+/// # Returns
+///
+/// A `Coordinate` covering the same share of `page` as `text_area` does of a reference page.
+pub fn fit_text_area(text_area: &Coordinate, reference: (f32, f32), page: &Page) -> Coordinate {
+    if (page.width, page.height) == reference || reference.0 <= 0.0 || reference.1 <= 0.0 {
+        return text_area.clone();
+    }
+    return text_area.scale(page.width / reference.0, page.height / reference.1);
+}
+
+/// Returns the main text area shared by the pages of a document.
+///
+/// Each edge is the median over the pages of the outermost line edges, so that a few pages
+/// with wider content do not widen the area. Pages whose size differs from the most common
+/// one, such as a foldout or a Letter page in an A4 document, are scaled to it first: the area
+/// is expressed in the coordinates of the most common page size, and `fit_text_area` maps it
+/// onto the other pages. On a single-page document the area is the extent of the lines of that
+/// page. Pages without lines are ignored, and the page bounds are returned when no page has
+/// any line.
+///
+/// # Arguments
+///
+/// * `pages` - The pages of the document.
+///
+/// # Returns
+///
+/// A `Coordinate` representing the text area of the document.
 pub fn get_text_area(pages: &Vec<Page>) -> Coordinate {
     let mut left_values: Vec<f32> = Vec::new();
     let mut right_values: Vec<f32> = Vec::new();
     let mut top_values: Vec<f32> = Vec::new();
     let mut bottom_values: Vec<f32> = Vec::new();
+    let (page_width, page_height) = reference_page_size(pages);
 
     for page in pages {
-        left_values.push(page.left());
-        right_values.push(page.right());
-        top_values.push(page.top());
-        bottom_values.push(page.bottom());
+        if page.blocks.iter().all(|block| block.lines.is_empty()) {
+            continue;
+        }
+        let (sx, sy) = if page.width > 0.0 && page.height > 0.0 {
+            (page_width / page.width, page_height / page.height)
+        } else {
+            (1.0, 1.0)
+        };
+        left_values.push(page.left() * sx);
+        right_values.push(page.right() * sx);
+        top_values.push(page.top() * sy);
+        bottom_values.push(page.bottom() * sy);
+    }
+    if left_values.is_empty() {
+        return Coordinate::from_rect(0.0, 0.0, page_width, page_height);
     }
 
-    let left = sci_rs::stats::median(left_values.iter()).0;
-    let right = sci_rs::stats::median(right_values.iter()).0;
-    let top = sci_rs::stats::median(top_values.iter()).0;
-    let bottom = sci_rs::stats::median(bottom_values.iter()).0;
+    let left = median(&left_values);
+    let right = median(&right_values);
+    let top = median(&top_values);
+    let bottom = median(&bottom_values);
 
-    return Coordinate {
+    let area = Coordinate {
         top_left: Point { x: left, y: top },
         top_right: Point { x: right, y: top },
         bottom_left: Point { x: left, y: bottom },
@@ -113,14 +232,64 @@ pub fn get_text_area(pages: &Vec<Page>) -> Coordinate {
             y: bottom,
         },
     };
+    return widen_degenerate_area(area, page_width, page_height);
+}
+
+/// The minimum number of lines a page needs for its own text area to be estimated.
+const MIN_LINES_FOR_PAGE_TEXT_AREA: usize = 8;
+
+/// Returns the main text area of a single page.
+///
+/// The left and right edges are the 10th and 90th percentiles of the line edges, so that
+/// margin notes and stamps do not widen the area, while the top and bottom edges are those
+/// of the topmost and bottommost lines, as in `get_text_area`.
+///
+/// # Arguments
+///
+/// * `page` - The page to estimate the text area of.
+/// * `fallback` - The area returned when the page has too few lines, usually the document-wide
+///   area returned by `get_text_area`.
+///
+/// # Returns
+///
+/// A `Coordinate` representing the text area of the page.
+pub fn get_page_text_area(page: &Page, fallback: &Coordinate) -> Coordinate {
+    let lines = page.blocks.iter().flat_map(|block| block.lines.iter()).collect::<Vec<&Line>>();
+    if lines.len() < MIN_LINES_FOR_PAGE_TEXT_AREA {
+        return fallback.clone();
+    }
+    let mut left_values = lines.iter().map(|line| line.x).collect::<Vec<f32>>();
+    let mut right_values = lines.iter().map(|line| line.x + line.width).collect::<Vec<f32>>();
+    left_values.sort_by(|a, b| a.total_cmp(b));
+    right_values.sort_by(|a, b| a.total_cmp(b));
+    let percentile =
+        |values: &Vec<f32>, p: f32| values[((values.len() - 1) as f32 * p).round() as usize];
+    let left = percentile(&left_values, 0.1);
+    let right = percentile(&right_values, 0.9);
+
+    let area = Coordinate::from_rect(left, page.top(), right, page.bottom());
+    return widen_degenerate_area(area, page.width, page.height);
 }
 
 pub fn adjst_columns(pages: &mut Vec<Page>, config: &ParserConfig) {
-    let page_width = config.pdf_info.get("page_width").unwrap().parse::<f32>().unwrap();
-    let last_page = config.sections.iter().map(|(page_number, _)| page_number).max().unwrap();
+    if pages.iter().all(|page| page.blocks.is_empty()) {
+        return;
+    }
+    // Pages parsed from a saved pdftotext HTML have no pdfinfo, so their own size is used
+    let page_width = match config.pdf_info.get("page_width") {
+        Some(width) => width.parse::<f32>().unwrap(),
+        None => reference_page_size(pages).0,
+    };
+    // Documents without any detected section (e.g. a one-page letter) use all their pages
+    let last_page = config
+        .sections
+        .iter()
+        .map(|(page_number, _)| *page_number)
+        .max()
+        .unwrap_or(PageNumber::MAX);
     let avg_line_width = pages
         .iter()
-        .filter(|page| page.page_number <= *last_page)
+        .filter(|page| page.page_number <= last_page && !page.blocks.is_empty())
         .map(|page| {
             page.blocks
                 .iter()
@@ -137,6 +306,9 @@ pub fn adjst_columns(pages: &mut Vec<Page>, config: &ParserConfig) {
     let half_width = page_width / 2.2;
     if avg_line_width < page_width / 1.5 {
         for page in pages.iter_mut() {
+            if page.writing_mode == WritingMode::Vertical {
+                continue;
+            }
             page.number_of_columns = 2;
             let mut right_blocks: Vec<Block> = Vec::new();
             let mut left_blocks: Vec<Block> = Vec::new();
@@ -153,14 +325,948 @@ pub fn adjst_columns(pages: &mut Vec<Page>, config: &ParserConfig) {
     }
 }
 
+/// The numbering in front of a section heading, such as "2.", "3 " or "3.1 ".
+static SECTION_NUMBER_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^\d+(?:\.\d+)*(?:\.\s*|\s+)").unwrap());
+
+/// Returns the name of the section started by a heading: its text without numbering.
+///
+/// Sections are named this way by `parser::parse_extract_section_text` and
+/// `parser::recompute_derived`, and headings are recognized against it, so that
+/// "2.1 Background" and an outline entry "Background" give the same section.
+pub(crate) fn section_name(text: &str) -> String {
+    return SECTION_NUMBER_REGEX.replace(text.trim(), "").trim().to_string();
+}
+
+fn is_heading(block: &Block) -> bool {
+    if block.lines.len() != 1 || block.section.is_empty() {
+        return false;
+    }
+    return section_name(&block.get_text()).to_lowercase() == block.section.to_lowercase();
+}
+
+/// The minimum ratio between the long and the short side of a block taken for a drawn rule.
+const RULE_ASPECT_RATIO: f32 = 20.0;
+
+/// Determines whether a block is a drawn rule or page border surfaced as text by pdftotext.
+///
+/// Such a block has no letter or digit, only rule characters such as `|`, `_` or `─`, and is
+/// at least `RULE_ASPECT_RATIO` times longer than thick, horizontally or vertically.
+pub fn is_rule_block(block: &Block) -> bool {
+    let text = block.get_text();
+    if text.chars().any(char::is_alphanumeric) {
+        return false;
+    }
+    let long_side = block.width.max(block.height);
+    let short_side = block.width.min(block.height);
+    return long_side > 0.0 && long_side >= short_side * RULE_ASPECT_RATIO;
+}
+
+/// Removes the drawn rules and page borders of a page, see `is_rule_block`.
+///
+/// Should run before the text area is extracted, as a border or a rule between columns
+/// otherwise widens the text area and hides the gutter of the page.
+pub fn drop_rule_blocks(page: &mut Page) {
+    page.blocks.retain(|block| !is_rule_block(block));
+}
+
+/// The minimum ratio between the height of a drop cap and the font size of its paragraph.
+const DROP_CAP_HEIGHT_RATIO: f32 = 1.8;
+
+/// Merges the drop caps of a page into the paragraphs they start.
+///
+/// A drop cap is a block made of a single letter set much larger than the text, whose
+/// paragraph starts at the same height right to its right. The letter is prepended to the
+/// first word of the paragraph and its block is removed, so that it is neither taken for a
+/// title nor skews the font size and text area of the page.
+///
+/// Expects the blocks of a horizontal page as read from pdftotext, and should run before the
+/// text area is extracted.
+pub fn merge_drop_caps(page: &mut Page) {
+    if page.writing_mode == WritingMode::Vertical {
+        return;
+    }
+    let mut i = 0;
+    while i < page.blocks.len() {
+        let cap = &page.blocks[i];
+        let letter = cap.get_text();
+        let is_letter = cap.lines.len() == 1
+            && cap.lines[0].words.len() == 1
+            && letter.chars().count() == 1
+            && letter.chars().all(char::is_alphabetic);
+        let cap_right = cap.x + cap.width;
+        let paragraph = page.blocks.iter().position(|block| {
+            let font_size = block.font_size();
+            return is_letter
+                && block.lines.len() >= 2
+                && cap.height >= font_size * DROP_CAP_HEIGHT_RATIO
+                && (block.y - cap.y).abs() < font_size
+                && block.x >= cap_right - font_size * 0.5
+                && block.x - cap_right < font_size * 2.0;
+        });
+        match paragraph {
+            Some(j) => {
+                let block = &mut page.blocks[j];
+                if let Some(word) = block.lines[0].words.first_mut() {
+                    word.text.insert_str(0, &letter);
+                }
+                block.invalidate_text();
+                page.blocks.remove(i);
+            }
+            None => i += 1,
+        }
+    }
+}
+
+/// Merges consecutive blocks of a page that pdftotext split in the middle of a paragraph.
+///
+/// Two blocks are merged when they belong to the same section and column, share the same font
+/// size, are left-aligned and the lower one starts right below the upper one. Section headings
+/// are never merged with their neighbors, and a block whose last line is a short line ending a
+/// sentence is considered the end of a paragraph.
+///
+/// Expects the section names to be assigned and the cross-column blocks to be split, otherwise
+/// headings are merged into their paragraphs and blocks of both columns into one another.
+pub fn merge_adjacent_blocks(page: &mut Page) {
+    if page.writing_mode == WritingMode::Vertical {
+        return;
+    }
+    let half_width = page.width / 2.2;
+    let mut blocks: Vec<Block> = Vec::new();
+    for block in page.blocks.drain(..) {
+        if let Some(upper) = blocks.last_mut() {
+            let font_size = upper.font_size();
+            let upper_coord = &upper.coordinate();
+            let lower_coord = &block.coordinate();
+            let gap = lower_coord.top_left.y - upper_coord.bottom_left.y;
+            let indent = (lower_coord.top_left.x - upper_coord.top_left.x).abs();
+            let same_column = page.number_of_columns < 2
+                || (upper_coord.top_left.x > half_width) == (lower_coord.top_left.x > half_width);
+            let paragraph_end = upper.lines.last().is_some_and(|line| {
+                line.get_text().ends_with(".") && line.width < upper.width * 0.8
+            });
+
+            if same_column
+                && upper.section == block.section
+                && !is_heading(upper)
+                && !is_heading(&block)
+                && !paragraph_end
+                && (block.font_size() - font_size).abs() < 0.5
+                && indent < font_size * 0.5
+                && gap > -font_size * 0.5
+                && gap < font_size
+            {
+                upper.merge(block);
+                continue;
+            }
+        }
+        blocks.push(block);
+    }
+    page.blocks = blocks;
+}
+
+fn block_from_lines(lines: Vec<Line>, section: &str) -> Block {
+    let coord = lines.iter().map(Coordinate::from).reduce(|a, b| a.union(&b)).unwrap();
+    let mut block = Block::new(
+        coord.top_left.x,
+        coord.top_left.y,
+        coord.width(),
+        coord.height(),
+    );
+    block.lines = lines;
+    block.section = section.to_string();
+    return block;
+}
+
+fn line_from_words(words: Vec<Word>) -> Line {
+    let coord = words.iter().map(Coordinate::from).reduce(|a, b| a.union(&b)).unwrap();
+    let mut line = Line::new(
+        coord.top_left.x,
+        coord.top_left.y,
+        coord.width(),
+        coord.height(),
+    );
+    line.words = words;
+    return line;
+}
+
+fn split_at_gutter(block: &Block, gutter: f32, min_gap: f32) -> Option<(Block, Block)> {
+    let words = block.lines.iter().flat_map(|line| line.words.iter()).collect::<Vec<&Word>>();
+    if words.iter().any(|word| word.x < gutter && word.x + word.width > gutter) {
+        return None;
+    }
+    let left_edge = words
+        .iter()
+        .filter(|word| word.x + word.width <= gutter)
+        .map(|word| word.x + word.width)
+        .reduce(f32::max)?;
+    let right_edge =
+        words.iter().filter(|word| word.x >= gutter).map(|word| word.x).reduce(f32::min)?;
+    if right_edge - left_edge < min_gap {
+        return None;
+    }
+
+    let mut left_lines: Vec<Line> = Vec::new();
+    let mut right_lines: Vec<Line> = Vec::new();
+    for line in block.lines.iter() {
+        let (left_words, right_words): (Vec<Word>, Vec<Word>) =
+            line.words.iter().cloned().partition(|word| word.x < gutter);
+        if !left_words.is_empty() {
+            left_lines.push(line_from_words(left_words));
+        }
+        if !right_words.is_empty() {
+            right_lines.push(line_from_words(right_words));
+        }
+    }
+    return Some((
+        block_from_lines(left_lines, &block.section),
+        block_from_lines(right_lines, &block.section),
+    ));
+}
+
+/// Splits the blocks of a two-column page whose words belong to both columns.
+///
+/// A block is split when its words fall on both sides of the page center with a gutter between
+/// them and no word crossing the center. The resulting blocks are reordered so that the left
+/// column still precedes the right column, as done by `adjst_columns`.
+///
+/// Expects `number_of_columns` to be set, by `adjst_columns`; single-column pages are left
+/// untouched.
+pub fn split_cross_column_blocks(page: &mut Page) {
+    if page.number_of_columns < 2 || page.writing_mode == WritingMode::Vertical {
+        return;
+    }
+    let gutter = page.width / 2.0;
+    let min_gap = page.width * 0.02;
+    let half_width = page.width / 2.2;
+    let mut left_blocks: Vec<Block> = Vec::new();
+    let mut right_blocks: Vec<Block> = Vec::new();
+    for block in page.blocks.drain(..) {
+        let blocks = match split_at_gutter(&block, gutter, min_gap) {
+            Some((left, right)) => vec![left, right],
+            None => vec![block],
+        };
+        for block in blocks {
+            if half_width < block.x {
+                right_blocks.push(block);
+            } else {
+                left_blocks.push(block);
+            }
+        }
+    }
+    left_blocks.append(&mut right_blocks);
+    page.blocks = left_blocks;
+}
+
+/// Removes the words of a page duplicated by an overlapping text layer.
+///
+/// A word is dropped when an earlier word of the page has the same text and a bounding box
+/// overlapping it with an IoU above `iou_threshold`. Lines and blocks left without words are
+/// removed as well, and the boxes of the others are shrunk to their remaining words.
+///
+/// Should run first, on the pages as read from pdftotext, since the other passes take the
+/// duplicated words for regular text.
+pub fn dedup_overlapping_words(page: &mut Page, iou_threshold: f32) {
+    let mut seen: HashMap<String, Vec<Coordinate>> = HashMap::new();
+    for block in page.blocks.iter_mut() {
+        let count = block.lines.iter().map(|line| line.words.len()).sum::<usize>();
+        for line in block.lines.iter_mut() {
+            line.words.retain(|word| {
+                let coord = Coordinate::from(word);
+                let coords = seen.entry(word.text.clone()).or_default();
+                if coords.iter().any(|other| other.iou(&coord) > iou_threshold) {
+                    return false;
+                }
+                coords.push(coord);
+                true
+            });
+        }
+        block.lines.retain(|line| !line.words.is_empty());
+        if block.lines.iter().map(|line| line.words.len()).sum::<usize>() < count {
+            block.fit_to_words();
+        }
+        block.invalidate_text();
+    }
+    page.blocks.retain(|block| !block.lines.is_empty());
+}
+
+/// Suppresses redundant blocks among heavily overlapping candidates.
+///
+/// Blocks are visited from the largest area to the smallest; a block is dropped when its
+/// bounding box overlaps an already kept block with an IoU above `iou_threshold`. The kept
+/// blocks are returned in their original order.
+///
+/// # Arguments
+///
+/// * `blocks` - The candidate blocks.
+/// * `iou_threshold` - The IoU above which two blocks are considered duplicates.
+///
+/// # Returns
+///
+/// A vector of the `Block`s that survived the suppression.
+pub fn nms_blocks(blocks: Vec<Block>, iou_threshold: f32) -> Vec<Block> {
+    let coords = blocks.iter().map(Coordinate::from).collect::<Vec<Coordinate>>();
+    let mut order = (0..blocks.len()).collect::<Vec<usize>>();
+    order.sort_by(|a, b| {
+        let area_a = coords[*a].width() * coords[*a].height();
+        let area_b = coords[*b].width() * coords[*b].height();
+        area_b.total_cmp(&area_a)
+    });
+    let mut keep = vec![false; blocks.len()];
+    let mut kept: Vec<usize> = Vec::new();
+    for i in order {
+        if kept.iter().all(|j| coords[*j].iou(&coords[i]) <= iou_threshold) {
+            keep[i] = true;
+            kept.push(i);
+        }
+    }
+    return blocks
+        .into_iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|(block, _)| block)
+        .collect();
+}
+
+/// An equation number closing a block, such as "(3)" or "(2.1a)".
+static EQUATION_NUMBER_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"\(\d+(\.\d+)?[a-z]?\)$").unwrap());
+
+/// A word or block holding only an equation number, such as "(3)" or "(2.1.4b)".
+static EQUATION_LABEL_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^\(\d+(\.\d+)*[a-z]?\)$").unwrap());
+
+/// Determines whether a block looks like a display equation.
+///
+/// A block of at most three lines is an equation when it contains a math operator or relation,
+/// such as "=", "+" or "∑", and either most of its glyphs are not letters or it ends with an
+/// equation number such as "(3)". Rows of numbers and reference entries, which have no
+/// operators, are left alone.
+pub fn is_equation(block: &Block) -> bool {
+    if block.lines.len() > 3 {
+        return false;
+    }
+    let text = block.get_text();
+    let glyphs = text.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>();
+    if glyphs.len() < 3 || glyphs.iter().all(|c| c.is_ascii_digit() || *c == '.') {
+        return false;
+    }
+    if !glyphs.iter().any(|c| is_math_operator(*c)) {
+        return false;
+    }
+    let symbols = glyphs.iter().filter(|c| !c.is_alphabetic()).count();
+    let numbered = EQUATION_NUMBER_REGEX.is_match(text.trim());
+    return symbols as f32 / glyphs.len() as f32 > 0.4 || numbered;
+}
+
+/// The minimum ratio between the font size of the paper title and the normal font size.
+const PAPER_TITLE_FONT_SIZE_RATIO: f32 = 1.3;
+
+/// Determines whether a block is the title of the paper.
+///
+/// The title is a block of up to three lines in the top third of the first page, set in a font
+/// clearly larger than the text, so that titles wrapped over several lines are kept whole.
+fn is_paper_title(block: &Block, ctx: &ClassifyContext) -> bool {
+    return ctx.page_number == 1
+        && (1..=3).contains(&block.lines.len())
+        && ctx.font_size > 0.0
+        && block.font_size() >= ctx.font_size * PAPER_TITLE_FONT_SIZE_RATIO
+        && block.y < ctx.page_height / 3.0;
+}
+
+/// Determines whether a block is the title of the paper from its position only.
+///
+/// The title is the first block of the text area of the first page, of up to three lines lying
+/// in the top third of the page, whose lines are all centered in the text area. This catches
+/// titles set in the same font size as the text.
+fn is_centered_title(block: &Block, ctx: &ClassifyContext) -> bool {
+    let left = ctx.text_area.top_left.x;
+    let right = ctx.text_area.top_right.x;
+    let center = (left + right) / 2.0;
+    let tolerance = ctx.font_size.max(1.0);
+    return ctx.page_number == 1
+        && (1..=3).contains(&block.lines.len())
+        && block.y <= ctx.body_top + tolerance * 0.5
+        && block.y < ctx.page_height / 3.0
+        && block.width < (right - left) * 0.9
+        && block.lines.iter().all(|line| (line.x + line.width / 2.0 - center).abs() <= tolerance);
+}
+
+/// An entry of a printed table of contents: its title, a dot leader and its page number.
+static TOC_ENTRY_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^(.*?\S)\s*(?:[.·…]\s*){3,}(\d+)$").unwrap());
+
+/// Parses a line of a printed table of contents such as "2.1 Background . . . . 5".
+///
+/// # Arguments
+///
+/// * `text` - The text of the line.
+///
+/// # Returns
+///
+/// The title and page number of the entry, or `None` when the line has no dot leader followed
+/// by a page number.
+pub fn parse_toc_entry(text: &str) -> Option<(String, u32)> {
+    let caps = TOC_ENTRY_REGEX.captures(text.trim())?;
+    return Some((caps[1].to_string(), caps[2].parse::<u32>().ok()?));
+}
+
+/// Returns the entries of a table of contents block.
+///
+/// # Arguments
+///
+/// * `block` - The block to parse, usually tagged with `BlockAttr::Toc`.
+///
+/// # Returns
+///
+/// A vector of `(title, page)` tuples, one for each line that parses as an entry.
+pub fn toc_entries(block: &Block) -> Vec<(String, u32)> {
+    return block.lines.iter().filter_map(|line| parse_toc_entry(&line.get_text())).collect();
+}
+
+fn is_toc(block: &Block) -> bool {
+    let entries = toc_entries(block).len();
+    return entries >= 2 && entries * 2 >= block.lines.len();
+}
+
+/// Tags the blocks of a page holding a printed table of contents with `BlockAttr::Toc`.
+///
+/// A block is a table of contents when at least two of its lines, and at least half of them,
+/// end with a dot leader followed by a page number.
+pub fn detect_toc(page: &mut Page) {
+    for block in page.blocks.iter_mut() {
+        if block.attr == BlockAttr::Text && is_toc(block) {
+            block.attr = BlockAttr::Toc;
+        }
+    }
+}
+
+/// The start of a figure or table caption, such as "Figure 1:" or "Table 2.".
+static CAPTION_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^(?:Figure|Fig\.|Table|Tab\.)\s*\d+[.:]").unwrap());
+
+/// Determines whether a block is the caption of a figure or a table.
+///
+/// A caption starts with "Figure", "Fig.", "Table" or "Tab." followed by a number and a colon
+/// or a period, such as "Figure 1:" or "Table 2.".
+pub fn is_caption(block: &Block) -> bool {
+    return CAPTION_REGEX.is_match(block.get_text().trim_start());
+}
+
+/// Tags the figure and table captions of a page with `BlockAttr::Caption`.
+pub fn detect_captions(page: &mut Page) {
+    for block in page.blocks.iter_mut() {
+        if block.attr == BlockAttr::Text && is_caption(block) {
+            block.attr = BlockAttr::Caption;
+        }
+    }
+}
+
+/// Tags the display equations of a page with `BlockAttr::Equation`.
+pub fn detect_equations(page: &mut Page) {
+    for block in page.blocks.iter_mut() {
+        if block.attr == BlockAttr::Text && is_equation(block) {
+            block.attr = BlockAttr::Equation;
+        }
+    }
+}
+
+/// Returns the equation number ending a line, such as "(3)", when it is set apart from the
+/// equation by more than two font sizes.
+fn line_equation_label(line: &Line) -> Option<String> {
+    let [.., previous, last] = line.words.as_slice() else {
+        return None;
+    };
+    let gap = last.x - (previous.x + previous.width);
+    if !EQUATION_LABEL_REGEX.is_match(&last.text) || gap <= line.dominant_font_size() * 2.0 {
+        return None;
+    }
+    return Some(last.text.clone());
+}
+
+/// Attaches the equation numbers of a page, such as "(3)", to their equations.
+///
+/// A number is either the last word of a line of an `Equation` block, set apart from the
+/// equation by more than two font sizes, or a block holding only the number that lies right of
+/// an equation within its vertical extent; such a block is merged into the equation. The number
+/// is recorded as the `label` of the equation block, the first one being kept when several
+/// lines are numbered.
+pub fn detect_equation_labels(page: &mut Page) {
+    let mut i = 0;
+    while i < page.blocks.len() {
+        let label = page.blocks[i].get_text().trim().to_string();
+        if !EQUATION_LABEL_REGEX.is_match(&label) {
+            i += 1;
+            continue;
+        }
+        let number = &page.blocks[i];
+        let center = number.y + number.height / 2.0;
+        let equation = page.blocks.iter().position(|block| {
+            block.attr == BlockAttr::Equation
+                && !EQUATION_LABEL_REGEX.is_match(block.get_text().trim())
+                && block.x + block.width <= number.x
+                && block.y <= center
+                && center <= block.y + block.height
+        });
+        match equation {
+            Some(j) => {
+                let number = page.blocks.remove(i);
+                let equation = &mut page.blocks[if j > i { j - 1 } else { j }];
+                equation.merge(number);
+                equation.label.get_or_insert(label);
+            }
+            None => i += 1,
+        }
+    }
+    for block in page.blocks.iter_mut() {
+        if block.attr == BlockAttr::Equation && block.label.is_none() {
+            block.label = block.lines.iter().find_map(line_equation_label);
+        }
+    }
+}
+
+fn is_quote(block: &Block, ctx: &ClassifyContext) -> bool {
+    if block.lines.len() < 2 || is_heading(block) {
+        return false;
+    }
+    let (column_left, column_right) = if ctx.columns.len() == 2 && block.x > ctx.page_width / 2.2 {
+        ctx.columns[1]
+    } else {
+        ctx.columns[0]
+    };
+    let indent = block.font_size() * 1.5;
+    let left_indented = block.lines.iter().all(|line| line.x - column_left > indent);
+    let right_indented =
+        block.lines.iter().all(|line| column_right - (line.x + line.width) > indent);
+    return left_indented && right_indented;
+}
+
+/// Tags the indented quotations of a page with `BlockAttr::Quote`.
+///
+/// The body column edges come from the text area of the document: on two-column pages the
+/// inner edges are taken from the lines of each column. A `Text` block of at least two lines
+/// is a quote when it is indented on both sides: every line starts more than 1.5 font sizes
+/// right of the column's left edge and ends more than 1.5 font sizes before its right edge.
+/// Blocks of short lines flush with the left edge, such as lists or addresses, are not quotes.
+///
+/// # Arguments
+///
+/// * `page` - The page whose blocks are tagged.
+/// * `text_area` - The body text area, as returned by `get_text_area`.
+pub fn detect_quotes(page: &mut Page, text_area: &Coordinate) {
+    let ctx = ClassifyContext::new(page, text_area);
+    for block in page.blocks.iter_mut() {
+        if block.attr == BlockAttr::Text && is_quote(block, &ctx) {
+            block.attr = BlockAttr::Quote;
+        }
+    }
+}
+
+/// The `ClassifyContext` struct holds the page-level measures a `BlockClassifier` can rely on.
+///
+/// # Fields
+///
+/// * `font_size` - The median font size of the lines of the page, `0.0` for an empty page.
+/// * `text_area` - The body text area, as returned by `get_text_area`.
+/// * `columns` - The left and right edges of each body column, from left to right.
+/// * `page_width` - The width of the page.
+/// * `page_height` - The height of the page.
+/// * `page_number` - The number of the page, starting at `1`.
+/// * `body_top` - The y-coordinate of the top of the first block overlapping the text area, the
+///   page height when there is none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassifyContext {
+    pub font_size: f32,
+    pub text_area: Coordinate,
+    pub columns: Vec<(f32, f32)>,
+    pub page_width: f32,
+    pub page_height: f32,
+    pub page_number: PageNumber,
+    pub body_top: f32,
+}
+
+impl ClassifyContext {
+    /// Creates the context of a page.
+    ///
+    /// On two-column pages the inner column edges are taken from the lines of each column.
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page whose blocks are classified.
+    /// * `text_area` - The body text area, as returned by `get_text_area`.
+    ///
+    /// # Returns
+    ///
+    /// A new `ClassifyContext` instance.
+    pub fn new(page: &Page, text_area: &Coordinate) -> ClassifyContext {
+        return ClassifyContext::with_font_size_threshold(page, text_area, 0.0);
+    }
+
+    /// Creates the context of a page, sampling the font size only from the lines lying in the
+    /// text area.
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page whose blocks are classified.
+    /// * `text_area` - The body text area, as returned by `get_text_area`.
+    /// * `font_size_threshold` - The minimum fraction of a line inside the text area for its
+    ///   font size to be sampled, see `TextAreaThresholds`. All lines are sampled when none
+    ///   qualifies.
+    ///
+    /// # Returns
+    ///
+    /// A new `ClassifyContext` instance.
+    pub fn with_font_size_threshold(
+        page: &Page,
+        text_area: &Coordinate,
+        font_size_threshold: f32,
+    ) -> ClassifyContext {
+        let lines = page.blocks.iter().flat_map(|block| block.lines.iter()).collect::<Vec<&Line>>();
+        let mut font_sizes = lines
+            .iter()
+            .filter(|line| {
+                let ratio = Coordinate::from(**line).overlap_ratio(text_area);
+                ratio > 0.0 && ratio >= font_size_threshold
+            })
+            .map(|line| line.dominant_font_size())
+            .collect::<Vec<f32>>();
+        if font_sizes.is_empty() {
+            font_sizes = lines.iter().map(|line| line.dominant_font_size()).collect::<Vec<f32>>();
+        }
+        let font_size = median(&font_sizes);
+
+        let half_width = page.width / 2.2;
+        let mut columns = vec![(text_area.top_left.x, text_area.top_right.x)];
+        if page.number_of_columns == 2 {
+            let (left_lines, right_lines): (Vec<&Line>, Vec<&Line>) =
+                lines.iter().partition(|line| line.x <= half_width);
+            let left_right = left_lines.iter().map(|line| line.x + line.width).fold(0.0, f32::max);
+            let right_left = right_lines.iter().map(|line| line.x).fold(f32::MAX, f32::min);
+            columns = vec![
+                (text_area.top_left.x, left_right.max(text_area.top_left.x)),
+                (right_left.min(text_area.top_right.x), text_area.top_right.x),
+            ];
+        }
+        let body_top = page
+            .blocks
+            .iter()
+            .filter(|block| block.coordinate().overlap_ratio(text_area) > 0.0)
+            .map(|block| block.y)
+            .fold(page.height, f32::min);
+        return ClassifyContext {
+            font_size,
+            text_area: text_area.clone(),
+            columns,
+            page_width: page.width,
+            page_height: page.height,
+            page_number: page.page_number,
+            body_top,
+        };
+    }
+
+    /// Re-estimates the font size of the context from the body text of a classified page.
+    ///
+    /// Only the lines of `Text` blocks are sampled, so that headings, equations and other
+    /// non-body lines do not skew the estimate. The context is returned unchanged when the page
+    /// has no `Text` block.
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page whose blocks were classified with this context.
+    ///
+    /// # Returns
+    ///
+    /// A new `ClassifyContext` instance with the refined font size.
+    pub fn refined(&self, page: &Page) -> ClassifyContext {
+        let font_sizes = page
+            .blocks
+            .iter()
+            .filter(|block| block.attr == BlockAttr::Text)
+            .flat_map(|block| block.lines.iter())
+            .map(|line| line.dominant_font_size())
+            .collect::<Vec<f32>>();
+        let mut ctx = self.clone();
+        if font_sizes.is_empty() {
+            return ctx;
+        }
+        ctx.font_size = median(&font_sizes);
+        return ctx;
+    }
+}
+
+/// The `BlockClassifier` trait decides the `BlockAttr` of each block of a page.
+///
+/// Implement it to plug domain-specific rules into the parser through
+/// `ParserConfig::classifier`.
+pub trait BlockClassifier: std::fmt::Debug + Send + Sync {
+    /// Returns the kind of content held by a block.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - The block to classify.
+    /// * `ctx` - The measures of the page the block belongs to.
+    ///
+    /// # Returns
+    ///
+    /// The `BlockAttr` of the block.
+    fn classify(&self, block: &Block, ctx: &ClassifyContext) -> BlockAttr;
+}
+
+/// The `PostProcessor` trait is a custom pass run on each page at the end of the parse.
+///
+/// Implement it to add domain-specific steps through `ParserConfig::post_processors`, such as
+/// tagging the blocks matching a pattern with one of the `Block::tags`, without extending
+/// `BlockAttr`.
+pub trait PostProcessor: std::fmt::Debug + Send + Sync {
+    /// Processes a parsed page, in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page, with its blocks classified and in top-left coordinates.
+    fn process(&self, page: &mut Page);
+}
+
+/// The `TitleDetection` enum selects the heuristics recognizing the title of the paper.
+///
+/// # Variants
+///
+/// * `Font` - The title is set in a font clearly larger than the text (the default).
+/// * `Position` - The title is the first block of the first page, centered in the text area.
+/// * `Either` - The title satisfies the font or the position heuristic.
+/// * `Both` - The title satisfies the font and the position heuristics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TitleDetection {
+    #[default]
+    Font,
+    Position,
+    Either,
+    Both,
+}
+
+/// The `DefaultClassifier` struct applies the built-in heuristics, in order: section headings
+/// (single-line blocks whose text, without its numbering, names their section, see
+/// `section_name`) and the title of the paper, possibly wrapped over several lines, figure
+/// and table captions (`detect_captions`), tables of contents (`detect_toc`), equations
+/// (`detect_equations`) and quotations (`detect_quotes`); any other block is `Text`.
+///
+/// # Fields
+///
+/// * `title_detection` - The heuristics recognizing the title of the paper.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefaultClassifier {
+    pub title_detection: TitleDetection,
+}
+
+impl DefaultClassifier {
+    fn is_paper_title(&self, block: &Block, ctx: &ClassifyContext) -> bool {
+        return match self.title_detection {
+            TitleDetection::Font => is_paper_title(block, ctx),
+            TitleDetection::Position => is_centered_title(block, ctx),
+            TitleDetection::Either => is_paper_title(block, ctx) || is_centered_title(block, ctx),
+            TitleDetection::Both => is_paper_title(block, ctx) && is_centered_title(block, ctx),
+        };
+    }
+}
+
+impl BlockClassifier for DefaultClassifier {
+    fn classify(&self, block: &Block, ctx: &ClassifyContext) -> BlockAttr {
+        if is_heading(block) || self.is_paper_title(block, ctx) {
+            return BlockAttr::Title;
+        } else if is_caption(block) {
+            return BlockAttr::Caption;
+        } else if is_toc(block) {
+            return BlockAttr::Toc;
+        } else if is_equation(block) {
+            return BlockAttr::Equation;
+        } else if is_quote(block, ctx) {
+            return BlockAttr::Quote;
+        }
+        return BlockAttr::Text;
+    }
+}
+
+/// Sets the `attr` of every block of a page with the given classifier.
+///
+/// Only the blocks still tagged `Text` are classified, so that the attributes set by earlier
+/// passes are kept. They are classified twice: a first pass uses the font size of all the
+/// lines, then the font size is refined from the lines of the `Text` blocks only (see
+/// `ClassifyContext::refined`) and the same blocks are classified again.
+///
+/// # Arguments
+///
+/// * `page` - The page whose blocks are classified.
+/// * `text_area` - The body text area, as returned by `get_text_area`.
+/// * `classifier` - The classifier deciding the attribute of each block.
+/// * `font_size_threshold` - The minimum fraction of a line inside the text area for its font
+///   size to be sampled, see `TextAreaThresholds`.
+pub fn classify_blocks(
+    page: &mut Page,
+    text_area: &Coordinate,
+    classifier: &dyn BlockClassifier,
+    font_size_threshold: f32,
+) {
+    let ctx = ClassifyContext::with_font_size_threshold(page, text_area, font_size_threshold);
+    let unclassified =
+        page.blocks.iter().map(|block| block.attr == BlockAttr::Text).collect::<Vec<bool>>();
+    for (block, _) in page.blocks.iter_mut().zip(&unclassified).filter(|(_, text)| **text) {
+        block.attr = classifier.classify(block, &ctx);
+    }
+    let ctx = ctx.refined(page);
+    for (block, _) in page.blocks.iter_mut().zip(&unclassified).filter(|(_, text)| **text) {
+        block.attr = classifier.classify(block, &ctx);
+    }
+}
+
+/// Determines whether the text of a page is written vertically.
+///
+/// The page is vertical when vertical lines, see `Line::is_vertical`, hold the majority of its
+/// characters.
+pub fn is_vertical_writing(page: &Page) -> bool {
+    let mut vertical_chars = 0;
+    let mut total_chars = 0;
+    for line in page.blocks.iter().flat_map(|block| block.lines.iter()) {
+        let chars = line.get_text().chars().filter(|c| !c.is_whitespace()).count();
+        total_chars += chars;
+        if chars > 1 && line.is_vertical() {
+            vertical_chars += chars;
+        }
+    }
+    return total_chars > 0 && vertical_chars * 2 > total_chars;
+}
+
+/// Rebuilds the reading order of a vertically written page.
+///
+/// The page is tagged with `WritingMode::Vertical`, words of each line are read top-to-bottom
+/// and joined without separators, the lines of each block right-to-left, and the blocks are
+/// ordered by band from the top of the page, right-to-left inside a band. Horizontal pages are
+/// left untouched.
+///
+/// Should run before the other passes, which skip vertical pages or assume a horizontal
+/// reading order.
+pub fn reorder_vertical_text(page: &mut Page) {
+    if !is_vertical_writing(page) {
+        return;
+    }
+    page.writing_mode = WritingMode::Vertical;
+    for block in page.blocks.iter_mut() {
+        for line in block.lines.iter_mut() {
+            line.words.sort_by(|a, b| a.y.total_cmp(&b.y));
+        }
+        block.lines.sort_by(|a, b| (b.x + b.width).total_cmp(&(a.x + a.width)));
+        block.invalidate_text();
+    }
+
+    // Blocks whose vertical extents overlap belong to the same band
+    let mut bands: Vec<(f32, f32, Vec<Block>)> = Vec::new();
+    let mut blocks = page.blocks.drain(..).collect::<Vec<Block>>();
+    blocks.sort_by(|a, b| a.y.total_cmp(&b.y));
+    for block in blocks {
+        match bands.last_mut() {
+            Some((_, bottom, members)) if block.y < *bottom => {
+                *bottom = bottom.max(block.y + block.height);
+                members.push(block);
+            }
+            _ => bands.push((block.y, block.y + block.height, vec![block])),
+        }
+    }
+    for (_, _, mut members) in bands {
+        members.sort_by(|a, b| (b.x + b.width).total_cmp(&(a.x + a.width)));
+        page.blocks.extend(members);
+    }
+}
+
+/// Marks the paragraphs continued from the bottom of a page to the top of the next one.
+///
+/// The first block of a page, in reading order, is marked `continued` when it and the last
+/// block of the previous page are both `Text` blocks of the same font size, lie in the last
+/// and first column of pages with the same number of columns, and the first block does not
+/// start with a capital letter. `Document::full_text` then joins both parts of the paragraph.
+///
+/// Expects the blocks to be classified, so that headings are not taken for continuations.
+///
+/// # Arguments
+///
+/// * `pages` - The pages of the document, in order.
+pub fn detect_page_continuations(pages: &mut [Page]) {
+    for i in 1..pages.len() {
+        let (previous, next) = pages.split_at_mut(i);
+        let (previous, next) = (&previous[i - 1], &mut next[0]);
+        for block in next.blocks.iter_mut() {
+            block.continued = false;
+        }
+        let horizontal = previous.writing_mode == WritingMode::Horizontal
+            && next.writing_mode == WritingMode::Horizontal;
+        if !horizontal || previous.number_of_columns != next.number_of_columns {
+            continue;
+        }
+        let previous_columns = previous.column_areas();
+        let next_columns = next.column_areas();
+        let last =
+            previous.blocks.iter().max_by_key(|block| block.reading_order_key(&previous_columns));
+        let first = next
+            .blocks
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, block)| block.reading_order_key(&next_columns));
+        let (Some(last), Some((first_index, first))) = (last, first) else {
+            continue;
+        };
+        let continued = last.attr == BlockAttr::Text
+            && first.attr == BlockAttr::Text
+            && (last.font_size() - first.font_size()).abs() < 0.5
+            && last.reading_order_key(&previous_columns).0 == previous_columns.len() - 1
+            && first.reading_order_key(&next_columns).0 == 0
+            && first.get_text().chars().next().is_some_and(|c| !c.is_uppercase());
+        next.blocks[first_index].continued = continued;
+    }
+}
+
+/// Runs the page-level post-processing passes that only need the pages as read from
+/// pdftotext, in their recommended order.
+///
+/// The passes are `dedup_overlapping_words` (when `config.dedup_words` is set, with
+/// `config.dedup_iou_threshold`), `reorder_vertical_text`, `drop_rule_blocks` and
+/// `merge_drop_caps`. `split_cross_column_blocks` needs the columns found by `adjst_columns`
+/// and `merge_adjacent_blocks` needs the sections assigned, so both are left to the caller once
+/// these steps have run, as `parser::parse` and `parser::parse_html` do.
+///
+/// # Arguments
+///
+/// * `pages` - The pages to post-process, in place.
+/// * `config` - The parser configuration.
+pub fn default_postprocess(pages: &mut [Page], config: &ParserConfig) {
+    for page in pages.iter_mut() {
+        if config.dedup_words {
+            dedup_overlapping_words(page, config.dedup_iou_threshold);
+        }
+        reorder_vertical_text(page);
+        drop_rule_blocks(page);
+        merge_drop_caps(page);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::config::ParserConfig;
+    #[cfg(feature = "download")]
     use crate::converter::pdf2html;
+    #[cfg(feature = "download")]
     use crate::extracter::adjst_columns;
-    use crate::models::{Coordinate, Section};
+    use crate::extracter::{
+        classify_blocks, dedup_overlapping_words, default_postprocess, detect_captions,
+        detect_equation_labels, detect_equations, detect_quotes, detect_toc, drop_rule_blocks,
+        is_rule_block, median, merge_adjacent_blocks, merge_drop_caps, nms_blocks, parse_toc_entry,
+        reorder_vertical_text, section_name, split_cross_column_blocks, toc_entries,
+        BlockClassifier, ClassifyContext, DefaultClassifier, TitleDetection,
+    };
+    #[cfg(feature = "download")]
+    use crate::models::Section;
+    use crate::models::{Block, BlockAttr, Coordinate, Line, Page, WritingMode};
+    #[cfg(feature = "download")]
     use crate::parser::parse_extract_textarea;
+    #[cfg(feature = "download")]
     use crate::parser::parse_html2pages;
+    use crate::test_utils::block_with_lines;
 
     #[test]
     fn test_coordinate_is_intercept() {
@@ -182,6 +1288,784 @@ mod tests {
         assert!(!b.is_intercept(&f));
     }
 
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[]), 0.0);
+        assert_eq!(median(&[7.0]), 7.0);
+        assert_eq!(median(&[540.0, 72.0, 300.0]), 300.0);
+        assert_eq!(median(&[540.0, 72.0, 300.0, 100.0]), 200.0);
+        assert_eq!(median(&[10.0, 10.0, 12.0, 10.0]), 10.0);
+    }
+
+    #[test]
+    fn test_merge_adjacent_blocks() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.blocks.push(block_with_lines(
+            72.0,
+            100.0,
+            200.0,
+            10.0,
+            "Introduction",
+            &["Introduction"],
+        ));
+        page.blocks.push(block_with_lines(
+            72.0,
+            116.0,
+            200.0,
+            10.0,
+            "Introduction",
+            &[
+                "Recurrent neural networks have been",
+                "firmly established as state of",
+            ],
+        ));
+        page.blocks.push(block_with_lines(
+            72.0,
+            142.0,
+            200.0,
+            10.0,
+            "Introduction",
+            &[
+                "the art approaches in sequence modeling",
+                "and transduction problems.",
+            ],
+        ));
+        merge_adjacent_blocks(&mut page);
+
+        assert_eq!(page.blocks.len(), 2);
+        assert_eq!(page.blocks[0].get_text(), "Introduction");
+        assert_eq!(page.blocks[1].lines.len(), 4);
+        assert_eq!(page.blocks[1].y, 116.0);
+        assert_eq!(page.blocks[1].height, 50.0);
+        assert!(page.blocks[1].get_text().starts_with("Recurrent neural networks"));
+        assert!(page.blocks[1].get_text().ends_with("transduction problems."));
+    }
+
+    #[test]
+    fn test_merge_drop_caps() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.add_block(72.0, 100.0, 30.0, 34.0);
+        page.blocks[0].add_line(72.0, 100.0, 30.0, 34.0);
+        page.blocks[0].lines[0].add_word("T".to_string(), 72.0, 100.0, 30.0, 34.0);
+        page.add_block(106.0, 100.0, 434.0, 34.0);
+        for (i, text) in ["he quick brown fox", "jumps over the", "lazy dog."].iter().enumerate() {
+            let y = 100.0 + 12.0 * i as f32;
+            page.blocks[1].add_line(106.0, y, 434.0, 10.0);
+            page.blocks[1].lines[i].add_word(text.to_string(), 106.0, y, 434.0, 10.0);
+        }
+
+        merge_drop_caps(&mut page);
+
+        assert_eq!(page.blocks.len(), 1);
+        assert!(page.blocks[0].get_text().starts_with("The quick brown fox"));
+    }
+
+    #[test]
+    fn test_drop_rule_blocks() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.blocks.push(block_with_lines(
+            72.0,
+            100.0,
+            200.0,
+            10.0,
+            "",
+            &["Body text of the column."],
+        ));
+        page.add_block(305.0, 72.0, 1.0, 648.0);
+        page.blocks[1].add_line(305.0, 72.0, 1.0, 648.0);
+        page.blocks[1].lines[0].add_word("|".to_string(), 305.0, 72.0, 1.0, 648.0);
+        page.add_block(72.0, 60.0, 468.0, 2.0);
+        page.blocks[2].add_line(72.0, 60.0, 468.0, 2.0);
+        page.blocks[2].lines[0].add_word("______".to_string(), 72.0, 60.0, 468.0, 2.0);
+        page.add_block(300.0, 400.0, 12.0, 10.0);
+        page.blocks[3].add_line(300.0, 400.0, 12.0, 10.0);
+        page.blocks[3].lines[0].add_word("—".to_string(), 300.0, 400.0, 12.0, 10.0);
+
+        assert!(!is_rule_block(&page.blocks[0]));
+        assert!(is_rule_block(&page.blocks[1]));
+        assert!(is_rule_block(&page.blocks[2]));
+        // a lone dash is too short to be a rule
+        assert!(!is_rule_block(&page.blocks[3]));
+
+        drop_rule_blocks(&mut page);
+
+        assert_eq!(page.blocks.len(), 2);
+        assert_eq!(page.blocks[0].get_text(), "Body text of the column.");
+        assert_eq!(page.blocks[1].get_text(), "—");
+    }
+
+    fn drop_cap_page() -> Page {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.add_block(72.0, 100.0, 30.0, 34.0);
+        page.blocks[0].add_line(72.0, 100.0, 30.0, 34.0);
+        page.blocks[0].lines[0].add_word("T".to_string(), 72.0, 100.0, 30.0, 34.0);
+        page.blocks.push(block_with_lines(
+            106.0,
+            100.0,
+            200.0,
+            10.0,
+            "",
+            &["he quick brown fox", "jumps over the", "lazy dog and"],
+        ));
+        page.blocks.push(block_with_lines(
+            106.0,
+            138.0,
+            200.0,
+            10.0,
+            "",
+            &["runs away.", "Then it sleeps."],
+        ));
+        return page;
+    }
+
+    #[test]
+    fn test_compose_passes() {
+        let mut page = drop_cap_page();
+        merge_drop_caps(&mut page);
+
+        let mut pages = vec![drop_cap_page()];
+        default_postprocess(&mut pages, &ParserConfig::new());
+        assert_eq!(pages[0], page);
+
+        merge_adjacent_blocks(&mut page);
+        assert_eq!(page.blocks.len(), 1);
+        assert_eq!(page.blocks[0].lines.len(), 5);
+        assert!(page.blocks[0].get_text().starts_with("The quick brown fox"));
+        assert!(page.blocks[0].get_text().ends_with("Then it sleeps."));
+
+        // Duplicated words are only dropped when asked for
+        let mut pages = vec![drop_cap_page()];
+        let words = pages[0].blocks[2].lines[0].words.len();
+        let duplicate = pages[0].blocks[2].lines[0].words[0].clone();
+        pages[0].blocks[2].lines[0].words.push(duplicate);
+        default_postprocess(&mut pages, &ParserConfig::new());
+        assert_eq!(pages[0].blocks[1].lines[0].words.len(), words + 1);
+        let mut config = ParserConfig::new();
+        config.dedup_words = true;
+        default_postprocess(&mut pages, &config);
+        assert_eq!(pages[0].blocks[1].lines[0].words.len(), words);
+    }
+
+    #[test]
+    fn test_split_cross_column_blocks() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.number_of_columns = 2;
+        let mut block = Block::new(72.0, 100.0, 468.0, 22.0);
+        for (i, (left, right)) in [
+            ("the encoder maps", "the decoder then"),
+            ("an input sequence", "generates an output"),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let y = 100.0 + 12.0 * i as f32;
+            block.add_line(72.0, y, 468.0, 10.0);
+            block.lines[i].add_word(left.to_string(), 72.0, y, 220.0, 10.0);
+            block.lines[i].add_word(right.to_string(), 320.0, y, 220.0, 10.0);
+        }
+        page.blocks.push(block);
+        split_cross_column_blocks(&mut page);
+
+        assert_eq!(page.blocks.len(), 2);
+        assert_eq!(
+            page.blocks[0].get_text(),
+            "the encoder maps an input sequence"
+        );
+        assert_eq!(
+            page.blocks[1].get_text(),
+            "the decoder then generates an output"
+        );
+        assert_eq!(page.blocks[0].x, 72.0);
+        assert_eq!(page.blocks[0].width, 220.0);
+        assert_eq!(page.blocks[1].x, 320.0);
+        assert_eq!(page.blocks[1].height, 22.0);
+    }
+
+    #[test]
+    fn test_dedup_overlapping_words() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.add_block(72.0, 100.0, 200.0, 10.0);
+        page.blocks[0].add_line(72.0, 100.0, 200.0, 10.0);
+        let line = &mut page.blocks[0].lines[0];
+        line.add_word("Attention".to_string(), 72.0, 100.0, 40.0, 10.0);
+        line.add_word("Attention".to_string(), 72.2, 100.1, 40.0, 10.0);
+        line.add_word("is".to_string(), 115.0, 100.0, 8.0, 10.0);
+        line.add_word("is".to_string(), 115.0, 100.0, 8.0, 10.0);
+        line.add_word("Attention".to_string(), 126.0, 100.0, 40.0, 10.0);
+        page.add_block(72.0, 100.0, 200.0, 10.0);
+        page.blocks[1].add_line(72.0, 100.0, 200.0, 10.0);
+        page.blocks[1].lines[0].add_word("Attention".to_string(), 72.0, 100.0, 40.0, 10.0);
+
+        dedup_overlapping_words(&mut page, 0.8);
+
+        assert_eq!(page.blocks.len(), 1);
+        assert_eq!(page.blocks[0].get_text(), "Attention is Attention");
+
+        // The boxes are shrunk once a trailing duplicate is dropped
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.add_block(72.0, 100.0, 200.0, 22.0);
+        page.blocks[0].add_line(72.0, 100.0, 200.0, 10.0);
+        page.blocks[0].lines[0].add_word("Attention".to_string(), 72.0, 100.0, 40.0, 10.0);
+        page.blocks[0].add_line(72.0, 112.0, 200.0, 10.0);
+        page.blocks[0].lines[1].add_word("Attention".to_string(), 72.0, 100.5, 40.0, 10.0);
+        page.blocks[0].lines[1].add_word("rocks".to_string(), 72.0, 112.0, 30.0, 10.0);
+
+        dedup_overlapping_words(&mut page, 0.8);
+
+        let block = &page.blocks[0];
+        assert_eq!(
+            Coordinate::from(&block.lines[0]).as_rect(),
+            (72.0, 100.0, 112.0, 110.0)
+        );
+        assert_eq!(
+            Coordinate::from(&block.lines[1]).as_rect(),
+            (72.0, 112.0, 102.0, 122.0)
+        );
+        assert_eq!(block.coordinate().as_rect(), (72.0, 100.0, 112.0, 122.0));
+        assert_eq!((block.x, block.width), (72.0, 40.0));
+    }
+
+    #[test]
+    fn test_nms_blocks() {
+        let mut small = Block::new(74.0, 102.0, 196.0, 28.0);
+        small.add_line(74.0, 102.0, 196.0, 10.0);
+        small.lines[0].add_word("duplicate".to_string(), 74.0, 102.0, 196.0, 10.0);
+        let mut large = Block::new(72.0, 100.0, 200.0, 30.0);
+        large.add_line(72.0, 100.0, 200.0, 10.0);
+        large.lines[0].add_word("original".to_string(), 72.0, 100.0, 200.0, 10.0);
+        let other = Block::new(72.0, 300.0, 200.0, 30.0);
+
+        let blocks = nms_blocks(vec![small, large, other], 0.5);
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].get_text(), "original");
+        assert_eq!(blocks[1].y, 300.0);
+    }
+
+    #[test]
+    fn test_section_name() {
+        assert_eq!(section_name("1 Introduction"), "Introduction");
+        assert_eq!(section_name("2. Background"), "Background");
+        assert_eq!(
+            section_name("3.1 Scaled Dot-Product Attention"),
+            "Scaled Dot-Product Attention"
+        );
+        assert_eq!(section_name(" Abstract "), "Abstract");
+    }
+
+    #[test]
+    fn test_classify_headings() {
+        let mut page = Page::new(612.0, 792.0, 3);
+        page.blocks.push(block_with_lines(
+            72.0,
+            100.0,
+            200.0,
+            10.0,
+            "Background",
+            &["2. Background"],
+        ));
+        page.blocks.push(block_with_lines(
+            72.0,
+            120.0,
+            200.0,
+            10.0,
+            "Background",
+            &["Background information on attention."],
+        ));
+        page.blocks.push(block_with_lines(
+            72.0,
+            140.0,
+            200.0,
+            10.0,
+            "Background",
+            &["Background", "Twice"],
+        ));
+        page.blocks.push(block_with_lines(
+            72.0,
+            160.0,
+            200.0,
+            10.0,
+            "Model Architecture",
+            &["3 Model Architecture"],
+        ));
+        let text_area = Coordinate::from_rect(72.0, 72.0, 272.0, 720.0);
+        let ctx = ClassifyContext::new(&page, &text_area);
+        let attrs = page
+            .blocks
+            .iter()
+            .map(|block| DefaultClassifier::default().classify(block, &ctx))
+            .collect::<Vec<BlockAttr>>();
+        assert_eq!(
+            attrs,
+            vec![
+                BlockAttr::Title,
+                BlockAttr::Text,
+                BlockAttr::Text,
+                BlockAttr::Title
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify_wrapped_paper_title() {
+        let body = [
+            "Recurrent neural networks, long short-term memory and gated",
+            "recurrent neural networks in particular, have been firmly",
+            "established as state of the art approaches.",
+        ];
+        let mut title = Block::new(120.0, 90.0, 372.0, 38.0);
+        for (i, text) in ["A Very Long Paper Title That Wraps", "Over Two Lines"].iter().enumerate()
+        {
+            let line_y = 90.0 + 20.0 * i as f32;
+            title.add_line(120.0, line_y, 372.0, 17.0);
+            title.lines[i].add_word(text.to_string(), 120.0, line_y, 372.0, 17.0);
+        }
+        let mut first = Page::new(612.0, 792.0, 1);
+        first.blocks.push(title);
+        first.blocks.push(block_with_lines(72.0, 200.0, 200.0, 10.0, "", &body));
+        first.blocks.push(block_with_lines(72.0, 260.0, 200.0, 10.0, "", &body));
+        let mut second = first.clone();
+        second.page_number = 2;
+        let text_area = Coordinate::from_rect(72.0, 72.0, 540.0, 720.0);
+
+        classify_blocks(&mut first, &text_area, &DefaultClassifier::default(), 0.0);
+        classify_blocks(&mut second, &text_area, &DefaultClassifier::default(), 0.0);
+
+        let titles = first.blocks_with_attr(BlockAttr::Title);
+        assert_eq!(titles.len(), 1);
+        assert_eq!(
+            titles[0].get_text(),
+            "A Very Long Paper Title That Wraps Over Two Lines"
+        );
+        assert!(second.blocks_with_attr(BlockAttr::Title).is_empty());
+    }
+
+    #[test]
+    fn test_classify_centered_paper_title() {
+        let body = [
+            "Recurrent neural networks, long short-term memory and gated",
+            "recurrent neural networks in particular, have been firmly",
+        ];
+        let mut first = Page::new(612.0, 792.0, 1);
+        first.blocks.push(block_with_lines(
+            206.0,
+            80.0,
+            200.0,
+            10.0,
+            "",
+            &["A Modest Paper Title"],
+        ));
+        first.blocks.push(block_with_lines(
+            206.0,
+            110.0,
+            200.0,
+            10.0,
+            "",
+            &["Jane Doe, John Smith"],
+        ));
+        first.blocks.push(block_with_lines(72.0, 200.0, 200.0, 10.0, "", &body));
+        first.blocks.push(block_with_lines(72.0, 240.0, 200.0, 10.0, "", &body));
+        let text_area = Coordinate::from_rect(72.0, 72.0, 540.0, 720.0);
+        let titles = |title_detection: TitleDetection| {
+            let mut page = first.clone();
+            let classifier = DefaultClassifier { title_detection };
+            classify_blocks(&mut page, &text_area, &classifier, 0.0);
+            return page
+                .blocks_with_attr(BlockAttr::Title)
+                .iter()
+                .map(|block| block.get_text())
+                .collect::<Vec<String>>();
+        };
+
+        assert!(titles(TitleDetection::Font).is_empty());
+        assert_eq!(
+            titles(TitleDetection::Position),
+            vec!["A Modest Paper Title"]
+        );
+        assert_eq!(titles(TitleDetection::Either), vec!["A Modest Paper Title"]);
+        assert!(titles(TitleDetection::Both).is_empty());
+    }
+
+    #[test]
+    fn test_classify_blocks_keeps_attrs() {
+        let body = [
+            "Recurrent neural networks, long short-term memory and gated",
+            "recurrent neural networks in particular, have been firmly",
+        ];
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.blocks.push(block_with_lines(72.0, 200.0, 200.0, 10.0, "", &body));
+        page.blocks.push(block_with_lines(72.0, 240.0, 200.0, 10.0, "", &body));
+        page.blocks[1].attr = BlockAttr::Caption;
+        let text_area = Coordinate::from_rect(72.0, 72.0, 272.0, 720.0);
+
+        classify_blocks(&mut page, &text_area, &DefaultClassifier::default(), 0.0);
+        assert_eq!(page.blocks[0].attr, BlockAttr::Text);
+        assert_eq!(page.blocks[1].attr, BlockAttr::Caption);
+    }
+
+    #[test]
+    fn test_classify_refined_font_size() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        let mut heading = block_with_lines(72.0, 80.0, 200.0, 10.0, "", &["A Short Paper Title"]);
+        heading.lines[0].words[0].height = 11.0;
+        page.blocks.push(heading);
+        page.blocks.push(block_with_lines(
+            72.0,
+            120.0,
+            200.0,
+            10.0,
+            "",
+            &[
+                "Recurrent neural networks, long short-term memory and gated",
+                "recurrent neural networks in particular, have been firmly",
+                "established as state of the art approaches.",
+            ],
+        ));
+        for y in [200.0, 240.0, 280.0] {
+            let mut equation =
+                block_with_lines(72.0, y, 200.0, 10.0, "", &["E = mc^2 (1)", "x + y = z (2)"]);
+            for line in equation.lines.iter_mut() {
+                line.words[0].height = 8.0;
+            }
+            page.blocks.push(equation);
+        }
+        let text_area = Coordinate::from_rect(72.0, 72.0, 272.0, 720.0);
+
+        let ctx = ClassifyContext::new(&page, &text_area);
+        assert_eq!(ctx.font_size, 8.0);
+        let rough = DefaultClassifier::default().classify(&page.blocks[0], &ctx);
+        assert_eq!(rough, BlockAttr::Title);
+        classify_blocks(&mut page, &text_area, &DefaultClassifier::default(), 0.0);
+        assert_eq!(
+            ClassifyContext::new(&page, &text_area).refined(&page).font_size,
+            10.0
+        );
+        let attrs = page.blocks.iter().map(|block| block.attr).collect::<Vec<BlockAttr>>();
+        assert_eq!(
+            attrs,
+            vec![
+                BlockAttr::Text,
+                BlockAttr::Text,
+                BlockAttr::Equation,
+                BlockAttr::Equation,
+                BlockAttr::Equation
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_equation_labels() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.add_block(200.0, 100.0, 200.0, 12.0);
+        page.blocks[0].add_line(200.0, 100.0, 200.0, 12.0);
+        for (text, x, width) in [
+            ("E", 200.0, 10.0),
+            ("=", 215.0, 10.0),
+            ("mc^2", 230.0, 30.0),
+        ] {
+            page.blocks[0].lines[0].add_word(text.to_string(), x, 100.0, width, 12.0);
+        }
+        page.add_block(510.0, 101.0, 20.0, 10.0);
+        page.blocks[1].add_line(510.0, 101.0, 20.0, 10.0);
+        page.blocks[1].lines[0].add_word("(3)".to_string(), 510.0, 101.0, 20.0, 10.0);
+        page.add_block(200.0, 150.0, 330.0, 12.0);
+        page.blocks[2].add_line(200.0, 150.0, 330.0, 12.0);
+        for (text, x, width) in [("x", 200.0, 10.0), ("=", 215.0, 10.0), ("(4)", 510.0, 20.0)] {
+            page.blocks[2].lines[0].add_word(text.to_string(), x, 150.0, width, 12.0);
+        }
+        page.blocks.push(block_with_lines(
+            72.0,
+            200.0,
+            200.0,
+            10.0,
+            "",
+            &["as shown in (3)"],
+        ));
+        for block in page.blocks.iter_mut().take(3) {
+            block.attr = BlockAttr::Equation;
+        }
+
+        detect_equation_labels(&mut page);
+
+        assert_eq!(page.blocks.len(), 3);
+        assert_eq!(page.blocks[0].label.as_deref(), Some("(3)"));
+        assert_eq!(page.blocks[0].get_text(), "E = mc^2 (3)");
+        assert_eq!(page.blocks[0].width, 330.0);
+        assert_eq!(page.blocks[1].label.as_deref(), Some("(4)"));
+        assert_eq!(page.blocks[2].label, None);
+    }
+
+    #[test]
+    fn test_detect_captions() {
+        let mut page = Page::new(612.0, 792.0, 3);
+        page.blocks.push(block_with_lines(
+            72.0,
+            400.0,
+            200.0,
+            10.0,
+            "",
+            &["Figure 1: The Transformer - model architecture."],
+        ));
+        page.blocks.push(block_with_lines(
+            72.0,
+            450.0,
+            200.0,
+            10.0,
+            "",
+            &["Table 2. BLEU scores"],
+        ));
+        page.blocks.push(block_with_lines(
+            72.0,
+            500.0,
+            200.0,
+            10.0,
+            "",
+            &["Fig.3: Attention heads"],
+        ));
+        page.blocks.push(block_with_lines(
+            72.0,
+            550.0,
+            200.0,
+            10.0,
+            "",
+            &["Figure 1 shows the model."],
+        ));
+        detect_captions(&mut page);
+
+        let attrs = page.blocks.iter().map(|block| block.attr).collect::<Vec<BlockAttr>>();
+        assert_eq!(
+            attrs,
+            vec![
+                BlockAttr::Caption,
+                BlockAttr::Caption,
+                BlockAttr::Caption,
+                BlockAttr::Text
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_toc() {
+        let mut page = Page::new(612.0, 792.0, 2);
+        page.blocks.push(block_with_lines(
+            72.0,
+            100.0,
+            200.0,
+            10.0,
+            "Contents",
+            &[
+                "1 Introduction .......... 3",
+                "2 Background . . . . . . . 5",
+                "2.1 Attention is all you need ... 12",
+                "References ……… 20",
+            ],
+        ));
+        page.blocks.push(block_with_lines(
+            72.0,
+            200.0,
+            200.0,
+            10.0,
+            "Contents",
+            &["See Section 2... 3 times", "for more details."],
+        ));
+        detect_toc(&mut page);
+        detect_equations(&mut page);
+
+        assert_eq!(page.blocks[0].attr, BlockAttr::Toc);
+        assert_eq!(page.blocks[1].attr, BlockAttr::Text);
+        assert_eq!(
+            toc_entries(&page.blocks[0]),
+            vec![
+                ("1 Introduction".to_string(), 3),
+                ("2 Background".to_string(), 5),
+                ("2.1 Attention is all you need".to_string(), 12),
+                ("References".to_string(), 20),
+            ]
+        );
+        assert_eq!(parse_toc_entry("Version 2.0"), None);
+    }
+
+    #[test]
+    fn test_detect_equations() {
+        let mut page = Page::new(612.0, 792.0, 3);
+        page.blocks.push(block_with_lines(
+            72.0,
+            100.0,
+            200.0,
+            10.0,
+            "Model Architecture",
+            &["We call our particular attention Scaled Dot-Product Attention."],
+        ));
+        page.blocks.push(block_with_lines(
+            150.0,
+            130.0,
+            200.0,
+            10.0,
+            "Model Architecture",
+            &["Attention(Q, K, V ) = softmax( QKT √dk )V (1)"],
+        ));
+        page.blocks.push(block_with_lines(
+            200.0,
+            160.0,
+            200.0,
+            10.0,
+            "Model Architecture",
+            &["∑ i ∈ [1, n] : x_i · w_i + b ≤ 0"],
+        ));
+        page.blocks.push(block_with_lines(
+            300.0,
+            700.0,
+            200.0,
+            10.0,
+            "Model Architecture",
+            &["3"],
+        ));
+        page.blocks.push(block_with_lines(
+            72.0,
+            400.0,
+            200.0,
+            10.0,
+            "Model Architecture",
+            &["25.8 26.4 4.92 (0.4)", "24.9 25.5 5.29 (1.2)"],
+        ));
+        page.blocks.push(block_with_lines(
+            72.0,
+            500.0,
+            200.0,
+            10.0,
+            "References",
+            &["[12] 2017, pp. 1–10."],
+        ));
+        detect_equations(&mut page);
+
+        let attrs = page.blocks.iter().map(|block| block.attr).collect::<Vec<BlockAttr>>();
+        assert_eq!(
+            attrs,
+            vec![
+                BlockAttr::Text,
+                BlockAttr::Equation,
+                BlockAttr::Equation,
+                BlockAttr::Text,
+                BlockAttr::Text,
+                BlockAttr::Text
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_quotes() {
+        let mut page = Page::new(612.0, 792.0, 2);
+        let text_area = Coordinate::from_rect(72.0, 72.0, 540.0, 720.0);
+        let texts = [
+            (
+                72.0,
+                468.0,
+                ["As Knuth once wrote in his famous", "essay on programming:"],
+            ),
+            (
+                108.0,
+                396.0,
+                [
+                    "Premature optimization is the root",
+                    "of all evil, at least most of it.",
+                ],
+            ),
+            (
+                72.0,
+                300.0,
+                [
+                    "We should forget about small",
+                    "efficiencies, say 97% of the time.",
+                ],
+            ),
+        ];
+        for (i, (x, width, lines)) in texts.iter().enumerate() {
+            let mut block = block_with_lines(
+                *x,
+                100.0 + 40.0 * i as f32,
+                200.0,
+                10.0,
+                "Introduction",
+                lines,
+            );
+            block.width = *width;
+            block.lines.iter_mut().for_each(|line| line.width = *width);
+            page.blocks.push(block);
+        }
+        let mut single_line = page.blocks[2].clone();
+        single_line.lines.truncate(1);
+        page.blocks.push(single_line);
+        detect_quotes(&mut page, &text_area);
+
+        let attrs = page.blocks.iter().map(|block| block.attr).collect::<Vec<BlockAttr>>();
+        assert_eq!(
+            attrs,
+            vec![
+                BlockAttr::Text,
+                BlockAttr::Quote,
+                BlockAttr::Text,
+                BlockAttr::Text
+            ]
+        );
+        assert!(page.blocks[1].get_markdown_text().starts_with("> Premature optimization"));
+    }
+
+    fn vertical_line(x: f32, y: f32, chars: &str) -> Line {
+        let mut line = Line::new(x, y, 10.0, 10.0 * chars.chars().count() as f32);
+        // pdftotext does not guarantee the order of the words in a vertical line
+        for (i, c) in chars.chars().enumerate().collect::<Vec<_>>().into_iter().rev() {
+            line.add_word(c.to_string(), x, y + 10.0 * i as f32, 10.0, 10.0);
+        }
+        return line;
+    }
+
+    #[test]
+    fn test_reorder_vertical_text() {
+        let mut page = Page::new(595.0, 842.0, 1);
+        let mut left = Block::new(300.0, 100.0, 40.0, 60.0);
+        left.lines = vec![
+            vertical_line(300.0, 100.0, "終わり"),
+            vertical_line(320.0, 100.0, "次に"),
+        ];
+        let mut right = Block::new(400.0, 100.0, 40.0, 60.0);
+        right.lines = vec![
+            vertical_line(400.0, 100.0, "二行目"),
+            vertical_line(420.0, 100.0, "一行目"),
+        ];
+        let mut lower = Block::new(500.0, 400.0, 20.0, 30.0);
+        lower.lines = vec![vertical_line(500.0, 400.0, "下段")];
+        page.blocks = vec![lower, left, right];
+        reorder_vertical_text(&mut page);
+
+        assert_eq!(page.writing_mode, WritingMode::Vertical);
+        let texts = page
+            .blocks
+            .iter()
+            .map(|block| block.lines.iter().map(|line| line.get_text()).collect())
+            .collect::<Vec<Vec<String>>>();
+        assert_eq!(
+            texts,
+            vec![
+                vec!["一行目".to_string(), "二行目".to_string()],
+                vec!["次に".to_string(), "終わり".to_string()],
+                vec!["下段".to_string()],
+            ]
+        );
+
+        let mut horizontal = Page::new(612.0, 792.0, 1);
+        horizontal.blocks.push(block_with_lines(
+            72.0,
+            100.0,
+            200.0,
+            10.0,
+            "",
+            &["Attention is all you need"],
+        ));
+        reorder_vertical_text(&mut horizontal);
+        assert_eq!(horizontal.writing_mode, WritingMode::Horizontal);
+    }
+
+    #[cfg(feature = "download")]
     #[tokio::test]
     async fn test_adjust_columns() {
         let time = std::time::Instant::now();
@@ -190,7 +2074,7 @@ mod tests {
 
         let html = pdf2html(url, &mut config, true, time).await.unwrap();
 
-        let mut pages = parse_html2pages(&mut config, html).unwrap();
+        let mut pages = parse_html2pages(&mut config, html, &mut Vec::new()).unwrap();
 
         parse_extract_textarea(&mut config, &mut pages).unwrap();
 
@@ -53,8 +53,11 @@
 //! ```
 
 pub mod config;
+#[cfg(feature = "native")]
 pub mod converter;
+pub mod exporter;
 pub mod extracter;
 pub mod models;
 pub mod parser;
+#[cfg(any(test, feature = "download"))]
 pub mod test_utils;
@@ -1,6 +1,10 @@
-use crate::config::PageNumber;
+use crate::config::{collapse_whitespace, FullTextOptions, PageNumber, TextOptions};
+use crate::extracter::median;
+use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{LazyLock, OnceLock};
+use unicode_bidi::{get_base_direction, BidiInfo, Direction, Level};
 
 // 2025.12.16 変更点
 use regex::Regex;
@@ -31,6 +35,20 @@ pub fn fix_based_hyphen(text: &str) -> String {
     .to_string()
 }
 
+/// The `Script` enum represents the vertical placement of a word relative to its line.
+///
+/// # Variants
+///
+/// * `Normal` - The word sits on the baseline of the line.
+/// * `Super` - The word is raised above the baseline (footnote markers, exponents).
+/// * `Sub` - The word is lowered below the baseline (chemical formulas, indices).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Script {
+    Normal,
+    Super,
+    Sub,
+}
+
 /// The `Word` struct represents a word in a PDF document.
 ///
 /// # Fields
@@ -39,6 +57,11 @@ pub fn fix_based_hyphen(text: &str) -> String {
 /// * `x` - The x-coordinate of the top-left corner of the word.
 /// * `y` - The y-coordinate of the top-left corner of the word.
 /// * `width` - The width of the word.
+/// * `height` - The height of the word.
+/// * `script` - Whether the word is set as superscript, subscript or on the baseline.
+/// * `bold` - Whether the word is set in a bold face.
+/// * `italic` - Whether the word is set in an italic face.
+/// * `normalized_text` - The text with its ligatures replaced, kept with `LigatureMode::Both`.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Word {
     pub text: String,
@@ -46,12 +69,136 @@ pub struct Word {
     pub y: f32,
     pub width: f32,
     pub height: f32,
+    pub script: Script,
+    pub bold: bool,
+    pub italic: bool,
+    pub normalized_text: Option<String>,
 }
 
 impl Word {
     pub fn font_size(&self) -> f32 {
         return self.height;
     }
+
+    /// Returns the normalized text of the word.
+    ///
+    /// # Returns
+    ///
+    /// The `normalized_text` of the word when it was recorded, its `text` otherwise.
+    pub fn normalized(&self) -> &str {
+        return self.normalized_text.as_deref().unwrap_or(&self.text);
+    }
+
+    /// Determines whether the word is a number, such as "42", "-0.5" or "1,234.5".
+    ///
+    /// # Returns
+    ///
+    /// `true` if the whole text of the word is a number, see `parse_numeric`.
+    pub fn is_numeric(&self) -> bool {
+        return parse_numeric(&self.text, false).is_some();
+    }
+
+    /// Returns the text of the word wrapped in Markdown emphasis markers.
+    ///
+    /// # Returns
+    ///
+    /// A `String` with `**` around bold text and `_` around italic text.
+    pub fn get_markdown_text(&self) -> String {
+        return emphasize(&self.text, self.bold, self.italic);
+    }
+
+    /// Returns the y-coordinate of the baseline of the word.
+    ///
+    /// pdftotext boxes span from the ascender to the descender of the font, so the baseline is
+    /// placed above the bottom of the box by the typical descender depth.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` representing the baseline of the word.
+    pub fn baseline(&self) -> f32 {
+        return self.y + self.height * (1.0 - DESCENDER_RATIO);
+    }
+}
+
+/// The typical depth of the descender, relative to the height of a pdftotext word box.
+const DESCENDER_RATIO: f32 = 0.2;
+
+/// The largest gap between two parts of a split word, relative to their font size.
+const SPLIT_WORD_GAP_RATIO: f32 = 0.05;
+
+fn emphasize(text: &str, bold: bool, italic: bool) -> String {
+    match (bold, italic) {
+        (true, true) => format!("**_{}_**", text),
+        (true, false) => format!("**{}**", text),
+        (false, true) => format!("_{}_", text),
+        (false, false) => text.to_string(),
+    }
+}
+
+/// The `Alignment` enum represents how a set of words is aligned horizontally.
+///
+/// # Variants
+///
+/// * `Left` - The left edges of the words are aligned.
+/// * `Right` - The right edges of the words are aligned.
+/// * `Center` - The horizontal centers of the words are aligned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Alignment {
+    Left,
+    Right,
+    Center,
+}
+
+/// Returns the horizontal alignment shared by a set of words.
+///
+/// The words are aligned when the spread of their left edges, right edges or centers is
+/// within the tolerance. Left alignment is checked first, then right, then center, so words
+/// of the same width stacked exactly on top of each other are reported as `Left`.
+///
+/// # Arguments
+///
+/// * `words` - The words to test, usually the first or last words of consecutive lines.
+/// * `tol` - The maximum spread, in points, of the aligned edges or centers.
+///
+/// # Returns
+///
+/// The `Alignment` of the words, or `None` if fewer than two words are given or they are
+/// not aligned.
+pub fn aligned_x(words: &[&Word], tol: f32) -> Option<Alignment> {
+    if words.len() < 2 {
+        return None;
+    }
+    let spread = |edge: &dyn Fn(&Word) -> f32| {
+        let (min, max) = words.iter().fold((f32::MAX, f32::MIN), |(min, max), word| {
+            let value = edge(word);
+            (min.min(value), max.max(value))
+        });
+        max - min
+    };
+    if spread(&|word| word.x) <= tol {
+        return Some(Alignment::Left);
+    }
+    if spread(&|word| word.x + word.width) <= tol {
+        return Some(Alignment::Right);
+    }
+    if spread(&|word| word.x + word.width / 2.0) <= tol {
+        return Some(Alignment::Center);
+    }
+    return None;
+}
+
+/// The `EmphasisSpan` struct represents a region of a page set in a bold or italic face.
+///
+/// # Fields
+///
+/// * `coordinates` - The area of the page covered by the emphasized text.
+/// * `bold` - Whether the text in the area is bold.
+/// * `italic` - Whether the text in the area is italic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmphasisSpan {
+    pub coordinates: Coordinate,
+    pub bold: bool,
+    pub italic: bool,
 }
 
 /// The `Line` struct represents a line of text in a PDF document.
@@ -104,28 +251,405 @@ impl Line {
     /// * `width` - The width of the word.
     /// * `height` - The height of the word.
     pub fn add_word(&mut self, text: String, x: f32, y: f32, width: f32, height: f32) {
+        self.add_untrimmed_word(text.trim().to_string(), x, y, width, height);
+    }
+
+    /// Adds a new `Word` to the `Line`, keeping the leading and trailing whitespace of its text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text content of the word.
+    /// * `x` - The x-coordinate of the top-left corner of the word.
+    /// * `y` - The y-coordinate of the top-left corner of the word.
+    /// * `width` - The width of the word.
+    /// * `height` - The height of the word.
+    pub fn add_untrimmed_word(&mut self, text: String, x: f32, y: f32, width: f32, height: f32) {
         self.words.push(Word {
-            text: text.trim().to_string(),
+            text,
             x: x,
             y: y,
             width: width,
             height: height,
+            script: Script::Normal,
+            bold: false,
+            italic: false,
+            normalized_text: None,
         });
     }
+
+    /// Joins consecutive words that pdftotext split inside a single visual word.
+    ///
+    /// Two words are joined when the horizontal gap between them is below
+    /// `SPLIT_WORD_GAP_RATIO` of their font size and their baselines are aligned. The joined
+    /// word covers the bounding boxes of both parts.
+    pub fn merge_split_words(&mut self) {
+        let mut words: Vec<Word> = Vec::with_capacity(self.words.len());
+        for word in self.words.drain(..) {
+            if let Some(last) = words.last_mut() {
+                let font_size = last.height.min(word.height);
+                let gap = word.x - (last.x + last.width);
+                if font_size > 0.0
+                    && gap.abs() < font_size * SPLIT_WORD_GAP_RATIO
+                    && (last.baseline() - word.baseline()).abs() < font_size * 0.1
+                {
+                    let right = (last.x + last.width).max(word.x + word.width);
+                    let bottom = (last.y + last.height).max(word.y + word.height);
+                    if last.normalized_text.is_some() || word.normalized_text.is_some() {
+                        last.normalized_text =
+                            Some(last.normalized().to_string() + word.normalized());
+                    }
+                    last.text.push_str(&word.text);
+                    last.x = last.x.min(word.x);
+                    last.y = last.y.min(word.y);
+                    last.width = right - last.x;
+                    last.height = bottom - last.y;
+                    continue;
+                }
+            }
+            words.push(word);
+        }
+        self.words = words;
+    }
+
+    /// Marks the words of the `Line` covered by the given emphasis spans as bold or italic.
+    ///
+    /// # Arguments
+    ///
+    /// * `spans` - The emphasis spans found on the page the line belongs to.
+    pub fn apply_emphasis(&mut self, spans: &[EmphasisSpan]) {
+        for word in self.words.iter_mut() {
+            let word_coord = Coordinate::from(&*word);
+            if word_coord.get_area() <= 0.0 {
+                continue;
+            }
+            for span in spans {
+                if word_coord.iou(&span.coordinates) <= 0.0 {
+                    continue;
+                }
+                let ratio =
+                    word_coord.intersection(&span.coordinates).get_area() / word_coord.get_area();
+                if ratio > 0.5 {
+                    word.bold |= span.bold;
+                    word.italic |= span.italic;
+                }
+            }
+        }
+    }
+
+    /// Returns the dominant font size of the `Line`.
+    ///
+    /// Word heights are rounded to half a point and the size covering the most characters wins,
+    /// so that a drop cap or an inline formula does not decide the size of the whole line.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` representing the font size of most of the text in the line, or the line height
+    /// when the line has no words.
+    pub fn dominant_font_size(&self) -> f32 {
+        let mut counts: Vec<(f32, usize)> = Vec::new();
+        for word in &self.words {
+            let size = (word.height * 2.0).round() / 2.0;
+            let chars = word.text.chars().count().max(1);
+            match counts.iter_mut().find(|(s, _)| *s == size) {
+                Some((_, count)) => *count += chars,
+                None => counts.push((size, chars)),
+            }
+        }
+        return counts
+            .iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.total_cmp(&a.0)))
+            .map_or(self.height, |(size, _)| *size);
+    }
+
+    /// Flags the words of the `Line` that are set as superscript or subscript.
+    ///
+    /// Words whose height is clearly smaller than the dominant font size of the line are compared
+    /// against the vertical center of the full-size words: a word whose center sits above it
+    /// is marked as `Script::Super`, one whose center sits below it as `Script::Sub`.
+    pub fn detect_scripts(&mut self) {
+        let font_size = self.dominant_font_size();
+        if font_size <= 0.0 {
+            return;
+        }
+
+        let centers: Vec<f32> = self
+            .words
+            .iter()
+            .filter(|word| word.height >= font_size * 0.8)
+            .map(|word| word.y + word.height / 2.0)
+            .collect();
+        if centers.len() == self.words.len() {
+            return;
+        }
+        let center = median(&centers);
+
+        for word in self.words.iter_mut() {
+            if word.height >= font_size * 0.8 {
+                word.script = Script::Normal;
+                continue;
+            }
+            let word_center = word.y + word.height / 2.0;
+            word.script = if word_center < center - font_size * 0.15 {
+                Script::Super
+            } else if word_center > center + font_size * 0.15 {
+                Script::Sub
+            } else {
+                Script::Normal
+            };
+        }
+    }
+
+    /// Determines whether the `Line` is written vertically, its words stacked top-to-bottom.
+    ///
+    /// # Returns
+    ///
+    /// `true` when the box of the line is at least 1.5 times taller than wide.
+    pub fn is_vertical(&self) -> bool {
+        return self.height > self.width * 1.5;
+    }
+
+    /// Determines whether the `Line` is written right-to-left (Arabic, Hebrew, ...).
+    ///
+    /// # Returns
+    ///
+    /// `true` when more words are right-to-left than left-to-right.
+    pub fn is_rtl(&self) -> bool {
+        let mut balance = 0;
+        for word in &self.words {
+            match get_base_direction(word.text.as_str()) {
+                Direction::Rtl => balance += 1,
+                Direction::Ltr => balance -= 1,
+                Direction::Mixed => {}
+            }
+        }
+        return balance > 0;
+    }
+
+    /// Determines whether every word of the `Line` is a number, as in a row of table values or
+    /// a page number.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the line has words and all of them are numbers, see `Word::is_numeric`.
+    pub fn is_numeric(&self) -> bool {
+        return !self.words.is_empty() && self.words.iter().all(Word::is_numeric);
+    }
+
+    /// Returns the numbers found in the `Line`, in reading order.
+    ///
+    /// Brackets, percent signs and the punctuation ending a clause or a sentence are stripped
+    /// from each word first, so that "(12.5%)," reads as 12.5. The other words are skipped.
+    ///
+    /// # Returns
+    ///
+    /// A vector of the values of the numeric words of the line.
+    pub fn numbers(&self) -> Vec<f64> {
+        return self
+            .logical_words()
+            .iter()
+            .filter_map(|word| {
+                let text = word.text.trim_matches(|c: char| "()[]%,;:".contains(c));
+                return parse_numeric(text, false).or_else(|| {
+                    text.strip_suffix('.').and_then(|text| parse_numeric(text, false))
+                });
+            })
+            .collect();
+    }
+
+    /// Returns the words of the `Line` in logical (reading) order.
+    ///
+    /// Left-to-right lines keep the order given by pdftotext. The words of right-to-left lines
+    /// are sorted by their x-coordinate and reordered with the Unicode bidi algorithm, so that
+    /// right-to-left runs are read from the right while embedded left-to-right runs (Latin
+    /// terms, numbers) keep their left-to-right order.
+    ///
+    /// # Returns
+    ///
+    /// A vector of references to the words of the line in reading order.
+    pub fn logical_words(&self) -> Vec<&Word> {
+        let mut words = self.words.iter().collect::<Vec<&Word>>();
+        if !self.is_rtl() {
+            return words;
+        }
+        words.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+        // Right-to-left words get level 1, left-to-right words level 2 and neutral words take
+        // the level of their neighbors when both agree. Numbers right after a left-to-right
+        // word stay in its run.
+        let directions = words
+            .iter()
+            .map(|word| match get_base_direction(word.text.as_str()) {
+                Direction::Ltr => Some(Level::new(2).unwrap()),
+                Direction::Rtl => Some(Level::rtl()),
+                Direction::Mixed => None,
+            })
+            .collect::<Vec<Option<Level>>>();
+        let levels = (0..words.len())
+            .map(|i| {
+                if let Some(level) = directions[i] {
+                    return level;
+                }
+                let before = directions[..i].iter().rev().find_map(|level| *level);
+                let after = directions[i + 1..].iter().find_map(|level| *level);
+                let is_number = words[i].text.chars().any(|c| c.is_ascii_digit());
+                match (before, after) {
+                    (Some(before), _) if is_number && before.is_ltr() => before,
+                    (Some(before), Some(after)) if before == after => before,
+                    _ => Level::rtl(),
+                }
+            })
+            .collect::<Vec<Level>>();
+
+        // The visual reordering of levels 1 and 2 is an involution, so it also maps the visual
+        // order back to the logical one.
+        return BidiInfo::reorder_visual(&levels).into_iter().map(|i| words[i]).collect();
+    }
+
+    /// Returns the y-coordinate of the baseline of the `Line`.
+    ///
+    /// The baseline is the median of the baselines of the words set in the dominant font size,
+    /// so that superscripts and subscripts do not shift it.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` representing the baseline of the line, derived from the line box when the line
+    /// has no words.
+    pub fn baseline(&self) -> f32 {
+        let font_size = self.dominant_font_size();
+        let mut baselines = self
+            .words
+            .iter()
+            .filter(|word| (word.height - font_size).abs() <= 0.5)
+            .map(|word| word.baseline())
+            .collect::<Vec<f32>>();
+        if baselines.is_empty() {
+            baselines = self.words.iter().map(|word| word.baseline()).collect();
+        }
+        if baselines.is_empty() {
+            return self.y + self.height * (1.0 - DESCENDER_RATIO);
+        }
+        return median(&baselines);
+    }
+
     /// Returns the concatenated text of all `Word` instances in the `Line`.
     ///
     /// # Returns
     ///
     /// A `String` containing the text of all words in the line, separated by spaces.
     pub fn get_text(&self) -> String {
+        return self.get_text_with(&TextOptions::default());
+    }
+
+    /// Returns the concatenated text of all `Word` instances in the `Line`, cleaned according to
+    /// the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `TextOptions` controlling whitespace handling.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the text of all words in the line, separated by spaces, or joined
+    /// without separators in a vertical line.
+    pub fn get_text_with(&self, options: &TextOptions) -> String {
+        let logical_words = self.logical_words();
+        let runs = match options.math_placeholder {
+            Some(_) => math_runs(&logical_words),
+            None => Vec::new(),
+        };
         let mut words = Vec::new();
-        for word in &self.words {
-            words.push(word.text.clone());
+        let mut i = 0;
+        while i < logical_words.len() {
+            match (
+                runs.iter().find(|run| run.start == i),
+                &options.math_placeholder,
+            ) {
+                (Some(run), Some(placeholder)) => {
+                    words.push(placeholder.clone());
+                    i = run.end;
+                }
+                _ => {
+                    words.push(logical_words[i].text.clone());
+                    i += 1;
+                }
+            }
         }
-        return words.join(" ");
+        let separator = if self.is_vertical() { "" } else { " " };
+        return options.clean_line(&words.join(separator));
+    }
+
+    /// Returns the runs of inline math of the `Line`.
+    ///
+    /// A run is a sequence of consecutive operands (single-letter variables, numbers, raised or
+    /// lowered words) and operators. It is math when a relation or an operator stands between
+    /// two operands, as in "L = ∑ x", or inside a word between letters or digits, as in "x=1",
+    /// when it holds a letter of a mathematical font, or when a single letter carries a
+    /// superscript or subscript, as in "x²". Words of running text in any script are never
+    /// operands, so that footnote markers such as "Vaswani¹", "C++" or "snake_case" and Greek
+    /// prose are left alone.
+    ///
+    /// # Returns
+    ///
+    /// A vector of the text of each run with its bounding box, in reading order.
+    pub fn inline_math(&self) -> Vec<(String, Coordinate)> {
+        let words = self.logical_words();
+        return math_runs(&words)
+            .into_iter()
+            .map(|run| {
+                let run = &words[run];
+                let text = run.iter().map(|word| word.text.as_str()).collect::<Vec<&str>>();
+                let coordinate = run.iter().skip(1).fold(Coordinate::from(run[0]), |area, word| {
+                    area.union(&Coordinate::from(*word))
+                });
+                (text.join(" "), coordinate)
+            })
+            .collect();
+    }
+
+    /// Returns the text of the `Line` with bold and italic runs wrapped in Markdown emphasis.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the words of the line, consecutive words sharing the same emphasis
+    /// being wrapped together.
+    pub fn get_markdown_text(&self) -> String {
+        let mut runs: Vec<(bool, bool, Vec<String>)> = Vec::new();
+        for word in self.logical_words() {
+            match runs.last_mut() {
+                Some((bold, italic, texts)) if *bold == word.bold && *italic == word.italic => {
+                    texts.push(word.text.clone());
+                }
+                _ => runs.push((word.bold, word.italic, vec![word.text.clone()])),
+            }
+        }
+        let runs = runs
+            .iter()
+            .map(|(bold, italic, texts)| emphasize(&texts.join(" "), *bold, *italic))
+            .collect::<Vec<String>>();
+        return runs.join(" ");
     }
 }
 
+/// The `BlockAttr` enum represents the kind of content held by a block.
+///
+/// # Variants
+///
+/// * `Text` - Running text (the default).
+/// * `Equation` - A display equation, dominated by math symbols.
+/// * `Quote` - A quotation indented relative to the body text column.
+/// * `Title` - A section heading.
+/// * `Toc` - Entries of a table of contents printed in the document.
+/// * `Caption` - The caption of a figure or a table, such as "Figure 1: ...".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BlockAttr {
+    Text,
+    Equation,
+    Quote,
+    Title,
+    Toc,
+    Caption,
+}
+
 /// The `Block` struct represents a block of text in a PDF document.
 ///
 /// # Fields
@@ -136,7 +660,14 @@ impl Line {
 /// * `width` - The width of the block.
 /// * `height` - The height of the block.
 /// * `section` - The section of the document to which the block belongs.
-#[derive(Debug, Clone, PartialEq)]
+/// * `attr` - The kind of content held by the block.
+/// * `label` - The number of an equation block, such as "(3)", set by `detect_equation_labels`.
+/// * `continued` - Whether the block continues the paragraph ending the previous page, set by
+///   `detect_page_continuations`.
+/// * `tags` - Free-form labels for domain-specific kinds of blocks, set by custom passes such as
+///   a `PostProcessor` and kept by the exports.
+/// * `text_cache` - The text of the block, filled by `prebuild_text` and cleared when lines change.
+#[derive(Debug, Clone)]
 pub struct Block {
     pub lines: Vec<Line>,
     pub x: f32,
@@ -144,8 +675,32 @@ pub struct Block {
     pub width: f32,
     pub height: f32,
     pub section: String,
+    pub attr: BlockAttr,
+    pub label: Option<String>,
+    pub continued: bool,
+    pub tags: Vec<String>,
+    text_cache: OnceLock<String>,
+}
+
+impl PartialEq for Block {
+    /// Compares the blocks field by field, whether their text is cached or not.
+    fn eq(&self, other: &Self) -> bool {
+        return self.lines == other.lines
+            && self.x == other.x
+            && self.y == other.y
+            && self.width == other.width
+            && self.height == other.height
+            && self.section == other.section
+            && self.attr == other.attr
+            && self.label == other.label
+            && self.continued == other.continued
+            && self.tags == other.tags;
+    }
 }
 
+/// The smallest share of flush-right lines for a block to be considered justified.
+const JUSTIFIED_LINE_RATIO: f32 = 0.75;
+
 impl Block {
     /// Creates a new `Block` instance.
     ///
@@ -167,6 +722,11 @@ impl Block {
             width: width,
             height: height,
             section: String::new(),
+            attr: BlockAttr::Text,
+            label: None,
+            continued: false,
+            tags: Vec::new(),
+            text_cache: OnceLock::new(),
         }
     }
     /// Adds a new `Line` to the `Block`.
@@ -179,163 +739,2292 @@ impl Block {
     /// * `height` - The height of the line.
     pub fn add_line(&mut self, x: f32, y: f32, width: f32, height: f32) {
         self.lines.push(Line::new(x, y, width, height));
+        self.invalidate_text();
     }
 
-    /// Returns the concatenated text of all `Line` instances in the `Block`.
+    /// Returns the bounding box of the `Block`, built from its position and size.
     ///
     /// # Returns
     ///
-    /// A `String` containing the text of all lines in the block, with hyphenated line endings removed.
-    pub fn get_text(&self) -> String {
-        let mut text = String::new();
-        for line in &self.lines {
-                text = text.trim().to_string();
-                if text.ends_with("-") {
-                    // 意味を壊すよりも、表記上の崩壊に逃げる            
-                    text = text.trim().trim_end_matches("-").to_string();
-                    // ハイフン終わりの時はスペースいらない
-                } else { 
-                    text.push_str(" ");
-                }
-        //      text = text.trim().trim_end_matches("-").to_string();
-        //     text.push_str(" ");
-            text.push_str(&line.get_text());
-        }
-
-        text = fix_based_hyphen(&text);
-        return text.trim().to_string();
+    /// A `Coordinate` representing the block.
+    pub fn coordinate(&self) -> Coordinate {
+        return Coordinate::from_object(self.x, self.y, self.width, self.height);
     }
-}
-
-/// The `Page` struct represents a page in a PDF document.
-///
-/// # Fields
-///
-/// * `blocks` - A vector of `Block` structs that make up the page.
-/// * `width` - The width of the page.
-/// * `height` - The height of the page.
-#[derive(Debug, Clone, PartialEq)]
-pub struct Page {
-    pub blocks: Vec<Block>,
-    pub width: f32,
-    pub height: f32,
-    pub tables: Vec<Coordinate>,
-    pub page_number: PageNumber,
-    pub number_of_columns: i8,
-}
 
-impl Page {
-    /// Creates a new `Page` instance.
-    ///
-    /// # Arguments
+    /// Appends the lines of another `Block` to this one and grows the bounding box to cover both.
     ///
-    /// * `width` - The width of the page.
-    /// * `height` - The height of the page.
-    /// * `page_number` - The page number.
+    /// The tags of the other block missing from this one are appended as well.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// A new `Page` instance with the specified dimensions and page number.
-    pub fn new(width: f32, height: f32, page_number: PageNumber) -> Page {
-        Page {
-            blocks: Vec::new(),
-            width: width,
-            height: height,
-            tables: Vec::new(),
-            page_number,
-            number_of_columns: 1,
+    /// * `other` - The `Block` to merge into this one.
+    pub fn merge(&mut self, other: Block) {
+        let coord = self.coordinate().union(&other.coordinate());
+        self.x = coord.top_left.x;
+        self.y = coord.top_left.y;
+        self.width = coord.width();
+        self.height = coord.height();
+        self.lines.extend(other.lines);
+        for tag in other.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
         }
+        self.invalidate_text();
     }
 
-    /// Adds a new `Block` to the `Page`.
+    /// Returns the median of the dominant font sizes of the lines in the `Block`.
     ///
-    /// # Arguments
+    /// # Returns
     ///
-    /// * `x` - The x-coordinate of the top-left corner of the block.
-    /// * `y` - The y-coordinate of the top-left corner of the block.
-    /// * `width` - The width of the block.
-    /// * `height` - The height of the block.
-    pub fn add_block(&mut self, x: f32, y: f32, width: f32, height: f32) {
-        self.blocks.push(Block::new(x, y, width, height));
+    /// A `f32` representing the typical font size of the block, or `0.0` for an empty block.
+    pub fn font_size(&self) -> f32 {
+        let values = self.lines.iter().map(|line| line.dominant_font_size()).collect::<Vec<f32>>();
+        return median(&values);
     }
 
-    /// Returns the concatenated text of all `Block` instances in the `Page`.
+    /// Returns the dominant line spacing (leading) of the `Block`.
+    ///
+    /// The spacing is the median of the vertical distances between the baselines of consecutive
+    /// lines, so that a single wider gap does not shift it.
     ///
     /// # Returns
     ///
-    /// A `String` containing the text of all blocks in the page, separated by double newlines.
-    pub fn get_text(&self) -> String {
-        let mut text = String::new();
-        for block in &self.blocks {
-            text.push_str(&block.get_text());
-            text.push_str("\n\n");
+    /// The line spacing as an `f32`, or `None` if the block has fewer than two lines.
+    pub fn line_spacing(&self) -> Option<f32> {
+        let distances = self
+            .lines
+            .windows(2)
+            .map(|pair| (pair[1].baseline() - pair[0].baseline()).abs())
+            .collect::<Vec<f32>>();
+        if distances.is_empty() {
+            return None;
         }
-        return text;
+        return Some(median(&distances));
     }
 
-    /// Returns the y-coordinate of the topmost line in the page.
+    /// Returns whether the text of the `Block` is justified rather than ragged-right.
+    ///
+    /// The last line of each paragraph is ignored, a paragraph ending where the next line is
+    /// indented or at the end of the block. The block is justified when at least
+    /// `JUSTIFIED_LINE_RATIO` of the remaining lines end within half a font size of the right
+    /// edge of the widest line, and at least two lines are left to compare.
     ///
     /// # Returns
     ///
-    /// A `f32` representing the y-coordinate of the topmost line.
-    pub fn top(&self) -> f32 {
-        let mut values: Vec<f32> = Vec::new();
-        for block in &self.blocks {
-            for line in &block.lines {
-                values.push(line.y);
-            }
+    /// A `bool` indicating whether the block is justified.
+    pub fn is_justified(&self) -> bool {
+        let font_size = self.font_size();
+        let tolerance = font_size * 0.5;
+        let left = self.lines.iter().map(|line| line.x).fold(f32::MAX, f32::min);
+        let right = self.lines.iter().map(|line| line.x + line.width).fold(f32::MIN, f32::max);
+        let inner_lines = self
+            .lines
+            .windows(2)
+            .filter(|pair| pair[1].x - left <= tolerance)
+            .map(|pair| &pair[0])
+            .collect::<Vec<&Line>>();
+        if inner_lines.len() < 2 {
+            return false;
         }
-        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        return values.first().unwrap().clone();
+        let flush =
+            inner_lines.iter().filter(|line| right - (line.x + line.width) <= tolerance).count();
+        return flush as f32 >= inner_lines.len() as f32 * JUSTIFIED_LINE_RATIO;
     }
 
-    /// Returns the y-coordinate of the bottommost line in the page.
+    /// Returns how consistent the geometry of the lines of the `Block` is.
+    ///
+    /// The score averages three shares: the lines starting within two line heights of the
+    /// median left edge, the lines whose height is within 30% of the median height, and the
+    /// pairs of consecutive lines whose boxes overlap by less than half the smaller height. A
+    /// low score hints at a mis-segmented block.
     ///
     /// # Returns
     ///
-    /// A `f32` representing the y-coordinate of the bottommost line.
-    pub fn bottom(&self) -> f32 {
+    /// A `f32` between `0.0` and `1.0`, higher for a more consistent block, `1.0` for a block of
+    /// fewer than two lines.
+    pub fn geometry_score(&self) -> f32 {
+        if self.lines.len() < 2 {
+            return 1.0;
+        }
+        let height = median(&self.lines.iter().map(|line| line.height).collect::<Vec<f32>>());
+        let left = median(&self.lines.iter().map(|line| line.x).collect::<Vec<f32>>());
+        let count = self.lines.len() as f32;
+
+        let aligned =
+            self.lines.iter().filter(|line| (line.x - left).abs() <= height * 2.0).count();
+        let even =
+            self.lines.iter().filter(|line| (line.height - height).abs() <= height * 0.3).count();
+        let mut lines = self.lines.iter().collect::<Vec<&Line>>();
+        lines.sort_by(|a, b| a.y.total_cmp(&b.y));
+        let overlapping = lines
+            .windows(2)
+            .filter(|pair| {
+                let (upper, lower) = (pair[0], pair[1]);
+                let side_by_side =
+                    upper.x >= lower.x + lower.width || lower.x >= upper.x + upper.width;
+                let overlap = upper.y + upper.height - lower.y;
+                !side_by_side && overlap > upper.height.min(lower.height) * 0.5
+            })
+            .count();
+
+        let separated = 1.0 - overlapping as f32 / (count - 1.0);
+        return (aligned as f32 / count + even as f32 / count + separated) / 3.0;
+    }
+
+    /// Returns the key sorting the `Block` in reading order among the given columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - The column areas of the page, from left to right.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the index of the column overlapping the block the most (`columns.len()` when
+    /// the block lies outside all columns) and the y-coordinate of the top of the block.
+    pub fn reading_order_key(&self, columns: &[Coordinate]) -> (usize, OrderedFloat<f32>) {
+        let column = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| (i, self.coordinate().intersection(column).get_area()))
+            .filter(|(_, area)| *area > 0.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+            .map_or(columns.len(), |(i, _)| i);
+        return (column, OrderedFloat(self.y));
+    }
+
+    /// Returns the concatenated text of all `Line` instances in the `Block`.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the text of all lines in the block, with hyphenated line endings removed.
+    pub fn get_text(&self) -> String {
+        return self.get_text_with(&TextOptions::default());
+    }
+
+    /// Returns the concatenated text of all `Line` instances in the `Block`, cleaned according to
+    /// the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `TextOptions` controlling whitespace handling.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the text of all lines in the block, with hyphenated line endings removed.
+    pub fn get_text_with(&self, options: &TextOptions) -> String {
+        if *options == TextOptions::default() {
+            if let Some(text) = self.text_cache.get() {
+                return text.clone();
+            }
+        }
+        let text = join_lines(self.lines.iter().map(|line| line.get_text_with(options)));
+        if options.collapse_whitespace {
+            return collapse_whitespace(&text);
+        }
+        return text;
+    }
+
+    /// Builds the text of the `Block` once and caches it for later `get_text` calls.
+    ///
+    /// The cache is cleared by `add_line`, `merge` and `invalidate_text`. Call
+    /// `invalidate_text` after editing `lines` or their words directly.
+    pub fn prebuild_text(&mut self) {
+        self.invalidate_text();
+        let text = self.get_text();
+        let _ = self.text_cache.set(text);
+    }
+
+    /// Clears the cached text of the `Block`.
+    pub fn invalidate_text(&mut self) {
+        self.text_cache.take();
+    }
+
+    /// Shrinks the bounding boxes of the lines and of the `Block` to the words they hold.
+    ///
+    /// Lines without words keep their box, and so does a block without any word.
+    pub fn fit_to_words(&mut self) {
+        for line in self.lines.iter_mut() {
+            let Some(coord) = line.words.iter().map(Coordinate::from).reduce(|a, b| a.union(&b))
+            else {
+                continue;
+            };
+            line.x = coord.top_left.x;
+            line.y = coord.top_left.y;
+            line.width = coord.width();
+            line.height = coord.height();
+        }
+        let words = self.lines.iter().filter(|line| !line.words.is_empty());
+        let Some(coord) = words.map(Coordinate::from).reduce(|a, b| a.union(&b)) else {
+            return;
+        };
+        self.x = coord.top_left.x;
+        self.y = coord.top_left.y;
+        self.width = coord.width();
+        self.height = coord.height();
+    }
+
+    /// Splits the text of the `Block` into sentences.
+    ///
+    /// Sentences end with `.`, `!` or `?` followed by a capitalized word or a number. Common
+    /// abbreviations in papers ("e.g.", "et al.", "Fig.", "Dr.", ...) and initials do not end
+    /// a sentence.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `String`s, one per sentence, computed on the hyphenation-fixed block text.
+    pub fn sentences(&self) -> Vec<String> {
+        return split_sentences(&self.get_text());
+    }
+
+    /// Returns the text of the `Block` with bold and italic runs wrapped in Markdown emphasis.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the Markdown text of all lines in the block, with hyphenated line
+    /// endings removed. Quote blocks are rendered as a Markdown blockquote.
+    pub fn get_markdown_text(&self) -> String {
+        let text = join_lines(self.lines.iter().map(|line| line.get_markdown_text()));
+        if self.attr == BlockAttr::Quote {
+            return format!("> {}", text);
+        }
+        return text;
+    }
+}
+
+const ABBREVIATIONS: [&str; 30] = [
+    "e.g.", "i.e.", "al.", "cf.", "vs.", "viz.", "approx.", "resp.", "fig.", "figs.", "eq.",
+    "eqs.", "sec.", "secs.", "tab.", "ref.", "refs.", "ch.", "no.", "nos.", "vol.", "pp.", "dr.",
+    "mr.", "mrs.", "ms.", "prof.", "jr.", "st.", "inc.",
+];
+
+fn is_sentence_end(token: &str, next: Option<&&str>) -> bool {
+    let stripped = token.trim_end_matches(|c: char| "\"')]".contains(c));
+    if !stripped.ends_with(['.', '!', '?']) {
+        return false;
+    }
+    if stripped.ends_with('.') {
+        let word = stripped.trim_start_matches(|c: char| "\"'([".contains(c));
+        if ABBREVIATIONS.contains(&word.to_lowercase().as_str()) {
+            return false;
+        }
+        // Initials such as "J." or "A.B."
+        let letters = word.chars().filter(|c| *c != '.').collect::<Vec<char>>();
+        if letters.iter().all(|c| c.is_uppercase()) && letters.len() == word.matches('.').count() {
+            return false;
+        }
+    }
+    return match next {
+        Some(next) => next
+            .trim_start_matches(|c: char| "\"'([".contains(c))
+            .starts_with(|c: char| c.is_uppercase() || c.is_ascii_digit()),
+        None => true,
+    };
+}
+
+fn split_sentences(text: &str) -> Vec<String> {
+    let tokens = text.split_whitespace().collect::<Vec<&str>>();
+    let mut sentences: Vec<String> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        current.push(token);
+        if is_sentence_end(token, tokens.get(i + 1)) {
+            sentences.push(current.join(" "));
+            current.clear();
+        }
+    }
+    if !current.is_empty() {
+        sentences.push(current.join(" "));
+    }
+    return sentences;
+}
+
+/// Parses a number written with an optional sign, thousands separators and decimal point.
+///
+/// Commas between groups of three digits are thousands separators, so that "1,234" reads as
+/// 1234 and "1,234.5" as 1234.5. Otherwise, the single comma of a number without a decimal
+/// point is read as the decimal separator when `decimal_comma` is set, so that "12,5" reads as
+/// 12.5, and makes the text not a number when it is not. Both the ASCII hyphen and the Unicode
+/// minus sign are accepted, and only finite values are returned.
+///
+/// # Arguments
+///
+/// * `text` - The text to parse, without surrounding whitespace.
+/// * `decimal_comma` - Whether a comma may stand for the decimal point.
+///
+/// # Returns
+///
+/// The value of the number, or `None` if the whole text is not a number.
+pub(crate) fn parse_numeric(text: &str, decimal_comma: bool) -> Option<f64> {
+    let (sign, unsigned) = match text.strip_prefix(['-', '−']) {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", text.strip_prefix('+').unwrap_or(text)),
+    };
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    let is_grouped = |part: &str| {
+        let groups = part.split(',').collect::<Vec<&str>>();
+        return groups.iter().all(|group| is_digits(group))
+            && (groups.len() == 1
+                || (!groups[0].is_empty()
+                    && groups[0].len() <= 3
+                    && groups[1..].iter().all(|group| group.len() == 3)));
+    };
+    let (integer, fraction) = match unsigned.split_once('.') {
+        Some(parts) => parts,
+        None if decimal_comma && !is_grouped(unsigned) => {
+            unsigned.split_once(',').unwrap_or((unsigned, ""))
+        }
+        None => (unsigned, ""),
+    };
+    if !is_grouped(integer) || !is_digits(fraction) || (integer.is_empty() && fraction.is_empty()) {
+        return None;
+    }
+    let value = format!("{}{}.{}", sign, integer.replace(',', ""), fraction).parse::<f64>().ok()?;
+    return Some(value).filter(|value| value.is_finite());
+}
+
+/// Determines whether a character is a math relation or operator, such as "=", "≤" or "∑".
+pub(crate) fn is_math_operator(c: char) -> bool {
+    return "=+<>^−×÷·±∓∑∏∫∮∂∇√≤≥≈≠≡∼≃∝∈∉∋⊂⊃⊆⊇∪∩∧∨¬∀∃→←↔⇒⇐⇔↦⊕⊗".contains(c);
+}
+
+/// Determines whether a character is a superscript or subscript, such as "²" or "ᵢ".
+fn is_script_char(c: char) -> bool {
+    return "²³¹ᵢⱼ".contains(c) || ('\u{2070}'..='\u{209F}').contains(&c);
+}
+
+/// Returns the ranges of the words forming runs of inline math, see `Line::inline_math`.
+fn math_runs(words: &[&Word]) -> Vec<std::ops::Range<usize>> {
+    let text = |word: &Word| word.text.trim_matches(|c: char| "()[]{},;".contains(c)).to_string();
+    let is_operator = |word: &Word| {
+        let text = text(word);
+        return !text.is_empty() && text.chars().all(is_math_operator);
+    };
+    let is_expression = |word: &Word| {
+        let chars = text(word).chars().collect::<Vec<char>>();
+        return chars.windows(3).any(|window| {
+            window[0].is_alphanumeric()
+                && is_math_operator(window[1])
+                && window[2].is_alphanumeric()
+        });
+    };
+    let is_math_font =
+        |word: &Word| word.text.chars().any(|c| ('\u{1D400}'..='\u{1D7FF}').contains(&c));
+    // A single letter, possibly carrying its superscripts or subscripts, such as "x" or "x²"
+    let is_variable = |word: &Word| {
+        let text = text(word);
+        let mut chars = text.chars();
+        return match chars.next() {
+            Some(c) if c.is_alphabetic() && !"aAI".contains(c) => chars.all(is_script_char),
+            _ => false,
+        };
+    };
+    let is_scripted_variable =
+        |word: &Word| is_variable(word) && word.text.chars().any(is_script_char);
+    let is_number = |word: &Word| parse_numeric(&text(word), false).is_some();
+    let is_scripted = |word: &Word| word.script != Script::Normal;
+    let is_operand = |word: &Word| {
+        is_variable(word)
+            || is_number(word)
+            || is_scripted(word)
+            || is_expression(word)
+            || is_math_font(word)
+            || text(word) == "∞"
+    };
+
+    let mut runs = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let start = i;
+        while i < words.len() && (is_operand(words[i]) || is_operator(words[i])) {
+            i += 1;
+        }
+        let run = &words[start..i];
+        let related = (0..run.len()).any(|k| {
+            is_operator(run[k])
+                && run[..k].iter().any(|word| is_operand(word))
+                && run[k + 1..].iter().any(|word| is_operand(word))
+        });
+        let scripted_variable = run.iter().any(|word| is_scripted_variable(word))
+            || run.windows(2).any(|pair| is_variable(pair[0]) && is_scripted(pair[1]));
+        let signaled = run.iter().any(|word| is_expression(word) || is_math_font(word));
+        if related || scripted_variable || signaled {
+            runs.push(start..i);
+        }
+        i = i.max(start + 1);
+    }
+    return runs;
+}
+
+fn join_lines<I: Iterator<Item = String>>(lines: I) -> String {
+    let mut text = String::new();
+    for line in lines {
+        text = text.trim().to_string();
+        if text.ends_with("-") {
+            // 意味を壊すよりも、表記上の崩壊に逃げる
+            text = text.trim().trim_end_matches("-").to_string();
+            // ハイフン終わりの時はスペースいらない
+        } else {
+            text.push_str(" ");
+        }
+        text.push_str(&line);
+    }
+
+    text = fix_based_hyphen(&text);
+    return text.trim().to_string();
+}
+
+/// The `WritingMode` enum represents the direction in which the text of a page runs.
+///
+/// # Variants
+///
+/// * `Horizontal` - Lines run left-to-right and are read top-to-bottom (the default).
+/// * `Vertical` - Lines run top-to-bottom and are read right-to-left, as in Japanese tategaki.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WritingMode {
+    Horizontal,
+    Vertical,
+}
+
+/// The `CoordinateOrigin` enum represents the corner of the page the coordinates are measured
+/// from.
+///
+/// # Variants
+///
+/// * `TopLeft` - The origin is the top-left corner and y grows downward, as in the pdftotext
+///   HTML (the default).
+/// * `BottomLeft` - The origin is the bottom-left corner and y grows upward, as in PDF user
+///   space. The `y` of a box is then the distance from the bottom of the page to its bottom
+///   edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum CoordinateOrigin {
+    #[default]
+    TopLeft,
+    BottomLeft,
+}
+
+/// The `CropMode` enum represents how `Page::crop_with` handles the blocks crossing the region.
+///
+/// # Variants
+///
+/// * `Whole` - Blocks overlapping the region are kept whole (the default).
+/// * `Clip` - Only the words lying mostly inside the region are kept, and the boxes of their
+///   lines and blocks are shrunk to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum CropMode {
+    #[default]
+    Whole,
+    Clip,
+}
+
+/// The largest coverage of a gutter between columns, relative to the mean coverage of the text.
+const GUTTER_MAX_COVERAGE_RATIO: f32 = 0.15;
+
+/// The smallest width of a column, relative to the width spanned by the words of the page.
+const MIN_COLUMN_WIDTH_RATIO: f32 = 0.2;
+
+/// The number of PDF points in an inch, the unit of pdftotext coordinates.
+const POINTS_PER_INCH: f32 = 72.0;
+
+/// The `Page` struct represents a page in a PDF document.
+///
+/// # Fields
+///
+/// * `blocks` - A vector of `Block` structs that make up the page.
+/// * `width` - The width of the page.
+/// * `height` - The height of the page.
+/// * `writing_mode` - The direction in which the text of the page runs.
+/// * `resolution` - The resolution in DPI of the page renders, used to convert coordinates
+///   (in PDF points) into pixels of those renders.
+/// * `margin_text` - The text of the blocks dropped for lying outside the text area, such as
+///   running headers or the arXiv stamp.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub blocks: Vec<Block>,
+    pub width: f32,
+    pub height: f32,
+    pub tables: Vec<Coordinate>,
+    pub page_number: PageNumber,
+    pub number_of_columns: i8,
+    pub writing_mode: WritingMode,
+    pub resolution: u32,
+    pub margin_text: Vec<String>,
+}
+
+impl Page {
+    /// Creates a new `Page` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the page.
+    /// * `height` - The height of the page.
+    /// * `page_number` - The page number.
+    ///
+    /// # Returns
+    ///
+    /// A new `Page` instance with the specified dimensions and page number.
+    pub fn new(width: f32, height: f32, page_number: PageNumber) -> Page {
+        Page {
+            blocks: Vec::new(),
+            width: width,
+            height: height,
+            tables: Vec::new(),
+            page_number,
+            number_of_columns: 1,
+            writing_mode: WritingMode::Horizontal,
+            resolution: POINTS_PER_INCH as u32,
+            margin_text: Vec::new(),
+        }
+    }
+
+    /// Returns the number of pixels per PDF point at the resolution of the `Page`.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` representing the scale, `1.0` at 72 DPI.
+    pub fn scale(&self) -> f32 {
+        return self.resolution as f32 / POINTS_PER_INCH;
+    }
+
+    /// Converts a coordinate of the `Page` into pixels of a render at the page resolution.
+    ///
+    /// # Arguments
+    ///
+    /// * `coord` - The coordinate in PDF points.
+    ///
+    /// # Returns
+    ///
+    /// A `Coordinate` in pixels.
+    pub fn to_pixels(&self, coord: &Coordinate) -> Coordinate {
+        return coord.scale(self.scale(), self.scale());
+    }
+
+    /// Adds a new `Block` to the `Page`.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The x-coordinate of the top-left corner of the block.
+    /// * `y` - The y-coordinate of the top-left corner of the block.
+    /// * `width` - The width of the block.
+    /// * `height` - The height of the block.
+    pub fn add_block(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.blocks.push(Block::new(x, y, width, height));
+    }
+
+    /// Returns the areas of the text columns of the `Page`, from left to right.
+    ///
+    /// # Returns
+    ///
+    /// A vector with the two halves of the page for two-column pages, or the whole page.
+    pub fn column_areas(&self) -> Vec<Coordinate> {
+        if self.number_of_columns == 2 {
+            let middle = self.width / 2.0;
+            return vec![
+                Coordinate::from_rect(0.0, 0.0, middle, self.height),
+                Coordinate::from_rect(middle, 0.0, self.width, self.height),
+            ];
+        }
+        return vec![Coordinate::from_rect(0.0, 0.0, self.width, self.height)];
+    }
+
+    /// Estimates the number of text columns of the `Page` from its vertical projection profile.
+    ///
+    /// The number of words covering each point of the x-axis is counted, and a gutter is a run
+    /// of points covered by less than `GUTTER_MAX_COVERAGE_RATIO` of the mean coverage, at
+    /// least as wide as the median word height. Full-width headings and captions only add a
+    /// few words across the gutters, so they do not hide them. Gutters leaving a column
+    /// narrower than `MIN_COLUMN_WIDTH_RATIO` of the text width, such as the gap before line
+    /// numbers or margin notes, are ignored.
+    ///
+    /// # Returns
+    ///
+    /// A `usize` representing the number of columns, `0` for a page without any word.
+    pub fn column_count(&self) -> usize {
+        let words = self
+            .blocks
+            .iter()
+            .flat_map(|block| block.lines.iter())
+            .flat_map(|line| line.words.iter())
+            .filter(|word| word.width > 0.0)
+            .collect::<Vec<&Word>>();
+        if words.is_empty() {
+            return 0;
+        }
+        let left = words.iter().map(|word| word.x).fold(f32::MAX, f32::min).floor();
+        let right = words.iter().map(|word| word.x + word.width).fold(f32::MIN, f32::max).ceil();
+        let mut coverage = vec![0usize; (right - left) as usize + 1];
+        for word in words.iter() {
+            let start = (word.x - left).floor() as usize;
+            let end = ((word.x + word.width - left).ceil() as usize).min(coverage.len());
+            for count in coverage[start..end].iter_mut() {
+                *count += 1;
+            }
+        }
+        let mean = coverage.iter().sum::<usize>() as f32 / coverage.len() as f32;
+        let heights = words.iter().map(|word| word.height).collect::<Vec<f32>>();
+        let min_gutter = median(&heights).max(1.0);
+        let min_column = coverage.len() as f32 * MIN_COLUMN_WIDTH_RATIO;
+
+        let mut columns = 1;
+        let mut column_start = 0;
+        let mut gutter_start: Option<usize> = None;
+        for (x, count) in coverage.iter().enumerate() {
+            if (*count as f32) < mean * GUTTER_MAX_COVERAGE_RATIO {
+                gutter_start.get_or_insert(x);
+                continue;
+            }
+            if let Some(start) = gutter_start.take() {
+                let is_gutter = (x - start) as f32 >= min_gutter
+                    && (start - column_start) as f32 >= min_column
+                    && (coverage.len() - x) as f32 >= min_column;
+                if is_gutter {
+                    columns += 1;
+                    column_start = x;
+                }
+            }
+        }
+        return columns;
+    }
+
+    /// Returns the blocks of a multi-column `Page` spanning the whole text width.
+    ///
+    /// The dominant column width is the median width of the blocks of the page. A block is
+    /// flagged when it is more than 1.5 times as wide as a column and covers at least 80% of the
+    /// width spanned by all the blocks, as wide tables, figures or banners do.
+    ///
+    /// # Returns
+    ///
+    /// A vector of references to the full-width blocks in page order, empty for single-column
+    /// pages.
+    pub fn flag_full_width_blocks(&self) -> Vec<&Block> {
+        if self.number_of_columns < 2 || self.blocks.is_empty() {
+            return Vec::new();
+        }
+        let widths = self.blocks.iter().map(|block| block.width).collect::<Vec<f32>>();
+        let column_width = median(&widths);
+        let left = self.blocks.iter().map(|block| block.x).fold(f32::MAX, f32::min);
+        let right = self.blocks.iter().map(|block| block.x + block.width).fold(f32::MIN, f32::max);
+        return self
+            .blocks
+            .iter()
+            .filter(|block| block.width > column_width * 1.5 && block.width >= (right - left) * 0.8)
+            .collect();
+    }
+
+    /// Returns the blocks of the `Page` in reading order.
+    ///
+    /// Blocks are sorted by column, then from top to bottom. Vertical pages keep the order set
+    /// by `reorder_vertical_text`.
+    ///
+    /// # Returns
+    ///
+    /// A vector of references to the blocks of the page in reading order.
+    pub fn blocks_in_reading_order(&self) -> Vec<&Block> {
+        let mut blocks = self.blocks.iter().collect::<Vec<&Block>>();
+        if self.writing_mode == WritingMode::Vertical {
+            return blocks;
+        }
+        let columns = self.column_areas();
+        blocks.sort_by_key(|block| block.reading_order_key(&columns));
+        return blocks;
+    }
+
+    /// Serializes the blocks of the `Page` with their position in the reading order.
+    ///
+    /// Blocks are listed in page order, each with its `reading_order` index among the blocks
+    /// of the page, as sorted by `blocks_in_reading_order`, and the index of its `column` in
+    /// `column_areas` (the number of columns for blocks lying outside all of them). The other
+    /// fields are the same as in `exporter::to_jsonl`.
+    ///
+    /// # Returns
+    ///
+    /// A `String` with a JSON array of one object per block.
+    pub fn to_blocks_json(&self) -> String {
+        return self.to_blocks_json_with(CoordinateOrigin::TopLeft);
+    }
+
+    /// Serializes the blocks of the `Page` like `to_blocks_json`, with the bounding boxes
+    /// measured from the given origin.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The corner of the page the bounding boxes are measured from.
+    ///
+    /// # Returns
+    ///
+    /// A `String` with a JSON array of one object per block.
+    pub fn to_blocks_json_with(&self, origin: CoordinateOrigin) -> String {
+        let columns = self.column_areas();
+        let keys = self
+            .blocks
+            .iter()
+            .map(|block| block.reading_order_key(&columns))
+            .collect::<Vec<(usize, OrderedFloat<f32>)>>();
+        let mut order = (0..self.blocks.len()).collect::<Vec<usize>>();
+        if self.writing_mode != WritingMode::Vertical {
+            order.sort_by_key(|i| keys[*i]);
+        }
+        let mut reading_order = vec![0; self.blocks.len()];
+        for (rank, i) in order.into_iter().enumerate() {
+            reading_order[i] = rank;
+        }
+
+        let mut records = Vec::new();
+        for (index, block) in self.blocks.iter().enumerate() {
+            let (x1, y1, x2, y2) = block.coordinate().to_origin(origin, self.height).as_rect();
+            records.push(serde_json::json!({
+                "block": index,
+                "reading_order": reading_order[index],
+                "column": keys[index].0,
+                "attr": block.attr,
+                "section": block.section,
+                "tags": block.tags,
+                "bbox": [x1, y1, x2, y2],
+                "text": block.get_text(),
+            }));
+        }
+        return serde_json::Value::Array(records).to_string();
+    }
+
+    /// Returns the text of the words of the `Page` lying in a region.
+    ///
+    /// A word is kept when at least half of its bounding box lies inside the region, so that
+    /// words cut by the border of the region are only taken when mostly inside it.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - The area of the page to extract the text from.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the kept words in reading order, separated by single spaces.
+    pub fn text_in_region(&self, region: &Coordinate) -> String {
+        let mut words: Vec<&str> = Vec::new();
+        for block in self.blocks_in_reading_order() {
+            for line in block.lines.iter() {
+                for word in line.words.iter() {
+                    if Coordinate::from(word).overlap_ratio(region) >= 0.5 {
+                        words.push(word.text.as_str());
+                    }
+                }
+            }
+        }
+        return words.join(" ");
+    }
+
+    /// Returns a copy of the `Page` keeping only the blocks overlapping a region.
+    ///
+    /// Blocks crossing the border of the region are kept whole, see `crop_with` to clip them.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - The area of the page to keep.
+    ///
+    /// # Returns
+    ///
+    /// A new `Page` of the same size, whose coordinates are unchanged.
+    pub fn crop(&self, region: &Coordinate) -> Page {
+        return self.crop_with(region, CropMode::Whole);
+    }
+
+    /// Returns a copy of the `Page` keeping only the content overlapping a region.
+    ///
+    /// With `CropMode::Clip`, a word is kept when at least half of its bounding box lies inside
+    /// the region, as in `text_in_region`, and lines and blocks left without words are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - The area of the page to keep.
+    /// * `mode` - How the blocks crossing the border of the region are handled.
+    ///
+    /// # Returns
+    ///
+    /// A new `Page` of the same size, whose coordinates are unchanged.
+    pub fn crop_with(&self, region: &Coordinate, mode: CropMode) -> Page {
+        let mut page = self.clone();
+        page.blocks.clear();
+        for block in self.blocks.iter() {
+            if block.coordinate().overlap_ratio(region) <= 0.0 {
+                continue;
+            }
+            if mode == CropMode::Whole {
+                page.blocks.push(block.clone());
+                continue;
+            }
+            let mut lines: Vec<Line> = Vec::new();
+            for line in block.lines.iter() {
+                let words = line
+                    .words
+                    .iter()
+                    .filter(|word| Coordinate::from(*word).overlap_ratio(region) >= 0.5)
+                    .cloned()
+                    .collect::<Vec<Word>>();
+                let Some(coord) = words.iter().map(Coordinate::from).reduce(|a, b| a.union(&b))
+                else {
+                    continue;
+                };
+                let mut clipped = Line::new(
+                    coord.top_left.x,
+                    coord.top_left.y,
+                    coord.width(),
+                    coord.height(),
+                );
+                clipped.words = words;
+                lines.push(clipped);
+            }
+            let Some(coord) = lines.iter().map(Coordinate::from).reduce(|a, b| a.union(&b)) else {
+                continue;
+            };
+            let mut clipped = Block::new(
+                coord.top_left.x,
+                coord.top_left.y,
+                coord.width(),
+                coord.height(),
+            );
+            clipped.lines = lines;
+            clipped.section = block.section.clone();
+            clipped.attr = block.attr;
+            clipped.label = block.label.clone();
+            clipped.tags = block.tags.clone();
+            page.blocks.push(clipped);
+        }
+        return page;
+    }
+
+    /// Returns the blocks of the `Page` in grid order.
+    ///
+    /// Blocks are sorted from top to bottom, blocks whose tops are within half a font size of
+    /// the first block of a row being ordered from left to right. Unlike
+    /// `blocks_in_reading_order`, columns are not taken into account.
+    ///
+    /// # Returns
+    ///
+    /// A vector of references to the blocks of the page in grid order.
+    pub fn blocks_sorted(&self) -> Vec<&Block> {
+        let mut blocks = self.blocks.iter().collect::<Vec<&Block>>();
+        blocks.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+        let mut sorted: Vec<&Block> = Vec::with_capacity(blocks.len());
+        let mut row: Vec<&Block> = Vec::new();
+        for block in blocks {
+            if let Some(first) = row.first() {
+                if block.y - first.y > first.font_size() * 0.5 {
+                    row.sort_by(|a, b| a.x.total_cmp(&b.x));
+                    sorted.append(&mut row);
+                }
+            }
+            row.push(block);
+        }
+        row.sort_by(|a, b| a.x.total_cmp(&b.x));
+        sorted.append(&mut row);
+        return sorted;
+    }
+
+    /// Returns the blocks of the `Page` with the given attribute.
+    ///
+    /// # Arguments
+    ///
+    /// * `attr` - The kind of block to keep.
+    ///
+    /// # Returns
+    ///
+    /// A vector of references to the matching blocks, in page order.
+    pub fn blocks_with_attr(&self, attr: BlockAttr) -> Vec<&Block> {
+        return self.blocks.iter().filter(|block| block.attr == attr).collect();
+    }
+
+    /// Matches the blocks of the `Page` to reference boxes, such as a ground-truth layout.
+    ///
+    /// Pairs are matched greedily from the highest IoU down, each block and each reference box
+    /// being used at most once.
+    ///
+    /// # Arguments
+    ///
+    /// * `reference` - The reference boxes.
+    /// * `iou_threshold` - The minimum IoU for a block and a box to match.
+    ///
+    /// # Returns
+    ///
+    /// A vector with, for each block, the index of its matching reference box or `None`.
+    pub fn match_blocks(&self, reference: &[Coordinate], iou_threshold: f32) -> Vec<Option<usize>> {
+        let mut pairs: Vec<(usize, usize, f32)> = Vec::new();
+        for (i, block) in self.blocks.iter().enumerate() {
+            for (j, coord) in reference.iter().enumerate() {
+                let iou = block.coordinate().iou(coord);
+                if iou > 0.0 && iou >= iou_threshold {
+                    pairs.push((i, j, iou));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+        let mut matches: Vec<Option<usize>> = vec![None; self.blocks.len()];
+        let mut used = vec![false; reference.len()];
+        for (i, j, _) in pairs {
+            if matches[i].is_none() && !used[j] {
+                matches[i] = Some(j);
+                used[j] = true;
+            }
+        }
+        return matches;
+    }
+
+    /// Builds and caches the text of every `Block` in the `Page`.
+    ///
+    /// This is useful before exporting the same parse into several formats.
+    pub fn prebuild_text(&mut self) {
+        for block in self.blocks.iter_mut() {
+            block.prebuild_text();
+        }
+    }
+
+    /// Returns the concatenated text of all `Block` instances in the `Page`.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the text of all blocks in the page, separated by double newlines.
+    pub fn get_text(&self) -> String {
+        return self.get_text_with(&TextOptions::default());
+    }
+
+    /// Returns the concatenated text of all `Block` instances in the `Page`, cleaned according to
+    /// the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `TextOptions` controlling whitespace handling.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the text of all blocks in the page, separated by double newlines.
+    pub fn get_text_with(&self, options: &TextOptions) -> String {
+        let mut text = String::new();
+        for block in &self.blocks {
+            text.push_str(&block.get_text_with(options));
+            text.push_str("\n\n");
+        }
+        return text;
+    }
+
+    /// Returns the text of the lines of the `Page` one at a time, in reading order.
+    ///
+    /// Unlike `get_text`, the text of the whole page is never held at once, and hyphenated line
+    /// endings are left as they are since lines are not joined.
+    ///
+    /// # Returns
+    ///
+    /// An iterator yielding the text of each line.
+    pub fn lines_text(&self) -> impl Iterator<Item = String> + '_ {
+        return self
+            .blocks_in_reading_order()
+            .into_iter()
+            .flat_map(|block| block.lines.iter())
+            .map(|line| line.get_text());
+    }
+
+    /// Returns the y-coordinate of the topmost line in the page.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` representing the y-coordinate of the topmost line.
+    pub fn top(&self) -> f32 {
+        let mut values: Vec<f32> = Vec::new();
+        for block in &self.blocks {
+            for line in &block.lines {
+                values.push(line.y);
+            }
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        return values.first().unwrap().clone();
+    }
+
+    /// Returns the y-coordinate of the bottommost line in the page.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` representing the y-coordinate of the bottommost line.
+    pub fn bottom(&self) -> f32 {
+        let mut values: Vec<f32> = Vec::new();
+        for block in &self.blocks {
+            for line in &block.lines {
+                values.push(line.y + line.height);
+            }
+        }
+        values.sort_by(|a, b| b.total_cmp(a));
+        return values.first().unwrap().clone();
+    }
+
+    /// Returns the x-coordinate of the leftmost line in the page.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` representing the x-coordinate of the leftmost line.
+    pub fn left(&self) -> f32 {
         let mut values: Vec<f32> = Vec::new();
         for block in &self.blocks {
             for line in &block.lines {
-                values.push(line.y + line.height);
+                values.push(line.x);
+            }
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        return values.first().unwrap().clone();
+    }
+
+    /// Returns the x-coordinate of the rightmost line in the page.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` representing the x-coordinate of the rightmost line.
+    pub fn right(&self) -> f32 {
+        let mut values: Vec<f32> = Vec::new();
+        for block in &self.blocks {
+            for line in &block.lines {
+                values.push(line.x + line.width);
+            }
+        }
+        values.sort_by(|a, b| b.total_cmp(a));
+        return values.first().unwrap().clone();
+    }
+
+    /// Returns the fraction of the page area covered by text blocks.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` between `0.0` and `1.0`. Overlapping blocks are counted once per block, so the
+    /// sum of their areas is clamped to the page area.
+    pub fn text_coverage(&self) -> f32 {
+        let page_area = self.width * self.height;
+        if page_area <= 0.0 {
+            return 0.0;
+        }
+        let text_area = self.blocks.iter().map(|block| block.coordinate().get_area()).sum::<f32>();
+        return (text_area / page_area).clamp(0.0, 1.0);
+    }
+
+    /// Returns the minimum, maximum and median areas of the blocks of the page.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<BlockAreaStats>` with the area statistics, or `None` for a page without blocks.
+    pub fn block_area_stats(&self) -> Option<BlockAreaStats> {
+        let areas =
+            self.blocks.iter().map(|block| block.coordinate().get_area()).collect::<Vec<f32>>();
+        if areas.is_empty() {
+            return None;
+        }
+        return Some(BlockAreaStats {
+            min: areas.iter().cloned().fold(f32::MAX, f32::min),
+            max: areas.iter().cloned().fold(f32::MIN, f32::max),
+            median: median(&areas),
+        });
+    }
+}
+
+/// The `BlockAreaStats` struct summarizes the areas of the blocks of a page.
+///
+/// # Fields
+///
+/// * `min` - The area of the smallest block.
+/// * `max` - The area of the largest block.
+/// * `median` - The median block area.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BlockAreaStats {
+    pub min: f32,
+    pub max: f32,
+    pub median: f32,
+}
+
+/// The `Warning` enum represents a non-fatal issue met while parsing a document.
+///
+/// # Variants
+///
+/// * `MalformedPage` - A page without a valid size, which was skipped.
+/// * `MalformedBlock` - A block without a valid bounding box, which was skipped.
+/// * `MalformedLine` - A line without a valid bounding box, which was skipped.
+/// * `MalformedWord` - A word without a valid bounding box, which was dropped.
+/// * `EmptyPage` - A page without any text, which was dropped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Warning {
+    MalformedPage {
+        page_number: PageNumber,
+    },
+    MalformedBlock {
+        page_number: PageNumber,
+    },
+    MalformedLine {
+        page_number: PageNumber,
+    },
+    MalformedWord {
+        page_number: PageNumber,
+        text: String,
+    },
+    EmptyPage {
+        page_number: PageNumber,
+    },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Warning::MalformedPage { page_number } => {
+                write!(
+                    f,
+                    "page {}: skipped a page without a valid size",
+                    page_number
+                )
+            }
+            Warning::MalformedBlock { page_number } => {
+                write!(
+                    f,
+                    "page {}: skipped a block without a valid bounding box",
+                    page_number
+                )
+            }
+            Warning::MalformedLine { page_number } => {
+                write!(
+                    f,
+                    "page {}: skipped a line without a valid bounding box",
+                    page_number
+                )
+            }
+            Warning::MalformedWord { page_number, text } => {
+                write!(
+                    f,
+                    "page {}: dropped the word {:?} without a valid bounding box",
+                    page_number, text
+                )
+            }
+            Warning::EmptyPage { page_number } => {
+                write!(f, "page {}: dropped a page without text", page_number)
+            }
+        };
+    }
+}
+
+/// The `DocumentStats` struct holds the measures inferred while classifying the blocks.
+///
+/// # Fields
+///
+/// * `normal_font_size` - The median font size of the lines of the `Text` blocks of the document
+///   (of all its lines when there is none), `0.0` without text.
+/// * `text_area` - The body text area shared by the pages, as returned by `get_text_area`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentStats {
+    pub normal_font_size: f32,
+    pub text_area: Coordinate,
+}
+
+impl DocumentStats {
+    /// Creates the statistics of a document.
+    ///
+    /// # Arguments
+    ///
+    /// * `pages` - The pages of the document.
+    /// * `text_area` - The body text area used to classify the blocks.
+    ///
+    /// # Returns
+    ///
+    /// A new `DocumentStats` instance.
+    pub fn new(pages: &[Page], text_area: &Coordinate) -> DocumentStats {
+        let blocks = pages.iter().flat_map(|page| page.blocks.iter()).collect::<Vec<&Block>>();
+        let font_sizes_of = |attr: Option<BlockAttr>| {
+            return blocks
+                .iter()
+                .filter(|block| attr.is_none_or(|attr| block.attr == attr))
+                .flat_map(|block| block.lines.iter())
+                .map(|line| line.dominant_font_size())
+                .collect::<Vec<f32>>();
+        };
+        let mut font_sizes = font_sizes_of(Some(BlockAttr::Text));
+        if font_sizes.is_empty() {
+            font_sizes = font_sizes_of(None);
+        }
+        return DocumentStats {
+            normal_font_size: median(&font_sizes),
+            text_area: text_area.clone(),
+        };
+    }
+}
+
+/// The smallest height of a figure, relative to the font size of its caption.
+const FIGURE_MIN_HEIGHT_RATIO: f32 = 3.0;
+
+/// The largest share of the area of a figure covered by words, such as axis labels.
+const FIGURE_MAX_TEXT_RATIO: f32 = 0.3;
+
+/// The start of a figure caption, such as "Figure 1" or "Fig. 2".
+static FIGURE_CAPTION_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:Figure|Fig\.)\s*\d+").unwrap());
+
+/// The start of a keywords line, such as "Keywords:" or "Index Terms—".
+static KEYWORDS_PREFIX_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^\s*(keywords|key words|index terms)\s*[:\-—–.]?").unwrap());
+
+/// A group of e-mail users sharing a domain, such as "{vaswani, shazeer}@google.com".
+static GROUPED_EMAIL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{([^{}@]+)\}\s*@\s*([\w-]+(?:\.[\w-]+)+)").unwrap());
+
+/// A single e-mail address, such as "avaswani@google.com".
+static EMAIL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+").unwrap());
+
+/// A separator between two author names: a comma, a semicolon, "and" or "&".
+static AUTHOR_SEPARATOR_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r",|;|\band\b|&").unwrap());
+
+/// An arXiv stamp, capturing the current ("1706.03762v7") or old ("hep-th/9901001") identifier.
+static ARXIV_ID_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)arXiv:\s*(\d{4}\.\d{4,5}(?:v\d+)?|[a-z\-]+(?:\.[A-Z]{2})?/\d{7}(?:v\d+)?)")
+        .unwrap()
+});
+
+/// A year between 1900 and 2099.
+static YEAR_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(?:19|20)\d{2}\b").unwrap());
+
+/// A known venue followed by its year, such as "NeurIPS 2017" or "ACL'20".
+static KNOWN_VENUE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\b(?:NeurIPS|NIPS|ICML|ICLR|ACL|EMNLP|NAACL|COLING|CVPR|ICCV|ECCV|AAAI|IJCAI|KDD|SIGIR|WWW|CHI|TACL|JMLR)\s*'?(?:19|20)?\d{2}\b",
+    )
+    .unwrap()
+});
+
+/// A phrase introducing a venue, such as "Published in" or "Proceedings".
+static VENUE_PHRASE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(?:published (?:in|as|at)|accepted (?:at|to|by|in)|to appear in|proceedings|conference|workshop|symposium|journal|transactions)\b",
+    )
+    .unwrap()
+});
+
+/// The numbering of a heading, capturing its numbers, such as "2.1" in "2.1 Background".
+static HEADING_NUMBER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d+(?:\.\d+)*)\.?\s+").unwrap());
+
+/// The `Figure` struct represents a figure of a document paired with its caption.
+///
+/// # Fields
+///
+/// * `page_number` - The number of the page holding the figure.
+/// * `region` - The area of the figure on the page, in PDF points.
+/// * `caption` - The `Caption` block describing the figure.
+/// * `image_path` - The path of the rendered crop of the figure, set by
+///   `converter::render_figures`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Figure {
+    pub page_number: PageNumber,
+    pub region: Coordinate,
+    pub caption: Block,
+    pub image_path: Option<String>,
+}
+
+/// The `Document` struct represents a parsed PDF document.
+///
+/// The pages are owned by default. A `Document<&[Page]>`, built with `Document::from_slice`,
+/// borrows them instead and offers the same methods without copying the pages.
+///
+/// # Fields
+///
+/// * `pages` - The `Page` structs that make up the document, a `Vec<Page>` or a `&[Page]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document<P = Vec<Page>> {
+    pub pages: P,
+}
+
+impl From<Vec<Page>> for Document {
+    fn from(pages: Vec<Page>) -> Self {
+        Document::new(pages)
+    }
+}
+
+impl Document {
+    /// Creates a new `Document` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `pages` - The pages returned by the parser.
+    ///
+    /// # Returns
+    ///
+    /// A new `Document` instance holding the given pages.
+    pub fn new(pages: Vec<Page>) -> Document {
+        Document { pages }
+    }
+}
+
+impl<'a> Document<&'a [Page]> {
+    /// Creates a `Document` borrowing the given pages.
+    ///
+    /// # Arguments
+    ///
+    /// * `pages` - The pages returned by the parser.
+    ///
+    /// # Returns
+    ///
+    /// A new `Document` instance referring to the given pages.
+    pub fn from_slice(pages: &'a [Page]) -> Document<&'a [Page]> {
+        Document { pages }
+    }
+}
+
+impl<P: std::ops::Deref<Target = [Page]>> Document<P> {
+    /// Returns the blocks of the document with the given attribute.
+    ///
+    /// # Arguments
+    ///
+    /// * `attr` - The kind of block to keep.
+    ///
+    /// # Returns
+    ///
+    /// A vector of references to the matching blocks, in page order.
+    pub fn blocks_with_attr(&self, attr: BlockAttr) -> Vec<&Block> {
+        return self.pages.iter().flat_map(|page| page.blocks_with_attr(attr)).collect();
+    }
+
+    /// Returns the figures of the document along with their captions.
+    ///
+    /// Each `Caption` block starting with "Figure" or "Fig." is paired with the text-sparse area
+    /// right above it. The area spans the text of the columns the caption overlaps (the whole
+    /// text width for a caption across both columns), since captions are often narrower than
+    /// their figure, and runs from the lowest body block above the caption (a heading or a
+    /// paragraph of at least two lines) or the top of the text, down to the caption. Smaller
+    /// blocks in between, such as axis labels, are taken as part of the figure. The area is
+    /// discarded when it is lower than `FIGURE_MIN_HEIGHT_RATIO` caption font sizes or when more
+    /// than `FIGURE_MAX_TEXT_RATIO` of it is covered by words.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `Figure`s in page order, without rendered images.
+    pub fn figures(&self) -> Vec<Figure> {
+        let mut figures: Vec<Figure> = Vec::new();
+        for page in self.pages.iter() {
+            let text_top = page.blocks.iter().map(|block| block.y).fold(page.height, f32::min);
+            let columns = page.column_areas();
+            for caption in page.blocks.iter() {
+                if caption.attr != BlockAttr::Caption
+                    || !FIGURE_CAPTION_REGEX.is_match(caption.get_text().trim_start())
+                {
+                    continue;
+                }
+                let spanned = columns
+                    .iter()
+                    .filter(|column| column.intersection(&caption.coordinate()).get_area() > 0.0)
+                    .collect::<Vec<&Coordinate>>();
+                let in_columns = |block: &Block| {
+                    let center = block.x + block.width / 2.0;
+                    return spanned.iter().any(|column| {
+                        column.top_left.x <= center && center <= column.bottom_right.x
+                    });
+                };
+                let column_blocks = page.blocks.iter().filter(|block| in_columns(block));
+                let (left, right) = column_blocks.fold(
+                    (caption.x, caption.x + caption.width),
+                    |(left, right), block| (left.min(block.x), right.max(block.x + block.width)),
+                );
+                let overlaps = |block: &Block| block.x < right && left < block.x + block.width;
+                let top = page
+                    .blocks
+                    .iter()
+                    .filter(|block| {
+                        let body = block.attr == BlockAttr::Title
+                            || (block.attr == BlockAttr::Text && block.lines.len() >= 2);
+                        body && overlaps(block) && block.y + block.height <= caption.y
+                    })
+                    .map(|block| block.y + block.height)
+                    .fold(text_top.min(caption.y), f32::max);
+                let region = Coordinate::from_rect(left, top, right, caption.y);
+                if region.height() < caption.font_size() * FIGURE_MIN_HEIGHT_RATIO {
+                    continue;
+                }
+                let text_area = page
+                    .blocks
+                    .iter()
+                    .flat_map(|block| block.lines.iter())
+                    .flat_map(|line| line.words.iter())
+                    .map(|word| {
+                        let coord = Coordinate::from(word);
+                        coord.overlap_ratio(&region) * coord.get_area()
+                    })
+                    .sum::<f32>();
+                if text_area > region.get_area() * FIGURE_MAX_TEXT_RATIO {
+                    continue;
+                }
+                figures.push(Figure {
+                    page_number: page.page_number,
+                    region,
+                    caption: caption.clone(),
+                    image_path: None,
+                });
+            }
+        }
+        return figures;
+    }
+
+    /// Returns the areas covered by the blocks of the document with the given attribute.
+    ///
+    /// Consecutive blocks of a page sharing the attribute are merged into a single box; runs
+    /// never span a page break.
+    ///
+    /// # Arguments
+    ///
+    /// * `attr` - The kind of block to locate.
+    ///
+    /// # Returns
+    ///
+    /// A vector of the union `Coordinate` of each run of matching blocks, in page order.
+    pub fn attr_bbox(&self, attr: BlockAttr) -> Vec<Coordinate> {
+        let mut boxes: Vec<Coordinate> = Vec::new();
+        for page in self.pages.iter() {
+            let mut run: Option<Coordinate> = None;
+            for block in page.blocks.iter() {
+                if block.attr != attr {
+                    boxes.extend(run.take());
+                    continue;
+                }
+                run = Some(match run {
+                    Some(area) => area.union(&block.coordinate()),
+                    None => block.coordinate(),
+                });
+            }
+            boxes.extend(run);
+        }
+        return boxes;
+    }
+
+    /// Counts the lines of the document set in each font size.
+    ///
+    /// Lines are bucketed by their dominant font size, rounded to half a point, which reveals
+    /// the distinct sizes used for the body text, headings and captions.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(font size, number of lines)` pairs sorted by increasing font size.
+    pub fn font_size_histogram(&self) -> Vec<(f32, usize)> {
+        let mut counts: HashMap<OrderedFloat<f32>, usize> = HashMap::new();
+        for page in self.pages.iter() {
+            for block in page.blocks.iter() {
+                for line in block.lines.iter() {
+                    *counts.entry(OrderedFloat(line.dominant_font_size())).or_insert(0) += 1;
+                }
+            }
+        }
+        let mut histogram =
+            counts.into_iter().map(|(size, count)| (size.0, count)).collect::<Vec<(f32, usize)>>();
+        histogram.sort_by(|a, b| a.0.total_cmp(&b.0));
+        return histogram;
+    }
+
+    /// Returns every word of the document along with its position in the document tree.
+    ///
+    /// Words are yielded page by page, in the order of the blocks, lines and words.
+    ///
+    /// # Returns
+    ///
+    /// An iterator over `WordRef`s.
+    pub fn iter_words_indexed(&self) -> impl Iterator<Item = WordRef<'_>> {
+        return self.pages.iter().enumerate().flat_map(|(page, p)| {
+            p.blocks.iter().enumerate().flat_map(move |(block, b)| {
+                b.lines.iter().enumerate().flat_map(move |(line, l)| {
+                    l.words.iter().enumerate().map(move |(index, word)| WordRef {
+                        page,
+                        block,
+                        line,
+                        index,
+                        word,
+                    })
+                })
+            })
+        });
+    }
+
+    /// Returns the text of the whole document.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `FullTextOptions` controlling block and page separators.
+    ///
+    /// The blocks of each page follow their reading order. Without a page marker, a paragraph
+    /// continued on the next page, whose first block in reading order is marked `continued`, is
+    /// joined back like the lines of a block instead of being split at the page break.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the text of all pages, with blocks joined by the block separator,
+    /// pages joined by the page separator and, if set, the page marker inserted at each page break.
+    pub fn full_text(&self, options: &FullTextOptions) -> String {
+        let page_break = match &options.page_marker {
+            Some(marker) => format!("{}{}", options.page_separator, marker),
+            None => options.page_separator.clone(),
+        };
+        let mut text = String::new();
+        for (i, page) in self.pages.iter().enumerate() {
+            let blocks = page.blocks_in_reading_order();
+            let page_text = blocks
+                .iter()
+                .map(|block| block.get_text_with(&options.text_options))
+                .collect::<Vec<String>>()
+                .join(&options.block_separator);
+            if i > 0 {
+                let continued = options.page_marker.is_none()
+                    && blocks.first().is_some_and(|block| block.continued);
+                if !continued {
+                    text.push_str(&page_break);
+                } else if text.ends_with('-') {
+                    text.pop();
+                } else {
+                    text.push(' ');
+                }
+            }
+            text.push_str(&page_text);
+        }
+        return text;
+    }
+
+    /// Returns the first words of the document, for quick previews.
+    ///
+    /// Words are collected page by page, following the reading order of the blocks, and
+    /// collection stops as soon as the limit is reached.
+    ///
+    /// # Arguments
+    ///
+    /// * `word_limit` - The maximum number of words in the preview.
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing at most `word_limit` words separated by single spaces.
+    pub fn preview(&self, word_limit: usize) -> String {
+        if word_limit == 0 {
+            return String::new();
+        }
+        let mut words: Vec<&str> = Vec::with_capacity(word_limit);
+        for page in self.pages.iter() {
+            for block in page.blocks_in_reading_order() {
+                for line in block.lines.iter() {
+                    for word in line.words.iter() {
+                        words.push(word.text.as_str());
+                        if words.len() >= word_limit {
+                            return words.join(" ");
+                        }
+                    }
+                }
+            }
+        }
+        return words.join(" ");
+    }
+
+    /// Returns the index of the block of the first page where the abstract starts, if any.
+    fn abstract_index(&self) -> Option<usize> {
+        let page = self.pages.first()?;
+        return page.blocks.iter().position(|block| {
+            let text = block.get_text().to_lowercase();
+            text.strip_prefix("abstract")
+                .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric()))
+        });
+    }
+
+    /// Returns the blocks of the first page that precede the abstract (title, authors, ...).
+    ///
+    /// When no abstract is found, the blocks above the first block of at least three lines are
+    /// used instead.
+    pub(crate) fn header_blocks(&self) -> Vec<&Block> {
+        let page = match self.pages.first() {
+            Some(page) => page,
+            None => return Vec::new(),
+        };
+        let end = self.abstract_index().unwrap_or_else(|| {
+            page.blocks.iter().position(|block| block.lines.len() >= 3).unwrap_or(0)
+        });
+        return page.blocks[..end].iter().collect();
+    }
+
+    /// Returns the title of the document.
+    ///
+    /// The title is the header block of the first page set in the largest font.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<String>` containing the title, or `None` if no header block was found.
+    pub fn title(&self) -> Option<String> {
+        let mut title: Option<&Block> = None;
+        for block in self.header_blocks() {
+            if title.is_none_or(|title| block.font_size() > title.font_size()) {
+                title = Some(block);
+            }
+        }
+        return title.map(|block| block.get_text());
+    }
+
+    /// Returns the text of the lines of the header blocks following the title, see
+    /// `header_blocks`.
+    ///
+    /// A line is split into cells at gaps wider than `AUTHOR_COLUMN_GAP_RATIO` times its font
+    /// size, so that the columns of an author grid, as in IEEE templates, are read separately.
+    /// Superscript words are attached to the preceding word.
+    fn author_region_cells(&self) -> Vec<String> {
+        let title = self.title().unwrap_or_default();
+        let mut cells: Vec<String> = Vec::new();
+        for block in
+            self.header_blocks().iter().skip_while(|block| block.get_text() != title).skip(1)
+        {
+            for line in block.lines.iter() {
+                let column_gap = line.dominant_font_size() * AUTHOR_COLUMN_GAP_RATIO;
+                let mut text = String::new();
+                let mut right: Option<f32> = None;
+                for word in line.words.iter() {
+                    if right.is_some_and(|right| word.x - right > column_gap) {
+                        cells.push(std::mem::take(&mut text));
+                    }
+                    if !text.is_empty() && word.script != Script::Super {
+                        text.push(' ');
+                    }
+                    text.push_str(&word.text);
+                    right = Some(word.x + word.width);
+                }
+                cells.push(text);
+            }
+        }
+        cells.retain(|cell| !cell.trim().is_empty());
+        return cells;
+    }
+
+    /// Returns the e-mail addresses of the authors.
+    ///
+    /// Addresses are searched in the lines between the title and the abstract. Grouped
+    /// addresses such as "{vaswani, shazeer}@google.com" are expanded into one address per
+    /// user.
+    ///
+    /// # Returns
+    ///
+    /// A vector of the distinct e-mail addresses in reading order.
+    pub fn emails(&self) -> Vec<String> {
+        let mut emails: Vec<String> = Vec::new();
+        for cell in self.author_region_cells() {
+            let mut found: Vec<String> = Vec::new();
+            for caps in GROUPED_EMAIL_REGEX.captures_iter(&cell) {
+                for user in caps[1].split([',', ';', '|']) {
+                    found.push(format!("{}@{}", user.trim(), &caps[2]));
+                }
+            }
+            let rest = GROUPED_EMAIL_REGEX.replace_all(&cell, "");
+            found.extend(EMAIL_REGEX.find_iter(&rest).map(|m| m.as_str().to_string()));
+            for address in found {
+                let address = address.trim_end_matches('.').to_string();
+                if !emails.contains(&address) {
+                    emails.push(address);
+                }
+            }
+        }
+        return emails;
+    }
+
+    /// Returns the affiliations of the authors.
+    ///
+    /// Affiliations are the cells of the lines between the title and the abstract naming an
+    /// institution, such as a university, a laboratory or a company, with the markers linking
+    /// them to the authors and the e-mail addresses removed. An institution is recognised by a
+    /// generic word such as "University" or "Research", or by the domain of an e-mail address
+    /// of the authors, as "Google Brain" next to "avaswani@google.com". See
+    /// `author_region_cells` for the handling of multi-column author blocks.
+    ///
+    /// # Returns
+    ///
+    /// A vector of the distinct affiliations in reading order.
+    pub fn affiliations(&self) -> Vec<String> {
+        let organisations = email_organisations(&self.emails());
+        let mut affiliations: Vec<String> = Vec::new();
+        for cell in self.author_region_cells() {
+            let text = strip_emails(&cell);
+            let text = text
+                .trim_start_matches(|c: char| is_author_marker(c) || c.is_whitespace())
+                .trim_matches(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                .to_string();
+            if looks_like_affiliation(&text, &organisations) && !affiliations.contains(&text) {
+                affiliations.push(text);
+            }
+        }
+        return affiliations;
+    }
+
+    /// Returns the author names found between the title and the abstract.
+    ///
+    /// Lines of the header blocks following the title are split into cells at wide gaps, see
+    /// `author_region_cells`, and on commas, semicolons and "and". E-mail addresses are removed,
+    /// lines that look like affiliations are skipped and only candidates shaped like personal
+    /// names (two to five capitalized words) are kept.
+    /// Affiliation and footnote markers are removed from the names, see
+    /// `authors_with_markers`.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `String`s containing the author names in reading order.
+    pub fn authors(&self) -> Vec<String> {
+        return self.authors_with_markers().into_iter().map(|author| author.name).collect();
+    }
+
+    /// Returns the authors together with the markers linking them to affiliations.
+    ///
+    /// Symbols such as `*`, `∗`, `†`, `‡` and digits attached to a name (or set as a
+    /// superscript word right after it) are stripped from the name and recorded as its
+    /// markers. A marker run following a separator, as in "Vaswani1,2", belongs to the
+    /// preceding author.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `Author`s in reading order.
+    pub fn authors_with_markers(&self) -> Vec<Author> {
+        let organisations = email_organisations(&self.emails());
+        let mut authors: Vec<Author> = Vec::new();
+        for cell in self.author_region_cells() {
+            let text = strip_emails(&cell);
+            if text.contains('@') || looks_like_affiliation(&text, &organisations) {
+                continue;
+            }
+            for candidate in AUTHOR_SEPARATOR_REGEX.split(&text) {
+                let mut name: Vec<&str> = Vec::new();
+                for token in candidate.split_whitespace() {
+                    let core = token.trim_end_matches(is_author_marker);
+                    let markers = split_author_markers(&token[core.len()..]);
+                    if core.is_empty() {
+                        if name.is_empty() {
+                            if let Some(author) = authors.last_mut() {
+                                author.markers.extend(markers);
+                            }
+                            continue;
+                        }
+                    } else {
+                        name.push(core);
+                    }
+                    if !markers.is_empty() {
+                        push_author(&mut authors, &name.join(" "), markers);
+                        name.clear();
+                    }
+                }
+                push_author(&mut authors, &name.join(" "), Vec::new());
             }
         }
-        values.sort_by(|a, b| b.partial_cmp(a).unwrap());
-        return values.first().unwrap().clone();
+        return authors;
     }
 
-    /// Returns the x-coordinate of the leftmost line in the page.
+    /// Returns the text of the abstract.
+    ///
+    /// The abstract starts at the first block of the first page beginning with "Abstract" and
+    /// spans the following blocks that belong to the same section. It ends before the keywords
+    /// line recognised by `keyword_list`, whether on its own block or closing the abstract.
     ///
     /// # Returns
     ///
-    /// A `f32` representing the x-coordinate of the leftmost line.
-    pub fn left(&self) -> f32 {
-        let mut values: Vec<f32> = Vec::new();
-        for block in &self.blocks {
-            for line in &block.lines {
-                values.push(line.x);
+    /// An `Option<String>` containing the abstract, or `None` if no abstract was found.
+    pub fn abstract_text(&self) -> Option<String> {
+        let index = self.abstract_index()?;
+        let heading = &self.pages[0].blocks[index];
+        let mut contents: Vec<String> = Vec::new();
+        let blocks = self
+            .pages
+            .iter()
+            .flat_map(|page| page.blocks.iter())
+            .skip(index)
+            .take_while(|block| block.section == heading.section);
+        for (i, block) in blocks.enumerate() {
+            let end = block
+                .lines
+                .iter()
+                .position(|line| KEYWORDS_PREFIX_REGEX.is_match(&line.get_text()));
+            let text = match end {
+                Some(end) => join_lines(block.lines[..end].iter().map(|line| line.get_text())),
+                None => block.get_text(),
+            };
+            let text = match i {
+                0 => text["abstract".len()..]
+                    .trim_start_matches(|c: char| !c.is_alphanumeric())
+                    .to_string(),
+                _ => text,
+            };
+            if !text.is_empty() {
+                contents.push(text);
+            }
+            if end.is_some() {
+                break;
             }
         }
-        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        return values.first().unwrap().clone();
+        if contents.is_empty() {
+            return None;
+        }
+        return Some(contents.join("\n"));
     }
 
-    /// Returns the x-coordinate of the rightmost line in the page.
+    /// Returns the keywords declared by the authors on the first page.
+    ///
+    /// The keywords follow a line starting with "Keywords", "Key words" or "Index Terms", on its
+    /// own block or at the end of the abstract, and run to the end of the block. Unlike
+    /// `keywords`, which extracts key phrases from the text, only the authors' own terms are
+    /// returned.
     ///
     /// # Returns
     ///
-    /// A `f32` representing the x-coordinate of the rightmost line.
-    pub fn right(&self) -> f32 {
-        let mut values: Vec<f32> = Vec::new();
-        for block in &self.blocks {
-            for line in &block.lines {
-                values.push(line.x + line.width);
+    /// A vector of the declared keywords split at commas and semicolons, in order, or an empty
+    /// vector when the first page has no keywords line.
+    pub fn keyword_list(&self) -> Vec<String> {
+        let page = match self.pages.first() {
+            Some(page) => page,
+            None => return Vec::new(),
+        };
+        for block in page.blocks.iter() {
+            let start = block
+                .lines
+                .iter()
+                .position(|line| KEYWORDS_PREFIX_REGEX.is_match(&line.get_text()));
+            if let Some(start) = start {
+                let text = join_lines(block.lines[start..].iter().map(|line| line.get_text()));
+                let m = KEYWORDS_PREFIX_REGEX.find(&text).unwrap();
+                return text[m.end()..]
+                    .split([',', ';'])
+                    .map(|keyword| keyword.trim().trim_end_matches('.').trim().to_string())
+                    .filter(|keyword| !keyword.is_empty())
+                    .collect();
             }
         }
-        values.sort_by(|a, b| b.partial_cmp(a).unwrap());
-        return values.first().unwrap().clone();
+        return Vec::new();
+    }
+
+    /// Extracts the most relevant key phrases of the document.
+    ///
+    /// The phrases are scored with a frequency-weighted RAKE: the text is split into candidate
+    /// phrases at stopwords and punctuation, every word is scored by its degree over its
+    /// frequency, and a phrase scores the sum of its word scores times its number of occurrences.
+    /// Blocks of the "References" section are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `top_n` - The maximum number of phrases to return.
+    ///
+    /// # Returns
+    ///
+    /// A vector of lowercase phrases, the most relevant first.
+    pub fn keywords(&self, top_n: usize) -> Vec<String> {
+        let mut phrases: Vec<Vec<String>> = Vec::new();
+        for page in self.pages.iter() {
+            for block in page.blocks.iter() {
+                let section = block.section.to_lowercase();
+                if section == "references" || section == "bibliography" {
+                    continue;
+                }
+                phrases.extend(candidate_phrases(&block.get_text()));
+            }
+        }
+
+        let mut frequencies: HashMap<&str, f32> = HashMap::new();
+        let mut degrees: HashMap<&str, f32> = HashMap::new();
+        let mut counts: HashMap<String, f32> = HashMap::new();
+        for phrase in phrases.iter() {
+            for word in phrase.iter() {
+                *frequencies.entry(word).or_insert(0.0) += 1.0;
+                *degrees.entry(word).or_insert(0.0) += phrase.len() as f32;
+            }
+            *counts.entry(phrase.join(" ")).or_insert(0.0) += 1.0;
+        }
+
+        let mut scores = counts
+            .iter()
+            .map(|(phrase, count)| {
+                let score =
+                    phrase.split(' ').map(|word| degrees[word] / frequencies[word]).sum::<f32>();
+                (phrase.clone(), score * count)
+            })
+            .collect::<Vec<(String, f32)>>();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        return scores.into_iter().take(top_n).map(|(phrase, _)| phrase).collect();
+    }
+
+    /// Returns the header metadata of the document in one bundle.
+    ///
+    /// # Returns
+    ///
+    /// A `HeaderMetadata` combining the title, authors, abstract and declared keywords, each
+    /// field being `None` when it could not be found.
+    pub fn header_metadata(&self) -> HeaderMetadata {
+        let authors = self.authors();
+        let keywords = self.keyword_list();
+        return HeaderMetadata {
+            title: self.title(),
+            authors: if authors.is_empty() {
+                None
+            } else {
+                Some(authors)
+            },
+            abstract_text: self.abstract_text(),
+            keywords: if keywords.is_empty() {
+                None
+            } else {
+                Some(keywords)
+            },
+        };
+    }
+
+    /// Returns the texts of the first page searched for publication details: the margin text
+    /// first, then the lines of the blocks.
+    fn first_page_lines(&self) -> Vec<String> {
+        let page = match self.pages.first() {
+            Some(page) => page,
+            None => return Vec::new(),
+        };
+        let mut lines = page.margin_text.clone();
+        for block in page.blocks.iter() {
+            lines.extend(block.lines.iter().map(|line| line.get_text()));
+        }
+        return lines;
+    }
+
+    /// Returns the arXiv identifier stamped on the first page.
+    ///
+    /// Both the current form ("arXiv:1706.03762v7") and the old form ("arXiv:hep-th/9901001")
+    /// are recognized, in the margin text or in the blocks of the first page.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<String>` containing the identifier with its version but without the "arXiv:"
+    /// prefix, or `None` if no identifier was found.
+    pub fn arxiv_id(&self) -> Option<String> {
+        for line in self.first_page_lines() {
+            if let Some(caps) = ARXIV_ID_REGEX.captures(&line) {
+                return Some(caps[1].to_string());
+            }
+        }
+        return None;
+    }
+
+    /// Returns the publication venue stated on the first page.
+    ///
+    /// A line is a venue line when it names a known venue next to a year ("NeurIPS 2017"), or
+    /// when it contains a year and words such as "Published in", "Proceedings" or
+    /// "Conference". The arXiv stamp is used when no other venue line is found.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<Venue>` containing the line and its date, or `None` if no venue was found.
+    pub fn venue(&self) -> Option<Venue> {
+        let lines = self.first_page_lines();
+        let line = lines
+            .iter()
+            .find(|line| {
+                KNOWN_VENUE_REGEX.is_match(line)
+                    || (VENUE_PHRASE_REGEX.is_match(line) && YEAR_REGEX.is_match(line))
+            })
+            .or_else(|| lines.iter().find(|line| line.to_lowercase().contains("arxiv:")))?;
+        return Some(Venue {
+            text: line.trim().to_string(),
+            date: find_date(line),
+        });
+    }
+
+    /// Groups the blocks of the document into sections with their heading level and body text.
+    ///
+    /// A section starts wherever the `section` of the blocks changes, so sections continue
+    /// across page boundaries. The level of a section is the depth of the numbering of its
+    /// heading ("2.1 Background" is level 2), unnumbered headings being level 1. The body of a
+    /// section gathers its own blocks and those of its subsections, up to the next heading of
+    /// the same or a higher level. Blocks without a section are skipped.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `DocumentSection`s in reading order.
+    pub fn sections(&self) -> Vec<DocumentSection> {
+        let mut headings: Vec<(String, u8, Vec<String>)> = Vec::new();
+        for page in self.pages.iter() {
+            for block in &page.blocks {
+                if block.section.is_empty() {
+                    continue;
+                }
+                let mut lines =
+                    block.lines.iter().map(|line| line.get_text()).collect::<Vec<String>>();
+                let current = headings.last().map(|(heading, _, _)| heading.as_str());
+                if current != Some(block.section.as_str()) {
+                    let mut level = 1;
+                    if let Some(first) = lines.first() {
+                        let title = HEADING_NUMBER_REGEX.replace(first.trim(), "").to_lowercase();
+                        if title == block.section.to_lowercase()
+                            || first.trim().to_lowercase() == block.section.to_lowercase()
+                        {
+                            if let Some(caps) = HEADING_NUMBER_REGEX.captures(first.trim()) {
+                                level = caps[1].split('.').count() as u8;
+                            }
+                            lines.remove(0);
+                        }
+                    }
+                    headings.push((block.section.clone(), level, Vec::new()));
+                }
+                if !lines.is_empty() {
+                    headings.last_mut().unwrap().2.push(lines.join("\n"));
+                }
+            }
+        }
+
+        let mut sections = Vec::new();
+        for (i, (heading, level, paragraphs)) in headings.iter().enumerate() {
+            let mut body = paragraphs.clone();
+            for (sub_heading, sub_level, sub_paragraphs) in &headings[i + 1..] {
+                if sub_level <= level {
+                    break;
+                }
+                body.push(sub_heading.clone());
+                body.extend(sub_paragraphs.iter().cloned());
+            }
+            sections.push(DocumentSection {
+                heading: heading.clone(),
+                level: *level,
+                body: body.join("\n"),
+            });
+        }
+        return sections;
+    }
+}
+
+/// The `DocumentSection` struct represents a section of a document with its body text.
+///
+/// # Fields
+///
+/// * `heading` - The heading of the section, without its numbering.
+/// * `level` - The depth of the section, `1` for top-level sections.
+/// * `body` - The text of the section, including its subsections.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentSection {
+    pub heading: String,
+    pub level: u8,
+    pub body: String,
+}
+
+/// The `WordRef` struct refers to a word of a document along with its indices.
+///
+/// # Fields
+///
+/// * `page` - The index of the page in `Document::pages`.
+/// * `block` - The index of the block in its page.
+/// * `line` - The index of the line in its block.
+/// * `index` - The index of the word in its line.
+/// * `word` - The word, whose coordinates are absolute on its page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WordRef<'a> {
+    pub page: usize,
+    pub block: usize,
+    pub line: usize,
+    pub index: usize,
+    pub word: &'a Word,
+}
+
+impl WordRef<'_> {
+    /// Returns the bounding box of the word on its page.
+    ///
+    /// # Returns
+    ///
+    /// A `Coordinate` representing the word.
+    pub fn coordinate(&self) -> Coordinate {
+        return Coordinate::from(self.word);
+    }
+}
+
+const STOPWORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "also", "am", "an", "and", "any",
+    "are", "as", "at", "be", "because", "been", "before", "being", "below", "between", "both",
+    "but", "by", "can", "could", "did", "do", "does", "doing", "down", "during", "each", "et",
+    "few", "for", "from", "further", "had", "has", "have", "having", "he", "her", "here", "hers",
+    "him", "his", "how", "however", "i", "if", "in", "into", "is", "it", "its", "itself", "may",
+    "more", "most", "much", "must", "my", "no", "nor", "not", "of", "off", "on", "once", "one",
+    "only", "or", "other", "our", "ours", "out", "over", "own", "same", "she", "should", "so",
+    "some", "such", "than", "that", "the", "their", "them", "then", "there", "these", "they",
+    "this", "those", "through", "thus", "to", "under", "until", "up", "use", "used", "using",
+    "very", "via", "was", "we", "were", "what", "when", "where", "which", "while", "who", "with",
+];
+
+fn candidate_phrases(text: &str) -> Vec<Vec<String>> {
+    let mut phrases: Vec<Vec<String>> = Vec::new();
+    for fragment in text.split(|c: char| ".,;:!?()[]{}\"".contains(c)) {
+        let mut phrase: Vec<String> = Vec::new();
+        for token in fragment.split_whitespace() {
+            let word = token.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+            let is_candidate = word.chars().count() > 2
+                && word.chars().all(|c| c.is_alphabetic() || c == '-')
+                && !STOPWORDS.contains(&word.as_str());
+            if is_candidate {
+                phrase.push(word);
+            } else if !phrase.is_empty() {
+                phrases.push(std::mem::take(&mut phrase));
+            }
+        }
+        if !phrase.is_empty() {
+            phrases.push(phrase);
+        }
+    }
+    phrases.retain(|phrase| phrase.len() <= 3);
+    return phrases;
+}
+
+/// The smallest gap between two words of a header line, relative to its font size, separating
+/// two columns of an author grid.
+const AUTHOR_COLUMN_GAP_RATIO: f32 = 2.0;
+
+/// An e-mail address or a group of them, as removed by `strip_emails`.
+static EMAIL_OR_GROUP_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:\{[^{}@]+\}|[\w.+-]+)\s*@\s*[\w-]+(?:\.[\w-]+)+").unwrap());
+
+/// Removes the e-mail addresses from the text, including grouped ones such as
+/// "{vaswani, shazeer}@google.com".
+fn strip_emails(text: &str) -> String {
+    return EMAIL_OR_GROUP_REGEX.replace_all(text, "").trim().to_string();
+}
+
+/// The words naming a kind of organisation, which make a header cell an affiliation.
+const ORGANISATION_KEYWORDS: [&str; 22] = [
+    "university",
+    "institute",
+    "department",
+    "dept.",
+    "school",
+    "college",
+    "academy",
+    "laboratory",
+    "laboratories",
+    "lab",
+    "labs",
+    "research",
+    "center",
+    "centre",
+    "foundation",
+    "company",
+    "corporation",
+    "corp.",
+    "inc.",
+    "ltd.",
+    "llc",
+    "gmbh",
+];
+
+/// Returns the names an e-mail domain gives to an organisation, such as "google" for
+/// "google.com" or "stanford" for "cs.stanford.edu".
+///
+/// The top-level domain and labels shorter than three letters, such as "cs" or "ac", are left
+/// out.
+fn email_organisations(emails: &[String]) -> Vec<String> {
+    let mut organisations: Vec<String> = Vec::new();
+    for email in emails {
+        let Some((_, domain)) = email.rsplit_once('@') else {
+            continue;
+        };
+        let labels = domain.split('.').collect::<Vec<&str>>();
+        for label in labels[..labels.len() - 1].iter() {
+            let label = label.to_lowercase();
+            if label.chars().count() >= 3 && !organisations.contains(&label) {
+                organisations.push(label);
+            }
+        }
+    }
+    return organisations;
+}
+
+/// Determines whether a header cell names an institution.
+///
+/// A cell is taken for an affiliation when one of its words is one of
+/// `ORGANISATION_KEYWORDS`, or one of the `organisations` named by the e-mail domains of the
+/// authors, as "Google" in "Google Brain" next to "avaswani@google.com".
+fn looks_like_affiliation(text: &str, organisations: &[String]) -> bool {
+    let text = text.to_lowercase();
+    return text.split(|c: char| !c.is_alphanumeric() && c != '.').any(|word| {
+        ORGANISATION_KEYWORDS.contains(&word)
+            || organisations.iter().any(|organisation| organisation == word)
+    });
+}
+
+fn is_author_marker(c: char) -> bool {
+    return c.is_ascii_digit() || "*∗†‡§¶‖#¹²³⁴⁵⁶⁷⁸⁹⁰".contains(c);
+}
+
+fn split_author_markers(text: &str) -> Vec<String> {
+    let mut markers: Vec<String> = Vec::new();
+    let mut digits = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        if !digits.is_empty() {
+            markers.push(std::mem::take(&mut digits));
+        }
+        if is_author_marker(c) {
+            markers.push(c.to_string());
+        }
+    }
+    if !digits.is_empty() {
+        markers.push(digits);
+    }
+    return markers;
+}
+
+fn push_author(authors: &mut Vec<Author>, name: &str, markers: Vec<String>) {
+    if looks_like_name(name) {
+        authors.push(Author {
+            name: name.to_string(),
+            markers,
+        });
+    }
+}
+
+fn looks_like_name(text: &str) -> bool {
+    let tokens = text.split_whitespace().collect::<Vec<&str>>();
+    return (2..=5).contains(&tokens.len())
+        && tokens.iter().all(|token| token.starts_with(|c: char| c.is_uppercase()));
+}
+
+/// A date with a month name, such as "2 Aug 2023", "August 2023" or "Aug. 2, 2023".
+static DATE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(?:\d{1,2}\s+)?(?:jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.?\s+(?:\d{1,2},\s*)?(?:19|20)\d{2}\b",
+    )
+    .unwrap()
+});
+
+/// Returns the date written in a line, such as "2 Aug 2023", "August 2023" or a bare year.
+fn find_date(text: &str) -> Option<String> {
+    if let Some(m) = DATE_REGEX.find(text) {
+        return Some(m.as_str().to_string());
     }
+    return YEAR_REGEX.find(text).map(|m| m.as_str().to_string());
+}
+
+/// The `Venue` struct represents the publication details stated on the first page.
+///
+/// # Fields
+///
+/// * `text` - The line naming the venue, such as "Published in NeurIPS 2017".
+/// * `date` - The date found in the line, such as "2 Aug 2023" or "2017", if any.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Venue {
+    pub text: String,
+    pub date: Option<String>,
+}
+
+/// The `Author` struct represents an author listed in the header of the paper.
+///
+/// # Fields
+///
+/// * `name` - The name of the author without affiliation or footnote markers.
+/// * `markers` - The markers attached to the name (e.g. "1", "∗", "†"), in order of appearance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Author {
+    pub name: String,
+    pub markers: Vec<String>,
+}
+
+/// The `HeaderMetadata` struct bundles the metadata found at the top of the first page.
+///
+/// # Fields
+///
+/// * `title` - The title of the paper.
+/// * `authors` - The names of the authors.
+/// * `abstract_text` - The text of the abstract.
+/// * `keywords` - The keywords declared by the authors.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeaderMetadata {
+    pub title: Option<String>,
+    pub authors: Option<Vec<String>>,
+    pub abstract_text: Option<String>,
+    pub keywords: Option<Vec<String>>,
 }
 
 /// The `Point` struct represents a point in 2D space.
@@ -439,6 +3128,68 @@ impl Coordinate {
         return self.bottom_left.y - self.top_left.y;
     }
 
+    /// Returns the center of the rectangle represented by the `Coordinate`.
+    ///
+    /// # Returns
+    ///
+    /// A `Point` halfway between the corners of the rectangle.
+    pub fn center(&self) -> Point {
+        return Point::new(
+            (self.top_left.x + self.bottom_right.x) / 2.0,
+            (self.top_left.y + self.bottom_right.y) / 2.0,
+        );
+    }
+
+    /// Returns the Euclidean distance between the centers of two rectangles.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other rectangle.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` representing the distance between the two centers.
+    pub fn center_distance(&self, other: &Coordinate) -> f32 {
+        let (a, b) = (self.center(), other.center());
+        return (a.x - b.x).hypot(a.y - b.y);
+    }
+
+    /// Returns the horizontal space between two rectangles.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other rectangle, on either side of this one.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` representing the distance between the facing vertical edges, or `0.0` when the
+    /// horizontal extents of the rectangles overlap.
+    pub fn horizontal_gap(&self, other: &Coordinate) -> f32 {
+        let gap = f32::max(
+            other.top_left.x - self.top_right.x,
+            self.top_left.x - other.top_right.x,
+        );
+        return gap.max(0.0);
+    }
+
+    /// Returns the vertical space between two rectangles.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other rectangle, above or below this one.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` representing the distance between the facing horizontal edges, or `0.0` when
+    /// the vertical extents of the rectangles overlap.
+    pub fn vertical_gap(&self, other: &Coordinate) -> f32 {
+        let gap = f32::max(
+            other.top_left.y - self.bottom_left.y,
+            self.top_left.y - other.bottom_left.y,
+        );
+        return gap.max(0.0);
+    }
+
     /// Determines if the rectangle represented by this `Coordinate` intersects with another `Coordinate`.
     ///
     /// # Arguments
@@ -484,6 +3235,139 @@ impl Coordinate {
         return Coordinate::from_rect(x1, y1, x2, y2);
     }
 
+    /// Returns a new `Coordinate` with every corner scaled by the given factors.
+    ///
+    /// # Arguments
+    ///
+    /// * `sx` - The scale factor applied to the x-coordinates.
+    /// * `sy` - The scale factor applied to the y-coordinates.
+    ///
+    /// # Returns
+    ///
+    /// A `Coordinate` representing the scaled rectangle.
+    pub fn scale(&self, sx: f32, sy: f32) -> Coordinate {
+        return Coordinate::from_rect(
+            self.top_left.x * sx,
+            self.top_left.y * sy,
+            self.bottom_right.x * sx,
+            self.bottom_right.y * sy,
+        );
+    }
+
+    /// Returns a new `Coordinate` mirrored vertically within a page of the given height.
+    ///
+    /// The rectangle keeps `top_left` as its corner of smallest coordinates, so that once
+    /// converted to the bottom-left origin, `as_rect` returns its bottom edge before its top edge.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - The height of the page.
+    ///
+    /// # Returns
+    ///
+    /// A `Coordinate` representing the mirrored rectangle.
+    pub fn flip_y(&self, height: f32) -> Coordinate {
+        return Coordinate::from_rect(
+            self.top_left.x,
+            height - self.bottom_right.y,
+            self.bottom_right.x,
+            height - self.top_left.y,
+        );
+    }
+
+    /// Returns the `Coordinate` measured from the given origin of a page of the given height.
+    ///
+    /// Coordinates are stored measured from the top-left corner, as in the pdftotext HTML, so
+    /// only `CoordinateOrigin::BottomLeft` flips the rectangle with `flip_y`.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The corner of the page to measure from.
+    /// * `height` - The height of the page.
+    ///
+    /// # Returns
+    ///
+    /// A `Coordinate` representing the rectangle seen from the origin.
+    pub fn to_origin(&self, origin: CoordinateOrigin, height: f32) -> Coordinate {
+        return match origin {
+            CoordinateOrigin::TopLeft => self.clone(),
+            CoordinateOrigin::BottomLeft => self.flip_y(height),
+        };
+    }
+
+    /// Returns a new `Coordinate` with every corner shifted by the given offsets.
+    ///
+    /// # Arguments
+    ///
+    /// * `dx` - The offset added to the x-coordinates.
+    /// * `dy` - The offset added to the y-coordinates.
+    ///
+    /// # Returns
+    ///
+    /// A `Coordinate` representing the translated rectangle.
+    pub fn translate(&self, dx: f32, dy: f32) -> Coordinate {
+        return Coordinate::from_rect(
+            self.top_left.x + dx,
+            self.top_left.y + dy,
+            self.bottom_right.x + dx,
+            self.bottom_right.y + dy,
+        );
+    }
+
+    /// Returns a new `Coordinate` grown by the given margin on every side.
+    ///
+    /// A negative margin shrinks the rectangle, see `inset`.
+    ///
+    /// # Arguments
+    ///
+    /// * `m` - The margin added to each side.
+    ///
+    /// # Returns
+    ///
+    /// A `Coordinate` representing the expanded rectangle.
+    pub fn expand(&self, m: f32) -> Coordinate {
+        let m = m.max(-self.width() / 2.0).max(-self.height() / 2.0);
+        return Coordinate::from_rect(
+            self.top_left.x - m,
+            self.top_left.y - m,
+            self.bottom_right.x + m,
+            self.bottom_right.y + m,
+        );
+    }
+
+    /// Returns a new `Coordinate` shrunk by the given margin on every side.
+    ///
+    /// The margin is capped so that the rectangle collapses to a line or a point at its center
+    /// rather than being inverted.
+    ///
+    /// # Arguments
+    ///
+    /// * `m` - The margin removed from each side.
+    ///
+    /// # Returns
+    ///
+    /// A `Coordinate` representing the inset rectangle.
+    pub fn inset(&self, m: f32) -> Coordinate {
+        return self.expand(-m);
+    }
+
+    /// Returns the smallest rectangle containing both this `Coordinate` and another `Coordinate`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Another `Coordinate` to combine with.
+    ///
+    /// # Returns
+    ///
+    /// A `Coordinate` representing the bounding box of both rectangles.
+    pub fn union(&self, other: &Coordinate) -> Coordinate {
+        let x1 = f32::min(self.top_left.x, other.top_left.x);
+        let y1 = f32::min(self.top_left.y, other.top_left.y);
+        let x2 = f32::max(self.bottom_right.x, other.bottom_right.x);
+        let y2 = f32::max(self.bottom_right.y, other.bottom_right.y);
+        return Coordinate::from_rect(x1, y1, x2, y2);
+    }
+
     ///
     /// Computes the Intersection over Union (IoU) of the rectangle represented by this `Coordinate` with another `Coordinate`.
     ///
@@ -525,12 +3409,71 @@ impl Coordinate {
         let self_area = self.get_area();
         return iou > 0.0 && intersection / self_area > 0.3;
     }
+
+    /// Returns the fraction of the area of this `Coordinate` lying inside another `Coordinate`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The enclosing area, such as the text area of a page.
+    ///
+    /// # Returns
+    ///
+    /// A `f32` between `0.0` (no overlap) and `1.0` (fully inside).
+    pub fn overlap_ratio(&self, other: &Coordinate) -> f32 {
+        let area = self.get_area();
+        if area <= 0.0 || !self.is_intercept(other) {
+            return 0.0;
+        }
+        return self.intersection(other).get_area() / area;
+    }
+
+    /// Returns the rectangle as a `(x1, y1, x2, y2)` tuple.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the top-left and bottom-right corners, the inverse of `Coordinate::from_rect`.
+    pub fn as_rect(&self) -> (f32, f32, f32, f32) {
+        return (
+            self.top_left.x,
+            self.top_left.y,
+            self.bottom_right.x,
+            self.bottom_right.y,
+        );
+    }
+}
+
+impl From<(f32, f32, f32, f32)> for Coordinate {
+    /// Creates a `Coordinate` from a `(x1, y1, x2, y2)` rectangle tuple.
+    fn from(rect: (f32, f32, f32, f32)) -> Self {
+        let (x1, y1, x2, y2) = rect;
+        return Coordinate::from_rect(x1, y1, x2, y2);
+    }
+}
+
+impl From<&Block> for Coordinate {
+    fn from(block: &Block) -> Self {
+        return block.coordinate();
+    }
+}
+
+impl From<&Line> for Coordinate {
+    fn from(line: &Line) -> Self {
+        return Coordinate::from_object(line.x, line.y, line.width, line.height);
+    }
+}
+
+impl From<&Word> for Coordinate {
+    fn from(word: &Word) -> Self {
+        return Coordinate::from_object(word.x, word.y, word.width, word.height);
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TextBlock {
     pub text: String,
     pub coordinates: Coordinate,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 pub struct Reference {
@@ -542,21 +3485,51 @@ impl TextBlock {
     pub fn from_block(block: &Block) -> TextBlock {
         TextBlock {
             text: block.get_text(),
-            coordinates: Coordinate::from_object(block.x, block.y, block.width, block.height),
+            coordinates: block.coordinate(),
+            tags: block.tags.clone(),
         }
     }
 }
+
+/// An appendix heading: an appendix keyword, possibly after an appendix letter, or appendix
+/// numbering such as "A.1". Only the keywords ignore case, so that "e.2" is not numbering.
+static APPENDIX_HEADING_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:[A-Z](?:\.\d+)*\.?\s+)?(?i:appendix|appendices|supplementary|supplemental)\b|^[A-Z]\.\d+\.?\s+\S",
+    )
+    .unwrap()
+});
+
+/// Determines whether a section heading starts the appendix.
+///
+/// Headings such as "Appendix", "Appendix A: Proofs", "Appendices", "Supplementary Material"
+/// and appendix numbering such as "A.1 Proofs" are recognized.
+///
+/// # Arguments
+///
+/// * `title` - The section heading.
+///
+/// # Returns
+///
+/// A `bool` indicating whether the heading starts the appendix.
+pub fn is_appendix_heading(title: &str) -> bool {
+    return APPENDIX_HEADING_REGEX.is_match(title.trim());
+}
+
 /// The `Section` struct represents a section in a PDF document.
 ///
 /// # Fields
 ///
 /// * `title` - The title of the section.
 /// * `content` - The content of the section.
+/// * `appendix` - Whether the section belongs to the appendix or the supplementary material.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Section {
     pub index: i8,
     pub title: String,
     pub contents: Vec<String>,
+    #[serde(default)]
+    pub appendix: bool,
 }
 
 impl Section {
@@ -568,15 +3541,16 @@ impl Section {
     ///
     /// # Returns
     ///
-    /// A vector of `Section` instances, each representing a section in the PDF document.
-    pub fn from_pages(pages: &Vec<Page>) -> Vec<Section> {
+    /// A vector of `Section` instances, each representing a section in the PDF document. The
+    /// sections from the first appendix heading on (see `is_appendix_heading`) are flagged as
+    /// `appendix`.
+    pub fn from_pages(pages: &[Page]) -> Vec<Section> {
         let mut section_indices: HashMap<String, i8> = HashMap::new();
         let mut section_map: HashMap<String, Vec<String>> = HashMap::new();
         let mut last_text = String::new();
         let eos_ptn = regex::Regex::new(r"(\.)(\W)").unwrap();
         let ex_ws_ptn = regex::Regex::new(r"\s+").unwrap();
-    
-        
+
         for page in pages {
             for block in &page.blocks {
                 let keys = section_map.keys().cloned().collect::<Vec<String>>();
@@ -611,9 +3585,16 @@ impl Section {
                 index: section_indices.get(&title).unwrap().clone(),
                 title: title,
                 contents: contents,
+                appendix: false,
             });
         }
         sections.sort_by(|a, b| a.index.cmp(&b.index));
+        if let Some(start) = sections.iter().position(|section| is_appendix_heading(&section.title))
+        {
+            for section in sections[start..].iter_mut() {
+                section.appendix = true;
+            }
+        }
         return sections;
     }
 
@@ -629,4 +3610,1499 @@ impl Section {
             return self.contents.join("\n");
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::block_with_lines;
+
+    #[test]
+    fn test_line_apply_emphasis() {
+        let mut line = Line::new(10.0, 100.0, 120.0, 10.0);
+        line.add_word("We".to_string(), 10.0, 100.0, 12.0, 10.0);
+        line.add_word("strongly".to_string(), 24.0, 100.0, 36.0, 10.0);
+        line.add_word("disagree".to_string(), 62.0, 100.0, 38.0, 10.0);
+        let spans = vec![EmphasisSpan {
+            coordinates: Coordinate::from_object(23.0, 99.0, 78.0, 12.0),
+            bold: true,
+            italic: false,
+        }];
+        line.apply_emphasis(&spans);
+
+        assert!(!line.words[0].bold);
+        assert!(line.words[1].bold);
+        assert!(line.words[2].bold);
+        assert!(!line.words[2].italic);
+        assert_eq!(line.get_markdown_text(), "We **strongly disagree**");
+    }
+
+    #[test]
+    fn test_get_text_with_collapse_whitespace() {
+        let mut block = Block::new(10.0, 100.0, 200.0, 22.0);
+        block.add_line(10.0, 100.0, 200.0, 10.0);
+        block.lines[0].add_word("multi   space".to_string(), 10.0, 100.0, 60.0, 10.0);
+        block.lines[0].add_word("tab\tseparated".to_string(), 75.0, 100.0, 60.0, 10.0);
+        block.add_line(10.0, 112.0, 200.0, 10.0);
+        block.lines[1].add_word("new\nline".to_string(), 10.0, 112.0, 60.0, 10.0);
+
+        assert_eq!(block.get_text(), "multi   space tab\tseparated new\nline");
+
+        let options = TextOptions {
+            collapse_whitespace: true,
+            trim_lines: true,
+            ..TextOptions::default()
+        };
+        assert_eq!(
+            block.lines[0].get_text_with(&options),
+            "multi space tab separated"
+        );
+        assert_eq!(
+            block.get_text_with(&options),
+            "multi space tab separated new line"
+        );
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        let line_with = |texts: &[&str]| {
+            let mut line = Line::new(10.0, 100.0, 300.0, 10.0);
+            for (i, text) in texts.iter().enumerate() {
+                line.add_word(text.to_string(), 10.0 + 40.0 * i as f32, 100.0, 35.0, 10.0);
+            }
+            return line;
+        };
+        for text in ["42", "-0.5", "+3", "−1.25", "1,234.5", ".75", "12."] {
+            assert!(line_with(&[text]).words[0].is_numeric(), "{}", text);
+        }
+        for text in [
+            "", "-", ".", "1,5", "12,34", "1.2.3", "v2", "1e5", "(3)", "n/a",
+        ] {
+            assert!(!line_with(&[text]).words[0].is_numeric(), "{}", text);
+        }
+
+        let row = line_with(&["28.4", "41.0", "−2.5", "1,024"]);
+        assert!(row.is_numeric());
+        assert_eq!(row.numbers(), vec![28.4, 41.0, -2.5, 1024.0]);
+
+        let page_number = line_with(&["12"]);
+        assert!(page_number.is_numeric());
+        assert_eq!(page_number.numbers(), vec![12.0]);
+
+        let mixed = line_with(&["BLEU", "of", "28.4", "(+2.0%),", "vs.", "26.4."]);
+        assert!(!mixed.is_numeric());
+        assert_eq!(mixed.numbers(), vec![28.4, 2.0, 26.4]);
+        assert!(!line_with(&[]).is_numeric());
+        assert_eq!(line_with(&["Table", "3"]).numbers(), vec![3.0]);
+    }
+
+    #[test]
+    fn test_get_text_with_math_placeholder() {
+        let mut block = Block::new(10.0, 100.0, 300.0, 22.0);
+        block.add_line(10.0, 100.0, 300.0, 10.0);
+        let words = [
+            "We", "minimize", "L", "=", "∑", "x", "i", "over", "all", "i.",
+        ];
+        for (i, text) in words.iter().enumerate() {
+            block.lines[0].add_word(text.to_string(), 10.0 + 30.0 * i as f32, 100.0, 25.0, 10.0);
+        }
+        block.lines[0].words[6].script = Script::Sub;
+        block.add_line(10.0, 112.0, 300.0, 10.0);
+        block.lines[1].add_word("Vaswani".to_string(), 10.0, 112.0, 50.0, 10.0);
+        block.lines[1].add_word("1".to_string(), 62.0, 110.0, 5.0, 6.0);
+        block.lines[1].words[1].script = Script::Super;
+        block.lines[1].add_word("proposed".to_string(), 70.0, 112.0, 50.0, 10.0);
+
+        let options = TextOptions {
+            math_placeholder: Some("[MATH]".to_string()),
+            ..TextOptions::default()
+        };
+        assert_eq!(
+            block.lines[0].get_text_with(&options),
+            "We minimize [MATH] over all i."
+        );
+        assert_eq!(block.lines[1].get_text_with(&options), "Vaswani 1 proposed");
+        let line_with = |texts: &[&str]| {
+            let mut line = Line::new(10.0, 100.0, 300.0, 10.0);
+            for (i, text) in texts.iter().enumerate() {
+                line.add_word(text.to_string(), 10.0 + 40.0 * i as f32, 100.0, 35.0, 10.0);
+            }
+            return line;
+        };
+        for text in [
+            "Ashish Vaswani¹ and C++ snake_case Noam",
+            "Η μέθοδος α και β είναι <Transformers>",
+            "BLEU > 28 on WMT",
+        ] {
+            let line = line_with(&text.split(' ').collect::<Vec<&str>>());
+            assert_eq!(line.get_text_with(&options), text);
+        }
+        let line = line_with(&["where", "x²", "and", "y=2x", "or", "α", "≤", "0.5", "hold"]);
+        assert_eq!(
+            line.get_text_with(&options),
+            "where [MATH] and [MATH] or [MATH] hold"
+        );
+        assert_eq!(
+            block.lines[0].get_text_with(&TextOptions::default()),
+            "We minimize L = ∑ x i over all i."
+        );
+
+        let math = block.lines[0].inline_math();
+        assert_eq!(math.len(), 1);
+        assert_eq!(math[0].0, "L = ∑ x i");
+        assert_eq!(math[0].1, Coordinate::from_rect(70.0, 100.0, 215.0, 110.0));
+        assert!(block.lines[1].inline_math().is_empty());
+    }
+
+    #[test]
+    fn test_block_coordinate() {
+        let mut block = Block::new(72.0, 100.0, 200.0, 24.0);
+        assert_eq!(
+            block.coordinate(),
+            Coordinate::from_object(block.x, block.y, block.width, block.height)
+        );
+        block.merge(Block::new(72.0, 130.0, 240.0, 12.0));
+        assert_eq!(
+            block.coordinate(),
+            Coordinate::from_object(block.x, block.y, block.width, block.height)
+        );
+        assert_eq!(
+            block.coordinate(),
+            Coordinate::from_rect(72.0, 100.0, 312.0, 142.0)
+        );
+
+        // Moving the block moves its bounding box
+        block.y = 200.0;
+        assert_eq!(
+            block.coordinate(),
+            Coordinate::from_rect(72.0, 200.0, 312.0, 242.0)
+        );
+    }
+
+    #[test]
+    fn test_block_prebuild_text() {
+        let mut block = block_with_lines(
+            72.0,
+            100.0,
+            468.0,
+            24.0,
+            "Introduction",
+            &["Attention is", "all we need."],
+        );
+        let text = block.get_text();
+        assert!(block.text_cache.get().is_none());
+        let uncached = block.clone();
+
+        block.prebuild_text();
+        assert_eq!(block.text_cache.get(), Some(&text));
+        assert_eq!(block, uncached);
+        assert_eq!(block.get_text(), text);
+        assert_eq!(block.get_text(), block.get_text());
+
+        block.add_line(72.0, 124.0, 468.0, 10.0);
+        assert!(block.text_cache.get().is_none());
+        block.lines[2].add_word("Really.".to_string(), 72.0, 124.0, 40.0, 10.0);
+        assert_eq!(block.get_text(), "Attention is all we need. Really.");
+    }
+
+    #[test]
+    fn test_coordinate_conversions() {
+        let coord = Coordinate::from((10.0, 20.0, 110.0, 70.0));
+        assert_eq!(coord, Coordinate::from_rect(10.0, 20.0, 110.0, 70.0));
+        assert_eq!(coord.as_rect(), (10.0, 20.0, 110.0, 70.0));
+        let coord: Coordinate = (1.0, 2.0, 3.0, 4.0).into();
+        assert_eq!(coord.as_rect(), (1.0, 2.0, 3.0, 4.0));
+
+        let mut block = Block::new(72.0, 100.0, 200.0, 24.0);
+        block.add_line(72.0, 100.0, 180.0, 10.0);
+        block.lines[0].add_word("Attention".to_string(), 72.0, 100.0, 50.0, 10.0);
+        assert_eq!(
+            Coordinate::from(&block),
+            Coordinate::from_object(72.0, 100.0, 200.0, 24.0)
+        );
+        assert_eq!(
+            Coordinate::from(&block.lines[0]).as_rect(),
+            (72.0, 100.0, 252.0, 110.0)
+        );
+        assert_eq!(
+            Coordinate::from(&block.lines[0].words[0]).as_rect(),
+            (72.0, 100.0, 122.0, 110.0)
+        );
+    }
+
+    #[test]
+    fn test_line_logical_words_rtl() {
+        // Visual order from left to right: "עולם" "Rust" "2024" "עם" "שלום"
+        let mut line = Line::new(10.0, 100.0, 200.0, 10.0);
+        line.add_word("Rust".to_string(), 60.0, 100.0, 30.0, 10.0);
+        line.add_word("שלום".to_string(), 170.0, 100.0, 40.0, 10.0);
+        line.add_word("עולם".to_string(), 10.0, 100.0, 40.0, 10.0);
+        line.add_word("2024".to_string(), 95.0, 100.0, 30.0, 10.0);
+        line.add_word("עם".to_string(), 135.0, 100.0, 25.0, 10.0);
+        assert!(line.is_rtl());
+        assert_eq!(line.get_text(), "שלום עם Rust 2024 עולם");
+
+        let mut line = Line::new(10.0, 100.0, 200.0, 10.0);
+        line.add_word("Attention".to_string(), 10.0, 100.0, 50.0, 10.0);
+        line.add_word("(انتباه)".to_string(), 65.0, 100.0, 50.0, 10.0);
+        assert!(!line.is_rtl());
+        assert_eq!(line.get_text(), "Attention (انتباه)");
+    }
+
+    #[test]
+    fn test_page_text_coverage() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        assert_eq!(page.text_coverage(), 0.0);
+        assert_eq!(page.block_area_stats(), None);
+
+        page.blocks = vec![
+            block_with_lines(72.0, 100.0, 468.0, 10.0, "Introduction", &["one", "two"]),
+            block_with_lines(72.0, 200.0, 468.0, 10.0, "Introduction", &["three"]),
+            block_with_lines(
+                72.0,
+                300.0,
+                468.0,
+                10.0,
+                "Introduction",
+                &["four", "five", "six"],
+            ),
+        ];
+        let coverage = page.text_coverage();
+        assert!(coverage > 0.0 && coverage < 1.0);
+        assert!((coverage - 468.0 * 72.0 / (612.0 * 792.0)).abs() < 1e-6);
+
+        let stats = page.block_area_stats().unwrap();
+        assert_eq!(stats.min, 468.0 * 12.0);
+        assert_eq!(stats.median, 468.0 * 24.0);
+        assert_eq!(stats.max, 468.0 * 36.0);
+    }
+
+    #[test]
+    fn test_line_baseline() {
+        let mut line = Line::new(72.0, 100.0, 40.0, 10.0);
+        line.add_word("x".to_string(), 72.0, 100.0, 10.0, 10.0);
+        assert_eq!(line.words[0].baseline(), 108.0);
+
+        let mut block = Block::new(72.0, 100.0, 468.0, 36.0);
+        for (i, y) in [100.0, 112.0, 124.0].iter().enumerate() {
+            block.add_line(72.0, *y, 468.0, 10.0);
+            block.lines[i].add_word("E".to_string(), 72.0, *y, 10.0, 10.0);
+            // A superscript does not move the baseline of the line
+            block.lines[i].add_word("2".to_string(), 82.0, *y - 2.0, 5.0, 6.0);
+            block.lines[i].add_word("= mc".to_string(), 90.0, *y, 40.0, 10.0);
+        }
+        let baselines = block.lines.iter().map(|line| line.baseline()).collect::<Vec<f32>>();
+        assert_eq!(baselines, vec![108.0, 120.0, 132.0]);
+        assert!(baselines.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_line_merge_split_words() {
+        let mut line = Line::new(72.0, 100.0, 200.0, 10.0);
+        line.add_word("Atten".to_string(), 72.0, 100.0, 30.0, 10.0);
+        line.add_word("tion".to_string(), 102.2, 100.0, 20.0, 10.0);
+        line.add_word("is".to_string(), 125.0, 100.0, 10.0, 10.0);
+        // Touching, but raised as a superscript
+        line.add_word("1".to_string(), 135.0, 97.0, 4.0, 6.0);
+        line.merge_split_words();
+
+        assert_eq!(line.get_text(), "Attention is 1");
+        assert_eq!(line.words[0].x, 72.0);
+        assert_eq!(line.words[0].y, 100.0);
+        assert!((line.words[0].width - 50.2).abs() < 1e-4);
+        assert_eq!(line.words[0].height, 10.0);
+    }
+
+    #[test]
+    fn test_coordinate_scale() {
+        let coord = Coordinate::from_rect(10.0, 20.0, 30.0, 60.0);
+        let scaled = coord.scale(2.0, 0.5);
+
+        assert_eq!(scaled.top_left, Point::new(20.0, 10.0));
+        assert_eq!(scaled.top_right, Point::new(60.0, 10.0));
+        assert_eq!(scaled.bottom_left, Point::new(20.0, 30.0));
+        assert_eq!(scaled.bottom_right, Point::new(60.0, 30.0));
+        assert_eq!(scaled.get_area(), coord.get_area() * 2.0 * 0.5);
+        assert_eq!(coord.scale(3.0, 4.0).get_area(), coord.get_area() * 12.0);
+    }
+
+    #[test]
+    fn test_coordinate_translate() {
+        let coord = Coordinate::from_rect(10.0, 20.0, 30.0, 60.0);
+        let translated = coord.translate(5.0, -10.0);
+
+        assert_eq!(translated.top_left, Point::new(15.0, 10.0));
+        assert_eq!(translated.top_right, Point::new(35.0, 10.0));
+        assert_eq!(translated.bottom_left, Point::new(15.0, 50.0));
+        assert_eq!(translated.bottom_right, Point::new(35.0, 50.0));
+        assert_eq!(translated.get_area(), coord.get_area());
+    }
+
+    #[test]
+    fn test_coordinate_expand() {
+        let point = Coordinate::from_rect(100.0, 100.0, 100.0, 100.0);
+        let nearby = Coordinate::from_rect(101.0, 99.0, 120.0, 110.0);
+        assert!(!point.is_intercept(&nearby));
+        assert!(point.expand(2.0).is_intercept(&nearby));
+
+        let coord = Coordinate::from_rect(10.0, 20.0, 30.0, 60.0);
+        assert_eq!(coord.expand(5.0).as_rect(), (5.0, 15.0, 35.0, 65.0));
+        assert_eq!(coord.inset(5.0).as_rect(), (15.0, 25.0, 25.0, 55.0));
+        assert_eq!(coord.inset(50.0).as_rect(), (20.0, 30.0, 20.0, 50.0));
+    }
+
+    #[test]
+    fn test_coordinate_center() {
+        let coord = Coordinate::from_rect(10.0, 20.0, 30.0, 60.0);
+        assert_eq!(coord.center(), Point::new(20.0, 40.0));
+        let point = Coordinate::from_rect(5.0, 5.0, 5.0, 5.0);
+        assert_eq!(point.center(), Point::new(5.0, 5.0));
+    }
+
+    #[test]
+    fn test_coordinate_center_distance() {
+        let a = Coordinate::from_rect(0.0, 0.0, 10.0, 10.0);
+        let b = Coordinate::from_rect(30.0, 40.0, 40.0, 50.0);
+        assert_eq!(a.center_distance(&b), 50.0);
+        assert_eq!(b.center_distance(&a), 50.0);
+        assert_eq!(a.center_distance(&a), 0.0);
+    }
+
+    #[test]
+    fn test_coordinate_gaps() {
+        let left = Coordinate::from_rect(72.0, 100.0, 292.0, 200.0);
+        let right = Coordinate::from_rect(320.0, 150.0, 540.0, 250.0);
+        let below = Coordinate::from_rect(72.0, 212.0, 292.0, 300.0);
+
+        assert_eq!(left.horizontal_gap(&right), 28.0);
+        assert_eq!(right.horizontal_gap(&left), 28.0);
+        assert_eq!(left.horizontal_gap(&below), 0.0);
+        assert_eq!(left.vertical_gap(&below), 12.0);
+        assert_eq!(below.vertical_gap(&left), 12.0);
+        assert_eq!(left.vertical_gap(&right), 0.0);
+    }
+
+    fn page_with_blocks(page_number: PageNumber, texts: &[&str]) -> Page {
+        let mut page = Page::new(612.0, 792.0, page_number);
+        for (i, text) in texts.iter().enumerate() {
+            let y = 100.0 + 20.0 * i as f32;
+            page.add_block(72.0, y, 200.0, 10.0);
+            page.blocks[i].add_line(72.0, y, 200.0, 10.0);
+            page.blocks[i].lines[0].add_word(text.to_string(), 72.0, y, 200.0, 10.0);
+        }
+        return page;
+    }
+
+    fn header_document() -> Document {
+        let mut first = Page::new(612.0, 792.0, 1);
+        first.blocks = vec![
+            block_with_lines(
+                72.0,
+                90.0,
+                468.0,
+                17.0,
+                "Abstract",
+                &["Attention Is All You Need"],
+            ),
+            block_with_lines(
+                72.0,
+                140.0,
+                468.0,
+                10.0,
+                "Abstract",
+                &[
+                    "Ashish Vaswani, Noam Shazeer and Niki Parmar",
+                    "Google Brain",
+                ],
+            ),
+            block_with_lines(
+                72.0,
+                170.0,
+                468.0,
+                10.0,
+                "Abstract",
+                &["avaswani@google.com"],
+            ),
+            block_with_lines(72.0, 200.0, 468.0, 12.0, "Abstract", &["Abstract"]),
+            block_with_lines(
+                72.0,
+                220.0,
+                468.0,
+                10.0,
+                "Abstract",
+                &[
+                    "The dominant sequence transduction models are based on complex",
+                    "recurrent or convolutional neural networks.",
+                ],
+            ),
+            block_with_lines(
+                72.0,
+                260.0,
+                468.0,
+                10.0,
+                "Abstract",
+                &["Keywords: attention; transformer, machine translation."],
+            ),
+            block_with_lines(
+                72.0,
+                300.0,
+                468.0,
+                12.0,
+                "Introduction",
+                &["1 Introduction"],
+            ),
+        ];
+        return Document::new(vec![first]);
+    }
+
+    #[test]
+    fn test_document_header_metadata() {
+        let metadata = header_document().header_metadata();
+
+        assert_eq!(metadata.title.unwrap(), "Attention Is All You Need");
+        assert_eq!(
+            metadata.authors.unwrap(),
+            vec!["Ashish Vaswani", "Noam Shazeer", "Niki Parmar"]
+        );
+        let abstract_text = metadata.abstract_text.unwrap();
+        assert!(abstract_text.starts_with("The dominant sequence transduction models"));
+        assert!(abstract_text.ends_with("recurrent or convolutional neural networks."));
+        assert!(!abstract_text.contains("Keywords"));
+        assert_eq!(
+            metadata.keywords.unwrap(),
+            vec!["attention", "transformer", "machine translation"]
+        );
+    }
+
+    #[test]
+    fn test_document_keyword_list() {
+        let mut document = header_document();
+        assert_eq!(
+            document.keyword_list(),
+            vec!["attention", "transformer", "machine translation"]
+        );
+
+        // An IEEE "Index Terms" line ending the abstract block, wrapped over two lines
+        document.pages[0].blocks.remove(5);
+        let block = &mut document.pages[0].blocks[4];
+        let y = block.y + block.height;
+        for (i, text) in [
+            "Index Terms—Neural networks, self-atten-",
+            "tion; sequence modeling",
+        ]
+        .iter()
+        .enumerate()
+        {
+            let line_y = y + 12.0 * i as f32;
+            block.add_line(72.0, line_y, 468.0, 10.0);
+            block.lines.last_mut().unwrap().add_word(text.to_string(), 72.0, line_y, 468.0, 10.0);
+        }
+        assert_eq!(
+            document.keyword_list(),
+            vec!["Neural networks", "self-attention", "sequence modeling"]
+        );
+        assert!(document
+            .abstract_text()
+            .unwrap()
+            .ends_with("recurrent or convolutional neural networks."));
+
+        document.pages[0].blocks.remove(4);
+        assert!(document.keyword_list().is_empty());
+        assert!(Document::new(Vec::new()).keyword_list().is_empty());
+    }
+
+    #[test]
+    fn test_document_authors_with_markers() {
+        let mut document = header_document();
+        let block = &mut document.pages[0].blocks[1];
+        block.lines[0].words[0].text =
+            "Ashish Vaswani∗, Noam Shazeer1,2 and Niki Parmar†".to_string();
+        block.lines[0].add_word("‡".to_string(), 400.0, 140.0, 4.0, 6.0);
+        block.lines[0].words[1].script = Script::Super;
+
+        assert_eq!(
+            document.authors(),
+            vec!["Ashish Vaswani", "Noam Shazeer", "Niki Parmar"]
+        );
+        let authors = document.authors_with_markers();
+        assert_eq!(authors[0].markers, vec!["∗"]);
+        assert_eq!(authors[1].markers, vec!["1", "2"]);
+        assert_eq!(authors[2].markers, vec!["†", "‡"]);
+    }
+
+    #[test]
+    fn test_document_emails_and_affiliations() {
+        let mut document = header_document();
+        document.pages[0].blocks[1].lines[0].words[0].text =
+            "Ashish Vaswani avaswani@google.com, Noam Shazeer and Niki Parmar".to_string();
+        document.pages[0].blocks[2].lines[0].words[0].text = "{noam, nikip}@google.com".to_string();
+
+        assert_eq!(
+            document.authors(),
+            vec!["Ashish Vaswani", "Noam Shazeer", "Niki Parmar"]
+        );
+        assert_eq!(
+            document.emails(),
+            vec!["avaswani@google.com", "noam@google.com", "nikip@google.com"]
+        );
+        assert_eq!(document.affiliations(), vec!["Google Brain"]);
+
+        // An IEEE author grid: one column per author in the same lines
+        let mut grid = Block::new(72.0, 140.0, 468.0, 36.0);
+        let rows = [
+            ["Alice Smith", "Bob Jones"],
+            [
+                "1Dept. of Computer Science, Stanford University",
+                "2Microsoft Research",
+            ],
+            ["alice@stanford.edu", "bob.jones@microsoft.com"],
+        ];
+        for (i, row) in rows.iter().enumerate() {
+            let y = 140.0 + 12.0 * i as f32;
+            grid.add_line(72.0, y, 468.0, 10.0);
+            grid.lines[i].add_word(row[0].to_string(), 72.0, y, 200.0, 10.0);
+            grid.lines[i].add_word(row[1].to_string(), 340.0, y, 200.0, 10.0);
+        }
+        document.pages[0].blocks.splice(1..3, [grid]);
+
+        assert_eq!(document.authors(), vec!["Alice Smith", "Bob Jones"]);
+        assert_eq!(
+            document.emails(),
+            vec!["alice@stanford.edu", "bob.jones@microsoft.com"]
+        );
+        assert_eq!(
+            document.affiliations(),
+            vec![
+                "Dept. of Computer Science, Stanford University",
+                "Microsoft Research"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_document_venue() {
+        let mut document = header_document();
+        assert_eq!(document.arxiv_id(), None);
+        assert_eq!(document.venue(), None);
+
+        document.pages[0].margin_text = vec!["arXiv:1706.03762v7 [cs.CL] 2 Aug 2023".to_string()];
+        assert_eq!(document.arxiv_id().unwrap(), "1706.03762v7");
+        let venue = document.venue().unwrap();
+        assert_eq!(venue.text, "arXiv:1706.03762v7 [cs.CL] 2 Aug 2023");
+        assert_eq!(venue.date.unwrap(), "2 Aug 2023");
+
+        let footnote = "31st Conference on Neural Information Processing Systems (NIPS 2017), \
+                        Long Beach, CA, USA.";
+        document.pages[0].blocks.push(block_with_lines(
+            72.0,
+            700.0,
+            468.0,
+            8.0,
+            "Introduction",
+            &[footnote],
+        ));
+        let venue = document.venue().unwrap();
+        assert_eq!(venue.text, footnote);
+        assert_eq!(venue.date.unwrap(), "2017");
+
+        document.pages[0].margin_text = vec!["arXiv:hep-th/9901001v2".to_string()];
+        assert_eq!(document.arxiv_id().unwrap(), "hep-th/9901001v2");
+    }
+
+    #[test]
+    fn test_document_keywords() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.blocks = vec![
+            block_with_lines(
+                72.0,
+                100.0,
+                468.0,
+                10.0,
+                "Introduction",
+                &[
+                    "The Transformer relies on multi-head attention.",
+                    "Multi-head attention lets the model attend to positions.",
+                    "We compare self-attention with recurrent layers.",
+                    "Self-attention and multi-head attention are fast.",
+                ],
+            ),
+            block_with_lines(
+                72.0,
+                200.0,
+                468.0,
+                10.0,
+                "References",
+                &["Recurrent Recurrent Recurrent networks. In Proceedings of ACL."],
+            ),
+        ];
+        let keywords = Document::new(vec![page]).keywords(3);
+
+        assert_eq!(keywords.len(), 3);
+        assert_eq!(keywords[0], "multi-head attention");
+        assert!(keywords.iter().all(|keyword| !keyword.contains("recurrent networks")));
+        assert!(keywords.iter().all(|keyword| !keyword.contains("proceedings")));
+    }
+
+    #[test]
+    fn test_page_to_blocks_json() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.number_of_columns = 2;
+        for (x, y, section) in [
+            (320.0, 100.0, "right top"),
+            (72.0, 400.0, "left bottom"),
+            (320.0, 400.0, "right bottom"),
+            (72.0, 250.0, "left middle"),
+            (72.0, 100.0, "left top"),
+        ] {
+            let mut block = block_with_lines(72.0, y, 468.0, 10.0, section, &[section]);
+            block.x = x;
+            block.width = 220.0;
+            page.blocks.push(block);
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&page.to_blocks_json()).unwrap();
+        let records = json.as_array().unwrap();
+        assert_eq!(records.len(), 5);
+        assert_eq!(records[0]["block"], 0);
+        assert_eq!(records[0]["text"], "right top");
+
+        let mut indices = records
+            .iter()
+            .map(|record| record["reading_order"].as_u64().unwrap() as usize)
+            .collect::<Vec<usize>>();
+        let mut by_reading_order = records.iter().collect::<Vec<&serde_json::Value>>();
+        by_reading_order.sort_by_key(|record| record["reading_order"].as_u64().unwrap());
+        indices.sort();
+        assert_eq!(indices, (0..5).collect::<Vec<usize>>());
+
+        let columns = by_reading_order
+            .iter()
+            .map(|record| record["column"].as_u64().unwrap())
+            .collect::<Vec<u64>>();
+        assert_eq!(columns, vec![0, 0, 0, 1, 1]);
+        let sections = by_reading_order
+            .iter()
+            .map(|record| record["section"].as_str().unwrap())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            sections,
+            vec![
+                "left top",
+                "left middle",
+                "left bottom",
+                "right top",
+                "right bottom"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_page_blocks_in_reading_order() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.number_of_columns = 2;
+        for (x, y, section) in [
+            (320.0, 100.0, "right top"),
+            (72.0, 400.0, "left bottom"),
+            (320.0, 400.0, "right bottom"),
+            (72.0, 100.0, "left top"),
+        ] {
+            let mut block = block_with_lines(72.0, y, 468.0, 10.0, section, &[section]);
+            block.x = x;
+            block.width = 220.0;
+            page.blocks.push(block);
+        }
+        let columns = page.column_areas();
+        assert_eq!(page.blocks[0].reading_order_key(&columns).0, 1);
+        assert_eq!(page.blocks[1].reading_order_key(&columns).0, 0);
+
+        let order = page
+            .blocks_in_reading_order()
+            .iter()
+            .map(|block| block.section.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            order,
+            vec!["left top", "left bottom", "right top", "right bottom"]
+        );
+    }
+
+    #[test]
+    fn test_page_blocks_sorted() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        for (x, y, section) in [
+            (320.0, 101.0, "top right"),
+            (72.0, 300.0, "bottom"),
+            (72.0, 200.0, "middle"),
+            (72.0, 102.0, "top left"),
+        ] {
+            let mut block = block_with_lines(72.0, y, 468.0, 10.0, section, &[section]);
+            block.x = x;
+            page.blocks.push(block);
+        }
+
+        let order =
+            page.blocks_sorted().iter().map(|block| block.section.as_str()).collect::<Vec<&str>>();
+        assert_eq!(order, vec!["top left", "top right", "middle", "bottom"]);
+    }
+
+    #[test]
+    fn test_document_blocks_with_attr() {
+        let mut document = Document::new(vec![
+            page_with_blocks(1, &["1 Introduction", "Transformers are everywhere."]),
+            page_with_blocks(2, &["x = 1", "2 Background"]),
+        ]);
+        document.pages[0].blocks[0].attr = BlockAttr::Title;
+        document.pages[1].blocks[0].attr = BlockAttr::Equation;
+        document.pages[1].blocks[1].attr = BlockAttr::Title;
+
+        let titles = document.blocks_with_attr(BlockAttr::Title);
+        assert_eq!(titles.len(), 2);
+        assert!(titles.iter().all(|block| block.attr == BlockAttr::Title));
+        assert_eq!(titles[1].get_text(), "2 Background");
+        assert_eq!(document.pages[0].blocks_with_attr(BlockAttr::Text).len(), 1);
+        assert_eq!(document.blocks_with_attr(BlockAttr::Quote).len(), 0);
+    }
+
+    #[test]
+    fn test_document_iter_words_indexed() {
+        let mut document = Document::new(vec![
+            page_with_blocks(1, &["Attention", "Is"]),
+            page_with_blocks(2, &["All"]),
+        ]);
+        document.pages[0].blocks[1].lines[0].add_word("All".to_string(), 300.0, 120.0, 20.0, 10.0);
+
+        let words = document.iter_words_indexed().collect::<Vec<WordRef>>();
+        assert_eq!(words.len(), 4);
+        let first = &words[0];
+        assert_eq!(
+            (first.page, first.block, first.line, first.index),
+            (0, 0, 0, 0)
+        );
+        assert_eq!(first.word.text, "Attention");
+        assert_eq!(first.coordinate().as_rect(), (72.0, 100.0, 272.0, 110.0));
+        let second = &words[2];
+        assert_eq!(
+            (second.page, second.block, second.line, second.index),
+            (0, 1, 0, 1)
+        );
+        assert_eq!((words[3].page, words[3].block), (1, 0));
+    }
+
+    #[test]
+    fn test_document_preview() {
+        let document = Document::new(vec![
+            page_with_blocks(1, &["Attention", "Is", "All"]),
+            page_with_blocks(2, &["You", "Need"]),
+        ]);
+
+        let preview = document.preview(4);
+        assert_eq!(preview.split(' ').count(), 4);
+        assert_eq!(preview, "Attention Is All You");
+        assert_eq!(document.preview(2), "Attention Is");
+        assert_eq!(document.preview(10), "Attention Is All You Need");
+        assert_eq!(document.preview(0), "");
+    }
+
+    fn word_at(x: f32, width: f32) -> Word {
+        let mut line = Line::new(x, 100.0, width, 10.0);
+        line.add_word("word".to_string(), x, 100.0, width, 10.0);
+        return line.words.remove(0);
+    }
+
+    #[test]
+    fn test_aligned_x() {
+        let left = [
+            word_at(72.0, 40.0),
+            word_at(72.5, 120.0),
+            word_at(71.8, 60.0),
+        ];
+        let refs = left.iter().collect::<Vec<&Word>>();
+        assert_eq!(aligned_x(&refs, 1.0), Some(Alignment::Left));
+
+        let right = [
+            word_at(200.0, 40.0),
+            word_at(120.0, 120.5),
+            word_at(180.0, 60.0),
+        ];
+        let refs = right.iter().collect::<Vec<&Word>>();
+        assert_eq!(aligned_x(&refs, 1.0), Some(Alignment::Right));
+
+        let center = [
+            word_at(280.0, 40.0),
+            word_at(240.0, 120.0),
+            word_at(270.5, 60.0),
+        ];
+        let refs = center.iter().collect::<Vec<&Word>>();
+        assert_eq!(aligned_x(&refs, 1.0), Some(Alignment::Center));
+
+        let ragged = [
+            word_at(72.0, 40.0),
+            word_at(90.0, 120.0),
+            word_at(130.0, 60.0),
+        ];
+        let refs = ragged.iter().collect::<Vec<&Word>>();
+        assert_eq!(aligned_x(&refs, 1.0), None);
+        assert_eq!(aligned_x(&refs, 60.0), Some(Alignment::Left));
+
+        assert_eq!(aligned_x(&refs[..1], 1.0), None);
+        assert_eq!(aligned_x(&[], 1.0), None);
+    }
+
+    #[test]
+    fn test_block_line_spacing() {
+        let mut block = Block::new(72.0, 100.0, 200.0, 80.0);
+        for (i, y) in [100.0, 112.0, 124.0, 150.0, 162.0].iter().enumerate() {
+            block.add_line(72.0, *y, 200.0, 10.0);
+            block.lines[i].add_word("word".to_string(), 72.0, *y, 200.0, 10.0);
+        }
+        assert_eq!(block.line_spacing(), Some(12.0));
+
+        let mut double = Block::new(72.0, 100.0, 200.0, 34.0);
+        for (i, y) in [100.0, 124.0].iter().enumerate() {
+            double.add_line(72.0, *y, 200.0, 10.0);
+            double.lines[i].add_word("word".to_string(), 72.0, *y, 200.0, 10.0);
+        }
+        assert_eq!(double.line_spacing(), Some(24.0));
+
+        double.lines.pop();
+        assert_eq!(double.line_spacing(), None);
+    }
+
+    fn block_with_line_widths(lines: &[(f32, f32)]) -> Block {
+        let mut block = Block::new(72.0, 100.0, 200.0, 12.0 * lines.len() as f32);
+        for (i, (x, width)) in lines.iter().enumerate() {
+            let y = 100.0 + 12.0 * i as f32;
+            block.add_line(*x, y, *width, 10.0);
+            block.lines[i].add_word("word".to_string(), *x, y, *width, 10.0);
+        }
+        return block;
+    }
+
+    #[test]
+    fn test_block_is_justified() {
+        let justified = block_with_line_widths(&[
+            (72.0, 200.0),
+            (72.0, 199.5),
+            (72.0, 200.0),
+            (72.0, 80.0),
+            (84.0, 188.0),
+            (72.0, 200.0),
+            (72.0, 120.0),
+        ]);
+        assert!(justified.is_justified());
+
+        let ragged = block_with_line_widths(&[
+            (72.0, 200.0),
+            (72.0, 160.0),
+            (72.0, 185.0),
+            (72.0, 140.0),
+            (72.0, 90.0),
+        ]);
+        assert!(!ragged.is_justified());
+
+        let short = block_with_line_widths(&[(72.0, 200.0), (72.0, 80.0)]);
+        assert!(!short.is_justified());
+    }
+
+    #[test]
+    fn test_block_geometry_score() {
+        let clean =
+            block_with_line_widths(&[(84.0, 188.0), (72.0, 200.0), (72.0, 200.0), (72.0, 120.0)]);
+        assert!(clean.geometry_score() > 0.99, "{}", clean.geometry_score());
+
+        let mut jumbled = Block::new(72.0, 100.0, 280.0, 40.0);
+        for (x, y, width, height) in [
+            (72.0, 100.0, 200.0, 10.0),
+            (150.0, 104.0, 100.0, 24.0),
+            (72.0, 106.0, 200.0, 10.0),
+            (300.0, 130.0, 50.0, 4.0),
+        ] {
+            jumbled.add_line(x, y, width, height);
+        }
+        assert!(
+            jumbled.geometry_score() < 0.5,
+            "{}",
+            jumbled.geometry_score()
+        );
+
+        assert_eq!(
+            block_with_line_widths(&[(72.0, 200.0)]).geometry_score(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_page_text_in_region() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.add_block(150.0, 80.0, 312.0, 18.0);
+        page.blocks[0].add_line(150.0, 80.0, 312.0, 18.0);
+        let title = [
+            ("Attention", 150.0, 100.0),
+            ("Is", 256.0, 20.0),
+            ("All", 282.0, 40.0),
+            ("You", 328.0, 50.0),
+            ("Need", 384.0, 78.0),
+        ];
+        for (text, x, width) in title {
+            page.blocks[0].lines[0].add_word(text.to_string(), x, 80.0, width, 18.0);
+        }
+        page.add_block(72.0, 200.0, 468.0, 10.0);
+        page.blocks[1].add_line(72.0, 200.0, 468.0, 10.0);
+        page.blocks[1].lines[0].add_word("Abstract".to_string(), 72.0, 200.0, 60.0, 10.0);
+
+        let title_region = Coordinate::from_rect(140.0, 75.0, 472.0, 100.0);
+        assert_eq!(
+            page.text_in_region(&title_region),
+            "Attention Is All You Need"
+        );
+
+        let partial = Coordinate::from_rect(140.0, 75.0, 310.0, 100.0);
+        assert_eq!(page.text_in_region(&partial), "Attention Is All");
+        let outside = Coordinate::from_rect(0.0, 300.0, 612.0, 792.0);
+        assert_eq!(page.text_in_region(&outside), "");
+    }
+
+    #[test]
+    fn test_page_lines_text() {
+        let page = &header_document().pages[0];
+        let lines = page.lines_text().collect::<Vec<String>>();
+        assert_eq!(lines[0], "Attention Is All You Need");
+        assert_eq!(lines[2], "Google Brain");
+
+        let words = |text: &str| text.split_whitespace().map(str::to_string).collect::<Vec<_>>();
+        assert_eq!(words(&lines.join("\n")), words(&page.get_text()));
+        assert_eq!(Page::new(612.0, 792.0, 1).lines_text().count(), 0);
+    }
+
+    #[test]
+    fn test_document_font_size_histogram() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.blocks = vec![
+            block_with_lines(
+                72.0,
+                80.0,
+                468.0,
+                14.0,
+                "Abstract",
+                &["Attention Is All You Need"],
+            ),
+            block_with_lines(
+                72.0,
+                120.0,
+                468.0,
+                10.0,
+                "Abstract",
+                &["The dominant", "sequence", "models"],
+            ),
+            block_with_lines(
+                72.0,
+                200.0,
+                468.0,
+                12.0,
+                "Introduction",
+                &["1 Introduction"],
+            ),
+            block_with_lines(
+                72.0,
+                230.0,
+                468.0,
+                10.0,
+                "Introduction",
+                &["Recurrent neural", "networks"],
+            ),
+            block_with_lines(
+                72.0,
+                300.0,
+                468.0,
+                9.2,
+                "Introduction",
+                &["Figure 1: The Transformer"],
+            ),
+        ];
+        let document = Document::new(vec![page.clone(), page]);
+
+        let histogram = document.font_size_histogram();
+        assert_eq!(histogram, vec![(9.0, 2), (10.0, 10), (12.0, 2), (14.0, 2)]);
+        let body = histogram.iter().max_by_key(|(_, count)| *count).unwrap();
+        assert_eq!(body.0, 10.0);
+        assert!(Document::new(Vec::new()).font_size_histogram().is_empty());
+    }
+
+    #[test]
+    fn test_document_attr_bbox() {
+        let mut first = Page::new(612.0, 792.0, 1);
+        first.blocks = vec![
+            block_with_lines(72.0, 90.0, 468.0, 17.0, "", &["Attention Is All You Need"]),
+            block_with_lines(
+                72.0,
+                200.0,
+                468.0,
+                10.0,
+                "Introduction",
+                &["Recurrent neural networks"],
+            ),
+        ];
+        first.blocks[0].attr = BlockAttr::Title;
+        let mut last = Page::new(612.0, 792.0, 2);
+        last.blocks = vec![
+            block_with_lines(
+                72.0,
+                100.0,
+                468.0,
+                10.0,
+                "Conclusion",
+                &["We presented the Transformer."],
+            ),
+            block_with_lines(72.0, 300.0, 468.0, 12.0, "References", &["References"]),
+            block_with_lines(
+                72.0,
+                320.0,
+                468.0,
+                9.0,
+                "References",
+                &["[1] Jimmy Lei Ba.", "Layer norm."],
+            ),
+            block_with_lines(
+                72.0,
+                350.0,
+                468.0,
+                9.0,
+                "References",
+                &["[2] Dzmitry Bahdanau."],
+            ),
+        ];
+        last.blocks[1].attr = BlockAttr::Title;
+        let document = Document::new(vec![first, last]);
+
+        let titles = document.attr_bbox(BlockAttr::Title);
+        assert_eq!(titles.len(), 2);
+        assert_eq!(titles[0].as_rect(), (72.0, 90.0, 540.0, 109.0));
+
+        let texts = document.attr_bbox(BlockAttr::Text);
+        assert_eq!(texts.len(), 3);
+        let references = texts.last().unwrap();
+        assert_eq!(references.as_rect(), (72.0, 320.0, 540.0, 361.0));
+        assert!(references.top_left.y > titles[1].bottom_left.y);
+        assert!(document.attr_bbox(BlockAttr::Toc).is_empty());
+    }
+
+    fn two_column_page() -> Page {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.number_of_columns = 2;
+        for (i, (x, text)) in [
+            (72.0, "the encoder maps"),
+            (320.0, "the decoder then"),
+            (72.0, "an input sequence"),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let y = 100.0 + 40.0 * (i / 2) as f32;
+            page.add_block(*x, y, 220.0, 10.0);
+            page.blocks[i].add_line(*x, y, 220.0, 10.0);
+            page.blocks[i].lines[0].add_word(text.to_string(), *x, y, 220.0, 10.0);
+        }
+        page.add_block(72.0, 200.0, 468.0, 10.0);
+        page.blocks[3].add_line(72.0, 200.0, 468.0, 10.0);
+        page.blocks[3].lines[0].add_word("Table 1:".to_string(), 72.0, 200.0, 50.0, 10.0);
+        page.blocks[3].lines[0].add_word("BLEU scores".to_string(), 400.0, 200.0, 80.0, 10.0);
+        return page;
+    }
+
+    #[test]
+    fn test_page_crop() {
+        let page = two_column_page();
+        let left_half = Coordinate::from_rect(0.0, 0.0, 306.0, 792.0);
+
+        let cropped = page.crop(&left_half);
+        assert_eq!((cropped.width, cropped.height), (612.0, 792.0));
+        let texts = cropped.blocks.iter().map(|block| block.get_text()).collect::<Vec<String>>();
+        assert_eq!(
+            texts,
+            vec![
+                "the encoder maps",
+                "an input sequence",
+                "Table 1: BLEU scores"
+            ]
+        );
+        assert_eq!(cropped.blocks[0], page.blocks[0]);
+
+        let clipped = page.crop_with(&left_half, CropMode::Clip);
+        let texts = clipped.blocks.iter().map(|block| block.get_text()).collect::<Vec<String>>();
+        assert_eq!(
+            texts,
+            vec!["the encoder maps", "an input sequence", "Table 1:"]
+        );
+        assert_eq!(
+            clipped.blocks[2].coordinate().as_rect(),
+            (72.0, 200.0, 122.0, 210.0)
+        );
+        assert!(clipped.blocks.iter().all(|block| block.x < 306.0));
+    }
+
+    #[test]
+    fn test_page_flag_full_width_blocks() {
+        let mut page = two_column_page();
+        let flagged = page.flag_full_width_blocks();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].get_text(), "Table 1: BLEU scores");
+
+        page.blocks.truncate(3);
+        assert!(page.flag_full_width_blocks().is_empty());
+        let mut single = two_column_page();
+        single.number_of_columns = 1;
+        assert!(single.flag_full_width_blocks().is_empty());
+    }
+
+    /// Adds a block of `lines` lines filled with words between `left` and `right`, the gaps
+    /// between the words shifting from line to line as in justified text.
+    fn add_text_block(page: &mut Page, left: f32, right: f32, top: f32, lines: usize) {
+        page.add_block(left, top, right - left, 12.0 * lines as f32);
+        let block = page.blocks.last_mut().unwrap();
+        for i in 0..lines {
+            let y = top + 12.0 * i as f32;
+            block.add_line(left, y, right - left, 10.0);
+            let mut x = left;
+            let mut k = 0;
+            while x < right {
+                let width = (20.0 + ((i * 7 + k * 13) % 30) as f32).min(right - x);
+                block.lines[i].add_word("word".to_string(), x, y, width, 10.0);
+                x += width + 4.0;
+                k += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_page_column_count() {
+        let mut two_columns = Page::new(612.0, 792.0, 2);
+        add_text_block(&mut two_columns, 72.0, 540.0, 60.0, 2);
+        add_text_block(&mut two_columns, 72.0, 300.0, 100.0, 40);
+        add_text_block(&mut two_columns, 312.0, 540.0, 100.0, 40);
+        assert_eq!(two_columns.column_count(), 2);
+
+        let header = two_columns.crop(&Coordinate::from_rect(0.0, 50.0, 612.0, 90.0));
+        assert_eq!(header.column_count(), 1);
+
+        let mut single = Page::new(612.0, 792.0, 1);
+        add_text_block(&mut single, 72.0, 540.0, 100.0, 40);
+        assert_eq!(single.column_count(), 1);
+
+        // Line numbers in the margin are not a column
+        add_text_block(&mut single, 40.0, 52.0, 100.0, 40);
+        assert_eq!(single.column_count(), 1);
+        assert_eq!(Page::new(612.0, 792.0, 1).column_count(), 0);
+    }
+
+    #[test]
+    fn test_document_figures() {
+        let mut page = Page::new(612.0, 792.0, 3);
+        page.blocks = vec![
+            block_with_lines(
+                72.0,
+                72.0,
+                468.0,
+                10.0,
+                "Model Architecture",
+                &[
+                    "The Transformer follows this overall",
+                    "architecture using stacked",
+                ],
+            ),
+            block_with_lines(72.0, 200.0, 468.0, 10.0, "", &["Encoder"]),
+            block_with_lines(
+                72.0,
+                400.0,
+                468.0,
+                12.0,
+                "",
+                &["Figure 1: The Transformer - model architecture."],
+            ),
+            block_with_lines(
+                72.0,
+                450.0,
+                468.0,
+                10.0,
+                "",
+                &["Table 1: Maximum path lengths"],
+            ),
+            block_with_lines(
+                72.0,
+                480.0,
+                468.0,
+                10.0,
+                "",
+                &["Layer Type Complexity per Layer", "Self-Attention O(n^2 d)"],
+            ),
+        ];
+        page.blocks[2].attr = BlockAttr::Caption;
+        page.blocks[3].attr = BlockAttr::Caption;
+        let document = Document::new(vec![page]);
+
+        let figures = document.figures();
+        assert_eq!(figures.len(), 1);
+        let figure = &figures[0];
+        assert_eq!(figure.page_number, 3);
+        assert_eq!(figure.region.as_rect(), (72.0, 96.0, 540.0, 400.0));
+        assert!(figure.caption.get_text().starts_with("Figure 1:"));
+        assert_eq!(figure.image_path, None);
+
+        // A caption narrower than its figure still gets the width of its column
+        let mut document = document;
+        let caption = &mut document.pages[0].blocks[2];
+        caption.x = 250.0;
+        caption.width = 112.0;
+        let figures = document.figures();
+        assert_eq!(figures[0].region.as_rect(), (72.0, 96.0, 540.0, 400.0));
+
+        let mut page = Page::new(612.0, 792.0, 4);
+        page.number_of_columns = 2;
+        for (x, y, text) in [
+            (72.0, 100.0, "Left column of text"),
+            (320.0, 100.0, "Right column of text"),
+            (360.0, 400.0, "Fig. 2: Right figure."),
+        ] {
+            let width = if y < 400.0 { 220.0 } else { 120.0 };
+            page.add_block(x, y, width, 24.0);
+            let block = page.blocks.last_mut().unwrap();
+            for line_y in [y, y + 12.0] {
+                block.add_line(x, line_y, width, 10.0);
+                block.lines.last_mut().unwrap().add_word(text.to_string(), x, line_y, width, 10.0);
+            }
+        }
+        page.blocks[2].attr = BlockAttr::Caption;
+        let figures = Document::new(vec![page]).figures();
+        assert_eq!(figures[0].region.as_rect(), (320.0, 124.0, 540.0, 400.0));
+    }
+
+    #[test]
+    fn test_page_match_blocks() {
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.add_block(72.0, 100.0, 200.0, 50.0);
+        page.add_block(72.0, 160.0, 200.0, 50.0);
+        page.add_block(300.0, 100.0, 200.0, 50.0);
+        page.add_block(72.0, 600.0, 200.0, 50.0);
+        let reference = vec![
+            // Covers both of the first two blocks, but fits the second one better
+            Coordinate::from_rect(72.0, 110.0, 272.0, 210.0),
+            Coordinate::from_rect(72.0, 100.0, 272.0, 150.0),
+            Coordinate::from_rect(310.0, 100.0, 500.0, 150.0),
+            Coordinate::from_rect(300.0, 600.0, 500.0, 650.0),
+        ];
+
+        assert_eq!(
+            page.match_blocks(&reference, 0.5),
+            vec![Some(1), Some(0), Some(2), None]
+        );
+        assert_eq!(
+            page.match_blocks(&reference, 0.99),
+            vec![Some(1), None, None, None]
+        );
+    }
+
+    #[test]
+    fn test_section_appendix() {
+        let mut page = page_with_blocks(1, &["Introduction", "Body", "Appendix A Proofs", "Lemma"]);
+        page.blocks[0].section = "Introduction".to_string();
+        page.blocks[1].section = "Introduction".to_string();
+        page.blocks[2].section = "Appendix A Proofs".to_string();
+        page.blocks[3].section = "Appendix A Proofs".to_string();
+        let mut next = page_with_blocks(2, &["B Additional Results", "Table"]);
+        next.blocks[0].section = "B Additional Results".to_string();
+        next.blocks[1].section = "B Additional Results".to_string();
+
+        let sections = Section::from_pages(&[page, next]);
+        let flags = sections.iter().map(|section| section.appendix).collect::<Vec<bool>>();
+        assert_eq!(flags, vec![false, true, true]);
+        assert_eq!(sections[1].contents, vec!["Appendix A Proofs", "Lemma"]);
+
+        assert!(is_appendix_heading("Supplementary Material"));
+        assert!(is_appendix_heading("A.1 Proof of Theorem 1"));
+        assert!(!is_appendix_heading("A Simple Baseline"));
+        assert!(!is_appendix_heading("Related Work"));
+        assert!(is_appendix_heading("APPENDIX"));
+        assert!(is_appendix_heading("B supplemental figures"));
+        assert!(!is_appendix_heading("e.2 Results"));
+        assert!(!is_appendix_heading("a appendix"));
+    }
+
+    #[test]
+    fn test_document_sections() {
+        let blocks = [
+            (1, "Introduction", &["1 Introduction"][..]),
+            (1, "Introduction", &["Transformers are everywhere."]),
+            (2, "Introduction", &["They scale well."]),
+            (2, "Background", &["2 Background", "Attention came first."]),
+            (2, "Related Work", &["2.1 Related Work"]),
+            (2, "Related Work", &["RNNs were common."]),
+            (3, "Method", &["3 Method"]),
+            (3, "Method", &["We propose a model."]),
+        ];
+        let mut pages = vec![
+            Page::new(612.0, 792.0, 1),
+            Page::new(612.0, 792.0, 2),
+            Page::new(612.0, 792.0, 3),
+        ];
+        for (i, (page_number, section, texts)) in blocks.iter().enumerate() {
+            let block =
+                block_with_lines(72.0, 100.0 + 40.0 * i as f32, 468.0, 10.0, section, texts);
+            pages[*page_number - 1].blocks.push(block);
+        }
+        let sections = Document::new(pages).sections();
+
+        let outline = sections
+            .iter()
+            .map(|section| (section.heading.as_str(), section.level))
+            .collect::<Vec<(&str, u8)>>();
+        assert_eq!(
+            outline,
+            vec![
+                ("Introduction", 1),
+                ("Background", 1),
+                ("Related Work", 2),
+                ("Method", 1)
+            ]
+        );
+        assert_eq!(
+            sections[0].body,
+            "Transformers are everywhere.\nThey scale well."
+        );
+        assert!(!sections[0].body.contains("Attention"));
+        assert_eq!(
+            sections[1].body,
+            "Attention came first.\nRelated Work\nRNNs were common."
+        );
+        assert_eq!(sections[2].body, "RNNs were common.");
+        assert_eq!(sections[3].body, "We propose a model.");
+    }
+
+    #[test]
+    fn test_document_full_text_page_marker() {
+        let document = Document::new(vec![
+            page_with_blocks(1, &["first block"]),
+            page_with_blocks(2, &[]),
+            page_with_blocks(3, &["third block", "fourth block"]),
+            page_with_blocks(4, &["fifth block"]),
+        ]);
+        let text = document.full_text(&FullTextOptions::with_page_marker("<<PAGE>>"));
+        assert_eq!(text.matches("<<PAGE>>").count(), document.pages.len() - 1);
+        assert_eq!(
+            text.split("<<PAGE>>").nth(2).unwrap().trim(),
+            "third block\n\nfourth block"
+        );
+
+        let text = document.full_text(&FullTextOptions::with_page_marker("\u{000C}"));
+        assert_eq!(text.matches('\u{000C}').count(), document.pages.len() - 1);
+    }
+
+    #[test]
+    fn test_document_full_text() {
+        let document = Document::new(vec![
+            page_with_blocks(1, &["first block", "second block"]),
+            page_with_blocks(2, &["third block"]),
+            page_with_blocks(3, &["fourth block"]),
+        ]);
+
+        assert_eq!(
+            document.full_text(&FullTextOptions::default()),
+            "first block\n\nsecond block\n\nthird block\n\nfourth block"
+        );
+
+        let options = FullTextOptions {
+            block_separator: "\n".to_string(),
+            page_marker: Some("\u{000C}".to_string()),
+            ..FullTextOptions::default()
+        };
+        let text = document.full_text(&options);
+        assert_eq!(
+            text,
+            "first block\nsecond block\n\n\u{000C}third block\n\n\u{000C}fourth block"
+        );
+        assert_eq!(text.matches('\u{000C}').count(), document.pages.len() - 1);
+
+        // The continued block is found in reading order, whatever its place in the page
+        let mut next = page_with_blocks(2, &["of the first block", "footnote"]);
+        next.blocks.swap(0, 1);
+        next.blocks[1].continued = true;
+        let document = Document::new(vec![page_with_blocks(1, &["end"]), next]);
+        assert_eq!(
+            document.full_text(&FullTextOptions::default()),
+            "end of the first block\n\nfootnote"
+        );
+    }
+
+    #[test]
+    fn test_block_sentences() {
+        let mut block = Block::new(72.0, 100.0, 300.0, 34.0);
+        let texts = [
+            "Recurrent models, e.g. LSTMs, were studied by Dr. Hochre-",
+            "iter and Schmidhuber et al. in 1997 (see Fig. 1). They",
+            "scale poorly! Does attention help? See Table 1. J. K. Rowling agrees.",
+        ];
+        for (i, text) in texts.iter().enumerate() {
+            let y = 100.0 + 12.0 * i as f32;
+            block.add_line(72.0, y, 300.0, 10.0);
+            block.lines[i].add_word(text.to_string(), 72.0, y, 300.0, 10.0);
+        }
+
+        assert_eq!(
+            block.sentences(),
+            vec![
+                "Recurrent models, e.g. LSTMs, were studied by Dr. Hochreiter and Schmidhuber et al. in 1997 (see Fig. 1).",
+                "They scale poorly!",
+                "Does attention help?",
+                "See Table 1.",
+                "J. K. Rowling agrees.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_dominant_font_size() {
+        let mut line = Line::new(72.0, 100.0, 300.0, 30.0);
+        line.add_word("T".to_string(), 72.0, 100.0, 20.0, 30.0);
+        line.add_word("he".to_string(), 94.0, 120.0, 10.0, 10.2);
+        line.add_word("model".to_string(), 108.0, 120.0, 25.0, 9.9);
+        line.add_word("x".to_string(), 136.0, 122.0, 4.0, 7.0);
+
+        assert_eq!(line.dominant_font_size(), 10.0);
+
+        let mut block = Block::new(72.0, 100.0, 300.0, 42.0);
+        block.lines.push(line);
+        block.add_line(72.0, 132.0, 300.0, 10.0);
+        block.lines[1].add_word("continues".to_string(), 72.0, 132.0, 40.0, 10.0);
+        assert_eq!(block.font_size(), 10.0);
+
+        assert_eq!(Line::new(0.0, 0.0, 10.0, 12.0).dominant_font_size(), 12.0);
+
+        // A malformed box does not make the sorts panic
+        let mut line = Line::new(72.0, 100.0, 300.0, 10.0);
+        line.add_word("broken".to_string(), 72.0, f32::NAN, 30.0, f32::NAN);
+        line.add_word("box".to_string(), 106.0, 100.0, 20.0, 10.0);
+        line.add_word("here".to_string(), 130.0, 100.0, 20.0, 10.0);
+        assert_eq!(line.dominant_font_size(), 10.0);
+        line.detect_scripts();
+    }
+
+    #[test]
+    fn test_line_detect_scripts() {
+        let mut line = Line::new(10.0, 100.0, 120.0, 10.0);
+        line.add_word("attention".to_string(), 10.0, 100.0, 40.0, 10.0);
+        line.add_word("1".to_string(), 51.0, 98.0, 3.0, 6.0);
+        line.add_word("H".to_string(), 60.0, 100.0, 6.0, 10.0);
+        line.add_word("2".to_string(), 66.0, 106.0, 3.0, 6.0);
+        line.add_word("O".to_string(), 70.0, 100.0, 6.0, 10.0);
+        line.detect_scripts();
+
+        let scripts = line.words.iter().map(|word| word.script).collect::<Vec<Script>>();
+        assert_eq!(
+            scripts,
+            vec![
+                Script::Normal,
+                Script::Super,
+                Script::Normal,
+                Script::Sub,
+                Script::Normal
+            ]
+        );
+    }
+}
@@ -1,37 +1,116 @@
-use anyhow::Result;
+use anyhow::{Error, Result};
 use scraper::html;
 use std::collections::HashMap;
+#[cfg(feature = "native")]
+use std::collections::HashSet;
+#[cfg(feature = "native")]
+use std::path::Path;
+use std::sync::LazyLock;
 
+#[cfg(feature = "native")]
+use crate::config::CleanupGuard;
 use crate::config::{PageNumber, ParserConfig};
-use crate::converter::pdf2html;
-use crate::extracter::{adjst_columns, extract_tables, get_text_area};
-use crate::models::{Block, Coordinate, Line, Page, Section};
+#[cfg(feature = "native")]
+use crate::converter::{content_hash, download_pdf, pdf2html};
+#[cfg(feature = "native")]
+use crate::exporter::{export, OutputFormat};
+#[cfg(feature = "native")]
+use crate::extracter::extract_tables;
+use crate::extracter::{
+    adjst_columns, classify_blocks, default_postprocess, detect_equation_labels,
+    detect_page_continuations, fit_text_area, get_page_text_area, get_text_area,
+    merge_adjacent_blocks, reference_page_size, section_name, split_cross_column_blocks,
+};
+use crate::models::{
+    parse_numeric, Block, BlockAttr, Coordinate, Document, DocumentStats, Line, Page, Section,
+    Warning,
+};
 
-pub(crate) fn parse_html2pages(config: &mut ParserConfig, html: html::Html) -> Result<Vec<Page>> {
+/// The leading number of an attribute, split into its mantissa and its exponent.
+static NUMBER_PREFIX_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"^([+-]?[\d.,]*)(?:[eE]([+-]?\d+))?").unwrap());
+
+/// Parses a numeric attribute of the pdftotext HTML, tolerating formatting variations.
+///
+/// Trailing units such as "px" or "pt" are ignored and a comma is accepted as the decimal
+/// separator in numbers without a decimal point, as emitted by some pdftotext builds. The
+/// mantissa is read by `parse_numeric`, so that "1,234.5" reads as 1234.5, and values that
+/// are not finite, such as "NaN" or "inf", are rejected.
+///
+/// # Arguments
+///
+/// * `value` - The text of the attribute.
+///
+/// # Returns
+///
+/// A `Result` containing the parsed number, or an error if the text does not start with one.
+pub(crate) fn parse_number(value: &str) -> Result<f32> {
+    let value = value.trim();
+    if let Some(number) = value.parse::<f32>().ok().filter(|number| number.is_finite()) {
+        return Ok(number);
+    }
+    let captures = NUMBER_PREFIX_REGEX.captures(value).unwrap();
+    let exponent = match captures.get(2) {
+        Some(exponent) => exponent.as_str().parse::<i32>().ok(),
+        None => Some(0),
+    };
+    let number = parse_numeric(&captures[1], true)
+        .zip(exponent)
+        .map(|(mantissa, exponent)| (mantissa * 10f64.powi(exponent)) as f32)
+        .filter(|number| number.is_finite());
+    return number.ok_or_else(|| Error::msg(format!("Error: {:?} is not a number", value)));
+}
+
+fn parse_bbox(element: &scraper::ElementRef) -> Option<(f32, f32, f32, f32)> {
+    let value = |name: &str| parse_number(element.value().attr(name)?).ok();
+    return Some((
+        value("xmin")?,
+        value("ymin")?,
+        value("xmax")?,
+        value("ymax")?,
+    ));
+}
+
+pub(crate) fn parse_html2pages(
+    config: &mut ParserConfig,
+    html: html::Html,
+    warnings: &mut Vec<Warning>,
+) -> Result<Vec<Page>> {
     let mut pages = Vec::new();
     let page_selector = scraper::Selector::parse("page").unwrap();
+    config.check_page_count(html.select(&page_selector).count())?;
     let _pages = html.select(&page_selector);
     for (_page_number, page) in _pages.enumerate() {
         let page_number = (_page_number + 1) as PageNumber;
-        let page_width = page.value().attr("width").unwrap().parse::<f32>().unwrap();
-        let page_height = page.value().attr("height").unwrap().parse::<f32>().unwrap();
+        let page_width = page.value().attr("width").and_then(|w| parse_number(w).ok());
+        let page_height = page.value().attr("height").and_then(|h| parse_number(h).ok());
+        let (page_width, page_height) = match (page_width, page_height) {
+            (Some(width), Some(height)) => (width, height),
+            _ => {
+                warnings.push(Warning::MalformedPage { page_number });
+                continue;
+            }
+        };
         let mut _page = Page::new(page_width, page_height, page_number);
+        _page.resolution = config.resolution;
 
-        let fig_path = config.pdf_figures.get(&page_number).unwrap();
-        extract_tables(
-            fig_path,
-            &mut _page.tables,
-            _page.width as i32,
-            _page.height as i32,
-        );
+        #[cfg(feature = "native")]
+        if let Some(fig_path) = config.pdf_figures.get(&page_number) {
+            extract_tables(
+                fig_path,
+                &mut _page.tables,
+                _page.width as i32,
+                _page.height as i32,
+            );
+        }
 
         let block_selector = scraper::Selector::parse("block").unwrap();
         let _blocks = page.select(&block_selector);
         for block in _blocks {
-            let block_xmin = block.value().attr("xmin").unwrap().parse::<f32>().unwrap();
-            let block_ymin = block.value().attr("ymin").unwrap().parse::<f32>().unwrap();
-            let block_xmax = block.value().attr("xmax").unwrap().parse::<f32>().unwrap();
-            let block_ymax = block.value().attr("ymax").unwrap().parse::<f32>().unwrap();
+            let Some((block_xmin, block_ymin, block_xmax, block_ymax)) = parse_bbox(&block) else {
+                warnings.push(Warning::MalformedBlock { page_number });
+                continue;
+            };
             let mut _block = Block::new(
                 block_xmin,
                 block_ymin,
@@ -41,12 +120,13 @@ pub(crate) fn parse_html2pages(config: &mut ParserConfig, html: html::Html) -> R
 
             let line_selector = scraper::Selector::parse("line").unwrap();
             let _lines = block.select(&line_selector);
-            'line_iter: for line in _lines {
-                let line_xmin = line.value().attr("xmin").unwrap().parse::<f32>().unwrap();
-                let line_ymin = line.value().attr("ymin").unwrap().parse::<f32>().unwrap();
-                let line_xmax = line.value().attr("xmax").unwrap().parse::<f32>().unwrap();
-                let line_ymax = line.value().attr("ymax").unwrap().parse::<f32>().unwrap();
-                let mut _line = Line::new(
+            'line_iter: for line_element in _lines {
+                let Some((line_xmin, line_ymin, line_xmax, line_ymax)) = parse_bbox(&line_element)
+                else {
+                    warnings.push(Warning::MalformedLine { page_number });
+                    continue;
+                };
+                let mut line = Line::new(
                     line_xmin,
                     line_ymin,
                     line_xmax - line_xmin,
@@ -54,39 +134,58 @@ pub(crate) fn parse_html2pages(config: &mut ParserConfig, html: html::Html) -> R
                 );
 
                 for table in _page.tables.iter() {
-                    let line_coord =
-                        Coordinate::from_object(_line.x, _line.y, _line.width, _line.height);
+                    let line_coord = Coordinate::from(&line);
                     if line_coord.is_contained_in(&table) {
                         continue 'line_iter;
                     }
                 }
 
+                let add_word = if config.text_options.keep_word_spaces {
+                    Line::add_untrimmed_word
+                } else {
+                    Line::add_word
+                };
                 let word_selector = scraper::Selector::parse("word").unwrap();
-                let _words = line.select(&word_selector);
+                let _words = line_element.select(&word_selector);
                 for word in _words {
-                    let word_xmin = word.value().attr("xmin").unwrap().parse::<f32>().unwrap();
-                    let word_ymin = word.value().attr("ymin").unwrap().parse::<f32>().unwrap();
-                    let word_xmax = word.value().attr("xmax").unwrap().parse::<f32>().unwrap();
-                    let word_ymax = word.value().attr("ymax").unwrap().parse::<f32>().unwrap();
-                    let text = word.text().collect::<String>();
-                    _line.add_word(
-                        text.clone(),
+                    let text = config.text_options.normalize(&word.text().collect::<String>());
+                    let Some((word_xmin, word_ymin, word_xmax, word_ymax)) = parse_bbox(&word)
+                    else {
+                        warnings.push(Warning::MalformedWord { page_number, text });
+                        continue;
+                    };
+                    let (text, normalized_text) = config.text_options.apply_ligatures(&text);
+                    add_word(
+                        &mut line,
+                        text,
                         word_xmin,
                         word_ymin,
                         word_xmax - word_xmin,
                         word_ymax - word_ymin,
                     );
+                    if let Some(word) = line.words.last_mut() {
+                        word.normalized_text = normalized_text;
+                    }
+                }
+                if config.merge_split_words {
+                    line.merge_split_words();
                 }
-                if _line.get_text().trim().len() > 0 {
-                    _block.lines.push(_line);
+                line.detect_scripts();
+                if let Some(spans) = config.pdf_emphasis.get(&page_number) {
+                    line.apply_emphasis(spans);
+                }
+                if !config.drop_empty || !line.get_text().trim().is_empty() {
+                    _block.lines.push(line);
                 }
             }
-            if _block.lines.len() > 0 {
+            if !config.drop_empty || !_block.lines.is_empty() {
                 _page.blocks.push(_block);
             }
         }
         if _page.blocks.len() > 0 {
             pages.push(_page);
+        } else {
+            warnings.push(Warning::EmptyPage { page_number });
         }
     }
     return Ok(pages);
@@ -99,6 +198,7 @@ pub(crate) fn parse_extract_textarea(
     let section_titles =
         config.sections.iter().map(|(_, section)| section.to_lowercase()).collect::<Vec<String>>();
     let text_area = get_text_area(&pages);
+    let reference = reference_page_size(pages);
     let title_index_regex = regex::Regex::new(r"\d+\.").unwrap();
     for page in pages.iter_mut() {
         let mut remove_indices: Vec<usize> = Vec::new();
@@ -107,22 +207,33 @@ pub(crate) fn parse_extract_textarea(
         } else {
             page.width / 1.1
         };
+        let document_text_area = fit_text_area(&text_area, reference, page);
+        let page_text_area = if config.per_page_text_area {
+            get_page_text_area(page, &document_text_area)
+        } else {
+            document_text_area
+        };
         for (i, block) in page.blocks.iter_mut().enumerate() {
-            let block_coord = Coordinate::from_object(block.x, block.y, block.width, block.height);
-            let iou = text_area.iou(&block_coord);
+            let iou = page_text_area.iou(&block.coordinate());
             let block_text = block.get_text();
             let block_text = title_index_regex.replace(&block_text, "").trim().to_string();
+            let is_title = section_titles.contains(&block_text.to_lowercase());
+            let threshold = if is_title {
+                config.text_area_thresholds.title
+            } else {
+                config.text_area_thresholds.text
+            };
 
-            if (iou - 0.0).abs() < 1e-6 {
-                remove_indices.push(i);
-            } else if !section_titles.contains(&block_text.to_lowercase())
-                && (block.width / width < 0.3 && block.lines.len() < 4)
+            if (iou - 0.0).abs() < 1e-6
+                || block.coordinate().overlap_ratio(&page_text_area) < threshold
+                || (!is_title && block.width / width < 0.3 && block.lines.len() < 4)
             {
                 remove_indices.push(i);
             }
         }
         for i in remove_indices.iter().rev() {
-            page.blocks.remove(*i);
+            let block = page.blocks.remove(*i);
+            page.margin_text.insert(0, block.get_text());
         }
     }
     return Ok(());
@@ -139,14 +250,13 @@ pub(crate) fn parse_extract_section_text(
     }
 
     let mut page_number = 1;
-    let title_regex = regex::Regex::new(r"\d+\.").unwrap();
     for page in pages.iter_mut() {
         for block in page.blocks.iter_mut() {
             for line in block.lines.iter_mut() {
-                let text = line.get_text();
-                let text = title_regex.replace(&text, "").trim().to_string();
+                let text = section_name(&line.get_text());
                 if config.sections.iter().any(|(pg, section)| {
-                    text.to_lowercase() == *section.to_lowercase() && pg == &page_number
+                    text.to_lowercase() == section_name(section).to_lowercase()
+                        && pg == &page_number
                 }) {
                     current_section = text;
                 }
@@ -157,15 +267,273 @@ pub(crate) fn parse_extract_section_text(
     }
     return Ok(());
 }
+
+/// Recomputes the reading order and sections of parsed pages from their current `BlockAttr`s.
+///
+/// This lets a block be re-tagged by hand without parsing the document again: the blocks of
+/// each page are sorted in reading order, then every `Title` block outside the header of the
+/// first page starts a section, which the following blocks belong to. Blocks before the first
+/// heading keep the section of the first block. `Document::sections` then reflects the new
+/// headings and their levels.
+///
+/// Sections are named like in `parse`, after the heading text without its numbering. Unlike
+/// `parse`, which only starts a section at a line matching an entry of the PDF outline on the
+/// same page, the outline is not consulted: a `Title` block missing from the outline starts a
+/// section here, and an outline entry that was not tagged `Title` does not.
+///
+/// # Arguments
+///
+/// * `pages` - The parsed pages, updated in place.
+pub fn recompute_derived(pages: &mut Vec<Page>) {
+    for page in pages.iter_mut() {
+        let blocks = page.blocks_in_reading_order().into_iter().cloned().collect::<Vec<Block>>();
+        page.blocks = blocks;
+    }
+    let document = Document::new(std::mem::take(pages));
+    let header_len = document.header_blocks().len();
+    *pages = document.pages;
+
+    let mut current_section = match pages.iter().find_map(|page| page.blocks.first()) {
+        Some(block) => block.section.clone(),
+        None => return,
+    };
+    for (i, page) in pages.iter_mut().enumerate() {
+        for (j, block) in page.blocks.iter_mut().enumerate() {
+            let in_header = i == 0 && j < header_len;
+            if block.attr == BlockAttr::Title && !in_header {
+                current_section = section_name(&block.get_text());
+            }
+            block.section = current_section.clone();
+        }
+    }
+}
+
+/// Lays out the pages read from pdftotext and classifies their blocks.
+///
+/// This is the part of the pipeline shared by `parse` and `parse_html`: the pages go through
+/// `default_postprocess`, blocks outside the text area are removed, columns are detected and
+/// blocks spanning both columns split, sections are assigned, adjacent blocks are merged when
+/// `config.merge_blocks` is set, and the blocks are classified before `config.post_processors`
+/// run.
+///
+/// # Arguments
+///
+/// * `config` - The parser configuration.
+/// * `pages` - The pages parsed by `parse_html2pages`, updated in place.
+/// * `log` - Called with the name of each step once it has run.
+///
+/// # Returns
+///
+/// A `Result` containing the `DocumentStats` inferred while classifying the blocks.
+pub(crate) fn parse_layout(
+    config: &mut ParserConfig,
+    pages: &mut Vec<Page>,
+    mut log: impl FnMut(&str),
+) -> Result<DocumentStats> {
+    default_postprocess(pages, config);
+    log("Post-processed Pages");
+
+    parse_extract_textarea(config, pages)?;
+    log("Extracted Text Area");
+
+    adjst_columns(pages, config);
+    log("Adjusted Columns");
+
+    for page in pages.iter_mut() {
+        split_cross_column_blocks(page);
+    }
+    log("Split Cross-Column Blocks");
+
+    parse_extract_section_text(config, pages)?;
+    log("Extracted Sections");
+
+    if config.merge_blocks {
+        for page in pages.iter_mut() {
+            merge_adjacent_blocks(page);
+        }
+        log("Merged Adjacent Blocks");
+    }
+
+    let text_area = get_text_area(pages);
+    let reference = reference_page_size(pages);
+    for page in pages.iter_mut() {
+        classify_blocks(
+            page,
+            &fit_text_area(&text_area, reference, page),
+            config.classifier.as_ref(),
+            config.text_area_thresholds.font_size,
+        );
+        detect_equation_labels(page);
+    }
+    let stats = DocumentStats::new(pages, &text_area);
+    detect_page_continuations(pages);
+    for page in pages.iter_mut() {
+        for post_processor in config.post_processors.iter() {
+            post_processor.process(page);
+        }
+    }
+    log("Classified Blocks");
+    return Ok(stats);
+}
+
+#[cfg(feature = "native")]
 pub async fn parse(
     path_or_url: &str,
     config: &mut ParserConfig,
     verbose: bool,
 ) -> Result<Vec<Page>> {
+    let (pages, _, _) = parse_with_warnings(path_or_url, config, verbose).await?;
+    return Ok(pages);
+}
+
+/// Parses a local PDF file.
+///
+/// The file is read in place by the Poppler tools and only the intermediate files are
+/// written to `/tmp`, so large local documents are not copied before parsing.
+///
+/// # Arguments
+///
+/// * `path` - The path of the PDF file.
+/// * `config` - The parser configuration.
+/// * `verbose` - Whether to log the progress of each step.
+///
+/// # Returns
+///
+/// A `Result` containing the parsed `Page`s, or an error if `path` is not a readable file.
+#[cfg(feature = "native")]
+pub async fn parse_file(path: &str, config: &mut ParserConfig, verbose: bool) -> Result<Vec<Page>> {
+    if !std::path::Path::new(path).is_file() {
+        return Err(anyhow::Error::msg(format!(
+            "Error: {} is not a readable file",
+            path
+        )));
+    }
+    return parse(path, config, verbose).await;
+}
+
+/// Parses a PDF like `parse`, blocking the current thread until it is done.
+///
+/// A single-threaded tokio runtime is started for the duration of the call, so that
+/// synchronous programs do not need to set one up. This function must not be called from
+/// within an async context: tokio panics when a runtime is started inside another one, and
+/// `parse` should be awaited there instead.
+///
+/// # Arguments
+///
+/// * `path_or_url` - The path or URL of the PDF.
+/// * `config` - The parser configuration.
+/// * `verbose` - Whether to log the progress of each step.
+///
+/// # Returns
+///
+/// A `Result` containing the parsed `Page`s, or an error if the runtime could not be started
+/// or the parse failed.
+#[cfg(feature = "blocking")]
+pub fn parse_blocking(
+    path_or_url: &str,
+    config: &mut ParserConfig,
+    verbose: bool,
+) -> Result<Vec<Page>> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    return runtime.block_on(parse(path_or_url, config, verbose));
+}
+
+/// Parses a batch of PDFs, skipping the inputs whose content duplicates an earlier input.
+///
+/// Each input is read once: URLs are downloaded to the `pdf_path` of a default
+/// `ParserConfig`, and the saved file is both hashed with `content_hash` and parsed. The
+/// intermediate files are removed afterwards.
+///
+/// # Arguments
+///
+/// * `inputs` - The paths or URLs of the PDFs.
+/// * `verbose` - Whether to log the progress of each step.
+///
+/// # Returns
+///
+/// For each distinct input, in their original order, the input and the result of its parsing,
+/// which holds the error met if the input could not be read.
+#[cfg(feature = "native")]
+pub async fn parse_batch(inputs: &[&str], verbose: bool) -> Vec<(String, Result<Vec<Page>>)> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut results = Vec::new();
+    for input in inputs {
+        let mut config = ParserConfig::new();
+        let is_url = input.starts_with("http");
+        let path = if is_url {
+            config.pdf_path.clone()
+        } else {
+            input.to_string()
+        };
+        let read = async {
+            if is_url {
+                download_pdf(input, &path).await?;
+            }
+            return Ok::<Vec<u8>, Error>(tokio::fs::read(&path).await?);
+        };
+        let pages = match read.await {
+            Ok(bytes) if !seen.insert(content_hash(&bytes)) => {
+                let _ = config.clean_files();
+                continue;
+            }
+            Ok(_) => parse(&path, &mut config, verbose).await,
+            Err(e) => Err(e),
+        };
+        let _ = config.clean_files();
+        results.push((input.to_string(), pages));
+    }
+    return results;
+}
+
+/// Parses a PDF and exports it in the given format in a single call.
+///
+/// The default `ParserConfig` is used and the intermediate files are removed afterwards.
+///
+/// # Arguments
+///
+/// * `input` - The path or URL of the PDF.
+/// * `format` - The output format.
+///
+/// # Returns
+///
+/// A `Result` containing the exported document.
+#[cfg(feature = "native")]
+pub async fn parse_to_format(input: &str, format: OutputFormat) -> Result<String> {
+    let mut config = ParserConfig::new();
+    let pages = parse(input, &mut config, false).await;
+    let _ = config.clean_files();
+    return Ok(export(&pages?, format));
+}
+
+/// Parses a PDF like `parse` and also returns the non-fatal issues met on the way and the
+/// statistics inferred while classifying the blocks.
+///
+/// Malformed pages, blocks, lines and words are skipped instead of failing the whole parse,
+/// and pages without any text are dropped; each of them is reported as a `Warning`. When the
+/// parse fails, panics or is cancelled, the intermediate files are removed unless
+/// `config.keep_intermediates` is set.
+///
+/// # Arguments
+///
+/// * `path_or_url` - The path or URL of the PDF.
+/// * `config` - The parser configuration.
+/// * `verbose` - Whether to log the progress of each step.
+///
+/// # Returns
+///
+/// A `Result` containing the parsed `Page`s, the collected `Warning`s and the `DocumentStats`.
+#[cfg(feature = "native")]
+pub async fn parse_with_warnings(
+    path_or_url: &str,
+    config: &mut ParserConfig,
+    verbose: bool,
+) -> Result<(Vec<Page>, Vec<Warning>, DocumentStats)> {
     let time = std::time::Instant::now();
     if verbose {
         tracing::info!("Parsing PDF: {}", path_or_url);
     }
+    let mut guard = CleanupGuard::new(config);
+    let config = &mut *guard;
 
     let html = pdf2html(path_or_url, config, verbose, time).await?;
     if verbose {
@@ -175,7 +543,8 @@ pub async fn parse(
         );
     }
 
-    let mut pages = parse_html2pages(config, html)?;
+    let mut warnings = Vec::new();
+    let mut pages = parse_html2pages(config, html, &mut warnings)?;
     if verbose {
         tracing::info!(
             "Parsed HTML into Pages in {:.2}s, found {} pages",
@@ -183,30 +552,110 @@ pub async fn parse(
             pages.len()
         );
     }
-
-    parse_extract_textarea(config, &mut pages)?;
-    if verbose {
-        tracing::info!("Extracted Text Area in {:.2}s", time.elapsed().as_secs());
+    if pages.is_empty() {
+        // Some malformed PDFs are converted without error into a document without any page
+        guard.disarm();
+        let stats = DocumentStats::new(&pages, &get_text_area(&pages));
+        return Ok((pages, warnings, stats));
     }
 
-    adjst_columns(&mut pages, config);
-    if verbose {
-        tracing::info!("Adjusted Columns in {:.2}s", time.elapsed().as_secs());
-    }
-
-    parse_extract_section_text(config, &mut pages)?;
-    if verbose {
-        tracing::info!("Extracted Sections in {:.2}s", time.elapsed().as_secs());
-    }
+    let stats = parse_layout(config, &mut pages, |step| {
+        if verbose {
+            tracing::info!("{} in {:.2}s", step, time.elapsed().as_secs());
+        }
+    })?;
 
     if verbose {
         tracing::info!("Finished Parsing in {:.2}s", time.elapsed().as_secs());
     }
 
+    guard.disarm();
+    return Ok((pages, warnings, stats));
+}
+
+/// Parses a pdftotext `-bbox-layout` HTML document into pages without running any external
+/// command.
+///
+/// Table detection is skipped for pages without a rendered figure in `config.pdf_figures`.
+/// The pages then go through the same steps as in `parse`, with section names taken from
+/// `config.sections` and the page width from the pages themselves unless `config.pdf_info`
+/// gives one. A document without any `page` element gives an empty vector.
+///
+/// # Arguments
+///
+/// * `html` - The parsed pdftotext HTML document.
+/// * `config` - The parser configuration.
+///
+/// # Returns
+///
+/// A `Result` containing the vector of parsed `Page`s.
+pub fn parse_html(html: html::Html, config: &mut ParserConfig) -> Result<Vec<Page>> {
+    let (pages, _, _) = parse_html_with_warnings(html, config)?;
     return Ok(pages);
 }
 
-pub fn pages2json(pages: &Vec<Page>) -> String {
+/// Parses a pdftotext `-bbox-layout` HTML document like `parse_html` and also returns the
+/// non-fatal issues met on the way and the statistics inferred while classifying the blocks.
+///
+/// # Arguments
+///
+/// * `html` - The parsed pdftotext HTML document.
+/// * `config` - The parser configuration.
+///
+/// # Returns
+///
+/// A `Result` containing the parsed `Page`s, the collected `Warning`s and the `DocumentStats`.
+pub fn parse_html_with_warnings(
+    html: html::Html,
+    config: &mut ParserConfig,
+) -> Result<(Vec<Page>, Vec<Warning>, DocumentStats)> {
+    let mut warnings = Vec::new();
+    let mut pages = parse_html2pages(config, html, &mut warnings)?;
+    if pages.is_empty() {
+        let stats = DocumentStats::new(&pages, &get_text_area(&pages));
+        return Ok((pages, warnings, stats));
+    }
+    let stats = parse_layout(config, &mut pages, |_| {})?;
+    return Ok((pages, warnings, stats));
+}
+
+/// Parses a pdftotext `-bbox-layout` HTML string into pages.
+///
+/// See `parse_html` for the steps applied to the document.
+///
+/// # Arguments
+///
+/// * `html` - The pdftotext HTML text.
+/// * `config` - The parser configuration.
+///
+/// # Returns
+///
+/// A `Result` containing the vector of parsed `Page`s.
+pub fn parse_html_str(html: &str, config: &mut ParserConfig) -> Result<Vec<Page>> {
+    return parse_html(scraper::Html::parse_document(html), config);
+}
+
+/// Parses a pdftotext `-bbox-layout` HTML file saved on disk into pages.
+///
+/// This allows checkpointing after the conversion step, for example by keeping the file at
+/// `config.pdf_text_path`. See `parse_html` for the steps applied to the document.
+///
+/// # Arguments
+///
+/// * `path` - The path of the pdftotext HTML file.
+/// * `config` - The parser configuration.
+///
+/// # Returns
+///
+/// A `Result` containing the vector of parsed `Page`s, or an error if the file can't be read.
+#[cfg(feature = "native")]
+pub fn parse_html_file(path: &Path, config: &mut ParserConfig) -> Result<Vec<Page>> {
+    let html = std::fs::read_to_string(path)
+        .map_err(|e| Error::msg(format!("Error: failed to read {}: {}", path.display(), e)))?;
+    return parse_html_str(&html, config);
+}
+
+pub fn pages2json(pages: &[Page]) -> String {
     let sections = Section::from_pages(pages);
     let mut json_data = Vec::<HashMap<&str, String>>::new();
     for section in sections.iter() {
@@ -219,15 +668,72 @@ pub fn pages2json(pages: &Vec<Page>) -> String {
     return json;
 }
 
+pub fn pages2markdown(pages: &[Page]) -> String {
+    let title_index_regex = regex::Regex::new(r"\d+\.").unwrap();
+    let mut markdown = String::new();
+    let mut current_section = String::new();
+    for page in pages {
+        for block in &page.blocks {
+            if block.section != current_section {
+                current_section = block.section.clone();
+                markdown.push_str(&format!("## {}\n\n", current_section));
+            }
+            let block_text = title_index_regex.replace(&block.get_text(), "").trim().to_string();
+            if block_text.to_lowercase() == current_section.to_lowercase() {
+                continue;
+            }
+            markdown.push_str(&block.get_markdown_text());
+            markdown.push_str("\n\n");
+        }
+    }
+    return markdown;
+}
+
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "download")]
     use super::*;
-    use crate::config::ParserConfig;
-    use crate::models::{Coordinate, Section};
+    use crate::config::{FullTextOptions, LigatureMode, PageNumber, ParserConfig, TooManyPages};
+    #[cfg(feature = "download")]
+    use crate::converter::render_figures;
+    #[cfg(feature = "native")]
+    use crate::exporter::OutputFormat;
+    use crate::exporter::{to_jsonl, to_jsonl_with};
+    use crate::extracter::{
+        classify_blocks, get_text_area, BlockClassifier, ClassifyContext, PostProcessor,
+    };
+    use crate::extracter::{fit_text_area, reference_page_size};
+    use crate::models::Document;
+    use crate::models::Page;
+    #[cfg(feature = "native")]
+    use crate::models::Section;
+    use crate::models::Warning;
+    use crate::models::{Block, BlockAttr, Coordinate, CoordinateOrigin, TextBlock};
+    #[cfg(feature = "download")]
     use crate::parser::pages2json;
+    #[cfg(feature = "native")]
     use crate::parser::parse;
+    #[cfg(feature = "native")]
+    use crate::parser::parse_batch;
+    #[cfg(feature = "blocking")]
+    use crate::parser::parse_blocking;
+    use crate::parser::parse_extract_textarea;
+    #[cfg(feature = "native")]
+    use crate::parser::parse_file;
+    use crate::parser::parse_html2pages;
+    #[cfg(feature = "native")]
+    use crate::parser::parse_html_file;
+    use crate::parser::parse_html_str;
+    use crate::parser::parse_html_with_warnings;
+    use crate::parser::parse_number;
+    #[cfg(feature = "native")]
+    use crate::parser::parse_to_format;
+    use crate::parser::recompute_derived;
+    #[cfg(feature = "download")]
     use crate::test_utils::{BuiltinPaper, TestPapers};
+    use std::sync::Arc;
 
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_parse_extract_sections_1() {
         let tp = TestPapers::setup().await.expect("setup test papers");
@@ -254,6 +760,39 @@ mod tests {
         assert!(config.sections.len() >= 5);
     }
 
+    #[cfg(feature = "download")]
+    #[test_log::test(tokio::test)]
+    async fn test_parse_figures() {
+        let tp = TestPapers::setup().await.expect("setup test papers");
+        let paper = tp.get_by_title(BuiltinPaper::AttentionIsAllYouNeed).unwrap();
+        let mut config = ParserConfig::new();
+        let pages = parse(
+            paper.dest_path(&tp.tmp_dir).to_str().unwrap(),
+            &mut config,
+            true,
+        )
+        .await
+        .unwrap();
+        let mut figures = Document::new(pages).figures();
+        for figure in figures.iter() {
+            tracing::info!(
+                "page {}: {:?} {}",
+                figure.page_number,
+                figure.region.as_rect(),
+                figure.caption.get_text()
+            );
+        }
+        assert!(!figures.is_empty(), "no figure found");
+        assert!(figures[0].caption.get_text().starts_with("Figure"));
+
+        render_figures(&config, &mut figures[..1], 72).await.unwrap();
+        let image_path = figures[0].image_path.clone().unwrap();
+        assert!(std::path::Path::new(&image_path).exists());
+        let _ = std::fs::remove_file(image_path);
+        let _ = config.clean_files();
+    }
+
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_parse_extract_sections_2() {
         let tp = TestPapers::setup().await.expect("setup test papers");
@@ -280,6 +819,7 @@ mod tests {
         assert!(config.sections.len() >= 6);
     }
 
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_parse_extract_sections_3() {
         let tp = TestPapers::setup().await.expect("setup test papers");
@@ -306,6 +846,7 @@ mod tests {
         assert!(config.sections.len() >= 9);
     }
 
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_parse_1() {
         let mut config = ParserConfig::new();
@@ -323,8 +864,7 @@ mod tests {
                 page.height
             );
             for block in &page.blocks {
-                let block_coord =
-                    Coordinate::from_object(block.x, block.y, block.width, block.height);
+                let block_coord = &block.coordinate();
                 tracing::info!(
                     "    {} [({},{})x({},{})]:{}",
                     block.section,
@@ -340,6 +880,7 @@ mod tests {
         let _ = config.clean_files();
     }
 
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_parse_2() {
         let mut config = ParserConfig::new();
@@ -357,8 +898,7 @@ mod tests {
                 page.height
             );
             for block in &page.blocks {
-                let block_coord =
-                    Coordinate::from_object(block.x, block.y, block.width, block.height);
+                let block_coord = &block.coordinate();
                 tracing::info!(
                     "    {} [({},{})x({},{})]:{}",
                     block.section,
@@ -374,6 +914,7 @@ mod tests {
         let _ = config.clean_files();
     }
 
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_parse_local_sample() {
         let tp = TestPapers::setup().await.expect("setup test papers");
@@ -388,6 +929,125 @@ mod tests {
         let _ = tp.cleanup();
     }
 
+    #[cfg(feature = "download")]
+    #[test_log::test(tokio::test)]
+    async fn test_header_metadata() {
+        let tp = TestPapers::setup().await.expect("setup test papers");
+        let paper = tp.get_by_title(BuiltinPaper::AttentionIsAllYouNeed).unwrap();
+        let mut config = ParserConfig::new();
+        let pages = parse(
+            paper.dest_path(&tp.tmp_dir).to_str().unwrap(),
+            &mut config,
+            true,
+        )
+        .await
+        .unwrap();
+        let document = Document::new(pages);
+        let metadata = document.header_metadata();
+        tracing::info!("{:?}", metadata);
+        tracing::info!("{:?} {:?}", document.arxiv_id(), document.venue());
+        assert_eq!(document.arxiv_id().unwrap(), "1706.03762v7");
+
+        assert!(metadata.title.unwrap().contains("Attention Is All You Need"));
+        assert!(metadata.abstract_text.unwrap().len() > 100);
+        let authors = metadata.authors.unwrap();
+        assert!(authors[0].starts_with("Ashish Vaswani"));
+        assert!(authors[0].ends_with(|c: char| c.is_alphabetic()));
+        tracing::info!("{:?} {:?}", document.emails(), document.affiliations());
+        assert!(document.emails().iter().any(|email| email == "avaswani@google.com"));
+        assert!(document.affiliations().iter().any(|affiliation| affiliation == "Google Brain"));
+
+        let _ = config.clean_files();
+        let _ = tp.cleanup();
+    }
+
+    #[cfg(feature = "download")]
+    #[test_log::test(tokio::test)]
+    async fn test_font_size_histogram() {
+        let tp = TestPapers::setup().await.expect("setup test papers");
+        let paper = tp.get_by_title(BuiltinPaper::AttentionIsAllYouNeed).unwrap();
+        let mut config = ParserConfig::new();
+        let (pages, _, stats) = parse_with_warnings(
+            paper.dest_path(&tp.tmp_dir).to_str().unwrap(),
+            &mut config,
+            true,
+        )
+        .await
+        .unwrap();
+        let histogram = Document::new(pages).font_size_histogram();
+        tracing::info!("{:?}", histogram);
+
+        let body = histogram.iter().max_by_key(|(_, count)| *count).unwrap();
+        assert_eq!(body.0, stats.normal_font_size);
+
+        let _ = config.clean_files();
+        let _ = tp.cleanup();
+    }
+
+    #[cfg(feature = "download")]
+    #[test_log::test(tokio::test)]
+    async fn test_column_count() {
+        let tp = TestPapers::setup().await.expect("setup test papers");
+
+        // The EMNLP paper is set in two columns below a full-width title block
+        let paper = tp.get_by_title(BuiltinPaper::UnsupervisedDialoguePolicies).unwrap();
+        let mut config = ParserConfig::new();
+        let pages = parse(
+            paper.dest_path(&tp.tmp_dir).to_str().unwrap(),
+            &mut config,
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(pages[1].column_count(), 2);
+        let abstract_top = pages[0]
+            .blocks
+            .iter()
+            .find(|block| block.get_text().starts_with("Abstract"))
+            .map_or(pages[0].height / 4.0, |block| block.y);
+        let title_region = Coordinate::from_rect(0.0, 0.0, pages[0].width, abstract_top - 1.0);
+        assert_eq!(pages[0].crop(&title_region).column_count(), 1);
+        let _ = config.clean_files();
+
+        // The Transformer paper is set in a single column
+        let paper = tp.get_by_title(BuiltinPaper::AttentionIsAllYouNeed).unwrap();
+        let mut config = ParserConfig::new();
+        let pages = parse(
+            paper.dest_path(&tp.tmp_dir).to_str().unwrap(),
+            &mut config,
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(pages[1].column_count(), 1);
+        let _ = config.clean_files();
+        let _ = tp.cleanup();
+    }
+
+    #[cfg(feature = "download")]
+    #[test_log::test(tokio::test)]
+    async fn test_keywords() {
+        let tp = TestPapers::setup().await.expect("setup test papers");
+        let paper = tp.get_by_title(BuiltinPaper::AttentionIsAllYouNeed).unwrap();
+        let mut config = ParserConfig::new();
+        let pages = parse(
+            paper.dest_path(&tp.tmp_dir).to_str().unwrap(),
+            &mut config,
+            true,
+        )
+        .await
+        .unwrap();
+        let keywords = Document::new(pages).keywords(10);
+        tracing::info!("{:?}", keywords);
+
+        assert_eq!(keywords.len(), 10);
+        assert!(keywords.iter().any(|keyword| keyword.contains("attention")));
+
+        let _ = config.clean_files();
+        let _ = tp.cleanup();
+    }
+
+    #[cfg(feature = "download")]
     #[test_log::test(tokio::test)]
     async fn test_pdf_to_json() {
         let tp = TestPapers::setup().await.expect("setup test papers");
@@ -423,4 +1083,961 @@ mod tests {
         }
         let _ = tp.cleanup();
     }
+
+    /// A word of an HTML fixture: its text and its box as `(xMin, yMin, xMax, yMax)`.
+    type FixtureWord<'a> = (&'a str, (f32, f32, f32, f32));
+
+    /// Builds a 612x792 `<page>` of pdftotext `-bbox-layout` HTML from its blocks, each block
+    /// being a list of lines and each line a list of words. The boxes of the lines and blocks
+    /// enclose their words.
+    fn page_html(blocks: &[&[&[FixtureWord]]]) -> String {
+        let union = |words: &mut dyn Iterator<Item = &FixtureWord>| {
+            return words.fold(
+                (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+                |(x0, y0, x1, y1), (_, (wx0, wy0, wx1, wy1))| {
+                    (x0.min(*wx0), y0.min(*wy0), x1.max(*wx1), y1.max(*wy1))
+                },
+            );
+        };
+        let bbox = |(x0, y0, x1, y1): (f32, f32, f32, f32)| {
+            return format!(
+                r#"xMin="{:.6}" yMin="{:.6}" xMax="{:.6}" yMax="{:.6}""#,
+                x0, y0, x1, y1
+            );
+        };
+        let mut html = String::from(r#"<page width="612.000000" height="792.000000"><flow>"#);
+        for lines in blocks {
+            let block_box = union(&mut lines.iter().flat_map(|words| words.iter()));
+            html.push_str(&format!("\n<block {}>", bbox(block_box)));
+            for words in lines.iter() {
+                html.push_str(&format!("\n<line {}>", bbox(union(&mut words.iter()))));
+                for (text, word_box) in words.iter() {
+                    html.push_str(&format!("\n<word {}>{}</word>", bbox(*word_box), text));
+                }
+                html.push_str("\n</line>");
+            }
+            html.push_str("\n</block>");
+        }
+        html.push_str("</flow>\n</page>");
+        return html;
+    }
+
+    /// Wraps `<page>` elements into a pdftotext HTML document.
+    fn html_document(pages: &[String]) -> String {
+        return format!("<html><body><doc>{}</doc></body></html>", pages.concat());
+    }
+
+    #[test]
+    fn test_parse_html_str() {
+        let html = r#"<!DOCTYPE html><html><head></head><body><doc>
+<page width="612.000000" height="792.000000">
+<flow><block xMin="72.0" yMin="100.0" xMax="400.0" yMax="122.0">
+<line xMin="72.0" yMin="100.0" xMax="400.0" yMax="110.0">
+<word xMin="72.0" yMin="100.0" xMax="120.0" yMax="110.0">Attention</word>
+<word xMin="124.0" yMin="100.0" xMax="140.0" yMax="110.0">is</word>
+<word xMin="144.0" yMin="100.0" xMax="160.0" yMax="110.0">all</word>
+</line>
+<line xMin="72.0" yMin="112.0" xMax="200.0" yMax="122.0">
+<word xMin="72.0" yMin="112.0" xMax="100.0" yMax="122.0">you</word>
+<word xMin="104.0" yMin="112.0" xMax="140.0" yMax="122.0">need.</word>
+</line>
+</block></flow>
+<flow><block xMin="150.0" yMin="200.0" xMax="400.0" yMax="210.0">
+<line xMin="150.0" yMin="200.0" xMax="400.0" yMax="210.0">
+<word xMin="150.0" yMin="200.0" xMax="180.0" yMax="210.0">y</word>
+<word xMin="184.0" yMin="200.0" xMax="190.0" yMax="210.0">=</word>
+<word xMin="194.0" yMin="200.0" xMax="240.0" yMax="210.0">f(x)+1</word>
+<word xMin="380.0" yMin="200.0" xMax="400.0" yMax="210.0">(1)</word>
+</line>
+</block></flow>
+</page>
+</doc></body></html>"#;
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(html, &mut config).unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].blocks.len(), 2);
+        assert_eq!(pages[0].blocks[0].get_text(), "Attention is all you need.");
+        assert_eq!(pages[0].blocks[0].attr, BlockAttr::Text);
+        assert_eq!(pages[0].blocks[1].attr, BlockAttr::Equation);
+    }
+
+    #[test]
+    fn test_parse_html_str_drops_rules() {
+        // A two-column page with a drawn rule between the columns and a page border on top
+        let column = |left: f32, right: f32| {
+            let mut lines = String::new();
+            for i in 0..4 {
+                let y = 100.0 + 12.0 * i as f32;
+                lines.push_str(&format!(
+                    r#"<line xMin="{left}" yMin="{y}" xMax="{right}" yMax="{bottom}">
+<word xMin="{left}" yMin="{y}" xMax="{right}" yMax="{bottom}">Body</word>
+</line>"#,
+                    bottom = y + 10.0
+                ));
+            }
+            return format!(
+                r#"<flow><block xMin="{left}" yMin="100" xMax="{right}" yMax="146">{lines}</block></flow>"#
+            );
+        };
+        let rule = |x1: f32, y1: f32, x2: f32, y2: f32, text: &str| {
+            return format!(
+                r#"<flow><block xMin="{x1}" yMin="{y1}" xMax="{x2}" yMax="{y2}">
+<line xMin="{x1}" yMin="{y1}" xMax="{x2}" yMax="{y2}">
+<word xMin="{x1}" yMin="{y1}" xMax="{x2}" yMax="{y2}">{text}</word>
+</line></block></flow>"#
+            );
+        };
+        let page_html = |rules: bool| {
+            let (border, separator) = match rules {
+                true => (
+                    rule(36.0, 36.0, 576.0, 37.0, "________"),
+                    rule(305.0, 90.0, 306.0, 700.0, "|"),
+                ),
+                false => (String::new(), String::new()),
+            };
+            return html_document(&[format!(
+                r#"<page width="612" height="792">{}{}{}{}</page>"#,
+                border,
+                column(72.0, 296.0),
+                separator,
+                column(316.0, 540.0)
+            )]);
+        };
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(&page_html(true), &mut config).unwrap();
+        let mut config = ParserConfig::new();
+        let expected = parse_html_str(&page_html(false), &mut config).unwrap();
+
+        assert_eq!(pages[0].blocks.len(), 2);
+        assert!(pages[0].blocks.iter().all(|block| block.get_text().starts_with("Body")));
+        assert_eq!(pages, expected);
+    }
+
+    #[test]
+    fn test_parse_extract_textarea_single_line() {
+        // pdftotext may report a zero height for a line, leaving a text area without height
+        let mut page = Page::new(612.0, 792.0, 1);
+        page.add_block(72.0, 380.0, 468.0, 12.0);
+        page.blocks[0].add_line(72.0, 386.0, 468.0, 0.0);
+        page.blocks[0].lines[0].add_word("Poster".to_string(), 72.0, 386.0, 468.0, 0.0);
+        let mut pages = vec![page];
+
+        let text_area = get_text_area(&pages);
+        assert!(text_area.height() > 0.0);
+
+        let mut config = ParserConfig::new();
+        parse_extract_textarea(&mut config, &mut pages).unwrap();
+        assert_eq!(pages[0].blocks.len(), 1);
+        classify_blocks(&mut pages[0], &text_area, config.classifier.as_ref(), 0.0);
+        assert_eq!(pages[0].blocks[0].attr, BlockAttr::Text);
+    }
+
+    #[test]
+    fn test_parse_extract_textarea_title_threshold() {
+        // The heading of the first page is wider than the body text of the document
+        let body_page = |page_number: PageNumber, title: bool| {
+            let mut page = Page::new(612.0, 792.0, page_number);
+            let top = if title { 112.0 } else { 100.0 };
+            if title {
+                page.add_block(52.0, 100.0, 508.0, 10.0);
+                page.blocks[0].add_line(52.0, 100.0, 508.0, 10.0);
+                page.blocks[0].lines[0].add_word(
+                    "Introduction".to_string(),
+                    52.0,
+                    100.0,
+                    508.0,
+                    10.0,
+                );
+            }
+            page.add_block(72.0, top, 468.0, 48.0);
+            let block = page.blocks.last_mut().unwrap();
+            for j in 0..4 {
+                let line_y = top + 12.0 * j as f32;
+                block.add_line(72.0, line_y, 468.0, 10.0);
+                block.lines[j].add_word("Body".to_string(), 72.0, line_y, 468.0, 10.0);
+            }
+            return page;
+        };
+        let pages = vec![body_page(1, true), body_page(2, false), body_page(3, false)];
+        let mut config = ParserConfig::new();
+        config.sections = vec![(1, "Introduction".to_string())];
+
+        config.text_area_thresholds.title = 0.95;
+        let mut strict = pages.clone();
+        parse_extract_textarea(&mut config, &mut strict).unwrap();
+        assert_eq!(strict[0].blocks.len(), 1);
+        assert_eq!(strict[0].margin_text, vec!["Introduction"]);
+
+        config.text_area_thresholds.title = 0.8;
+        let mut lenient = pages.clone();
+        parse_extract_textarea(&mut config, &mut lenient).unwrap();
+        assert_eq!(lenient[0].blocks.len(), 2);
+        assert_eq!(lenient[0].blocks[0].get_text(), "Introduction");
+    }
+
+    #[test]
+    fn test_recompute_derived() {
+        let texts = [
+            "Attention Is All You Need",
+            "Abstract We propose the Transformer.",
+            "1 Introduction",
+            "Recurrent models are sequential.",
+            "1.1 Motivation",
+            "Attention removes the recurrence.",
+        ];
+        let mut page = Page::new(612.0, 792.0, 1);
+        // Stored out of order, as pdftotext may emit them
+        for (i, text) in texts.iter().enumerate().rev() {
+            let y = 100.0 + 30.0 * i as f32;
+            let mut block = Block::new(72.0, y, 468.0, 10.0);
+            block.add_line(72.0, y, 468.0, 10.0);
+            block.lines[0].add_word(text.to_string(), 72.0, y, 468.0, 10.0);
+            block.section = "Abstract".to_string();
+            page.blocks.push(block);
+        }
+        page.blocks[5].attr = BlockAttr::Title;
+        page.blocks[3].attr = BlockAttr::Title;
+        let mut pages = vec![page];
+
+        recompute_derived(&mut pages);
+        assert_eq!(pages[0].blocks[0].get_text(), texts[0]);
+        let outline = |pages: &Vec<Page>| {
+            return Document::new(pages.clone())
+                .sections()
+                .iter()
+                .map(|section| (section.heading.clone(), section.level))
+                .collect::<Vec<(String, u8)>>();
+        };
+        assert_eq!(
+            outline(&pages),
+            vec![("Abstract".to_string(), 1), ("Introduction".to_string(), 1)]
+        );
+        assert_eq!(pages[0].blocks[5].section, "Introduction");
+
+        pages[0].blocks[4].attr = BlockAttr::Title;
+        recompute_derived(&mut pages);
+        assert_eq!(
+            outline(&pages),
+            vec![
+                ("Abstract".to_string(), 1),
+                ("Introduction".to_string(), 1),
+                ("Motivation".to_string(), 2)
+            ]
+        );
+        assert_eq!(pages[0].blocks[5].section, "Motivation");
+
+        let mut empty: Vec<Page> = Vec::new();
+        recompute_derived(&mut empty);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_parse_extract_textarea_per_page() {
+        // The body of the first page sits in the right half, the other pages use the left half
+        let body_page = |page_number: PageNumber, x: f32, stamp: bool| {
+            let mut page = Page::new(612.0, 792.0, page_number);
+            if stamp {
+                page.add_block(10.0, 200.0, 10.0, 300.0);
+                page.blocks[0].add_line(10.0, 200.0, 10.0, 300.0);
+                page.blocks[0].lines[0].add_word("arXiv".to_string(), 10.0, 200.0, 10.0, 300.0);
+            }
+            for i in 0..2 {
+                let y = 100.0 + 100.0 * i as f32;
+                page.add_block(x, y, 220.0, 48.0);
+                let block = page.blocks.last_mut().unwrap();
+                for j in 0..4 {
+                    let line_y = y + 12.0 * j as f32;
+                    block.add_line(x, line_y, 220.0, 10.0);
+                    block.lines[j].add_word("Body".to_string(), x, line_y, 220.0, 10.0);
+                }
+            }
+            return page;
+        };
+        let pages = vec![
+            body_page(1, 320.0, true),
+            body_page(2, 72.0, false),
+            body_page(3, 72.0, false),
+        ];
+
+        let mut config = ParserConfig::new();
+        let mut document_wide = pages.clone();
+        parse_extract_textarea(&mut config, &mut document_wide).unwrap();
+        assert_eq!(document_wide[0].blocks.len(), 0);
+
+        config.per_page_text_area = true;
+        let mut per_page = pages.clone();
+        parse_extract_textarea(&mut config, &mut per_page).unwrap();
+        assert_eq!(per_page[0].blocks.len(), 2);
+        assert!(per_page[0].blocks.iter().all(|block| block.x == 320.0));
+        assert_eq!(per_page[1].blocks.len(), 2);
+        assert_eq!(per_page[2].blocks.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_html_with_warnings() {
+        let html = r#"<html><body><doc>
+<page width="612.000000" height="792.000000">
+<flow><block xMin="72.0" yMin="100.0" xMax="400.0" yMax="110.0">
+<line xMin="72.0" yMin="100.0" xMax="400.0" yMax="110.0">
+<word xMin="72.0" yMin="100.0" xMax="120.0" yMax="110.0">Attention</word>
+<word xMin="124.0" yMin="100.0" xMax="abc" yMax="110.0">is</word>
+<word xMin="144.0" yMin="100.0" xMax="400.0" yMax="110.0">everything</word>
+</line>
+</block></flow>
+</page>
+<page width="612.000000"></page>
+<page width="612.000000" height="792.000000"></page>
+</doc></body></html>"#;
+        let mut config = ParserConfig::new();
+        let (pages, warnings, _) =
+            parse_html_with_warnings(scraper::Html::parse_document(html), &mut config).unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].blocks[0].get_text(), "Attention everything");
+        assert_eq!(
+            warnings,
+            vec![
+                Warning::MalformedWord {
+                    page_number: 1,
+                    text: "is".to_string()
+                },
+                Warning::MalformedPage { page_number: 2 },
+                Warning::EmptyPage { page_number: 3 },
+            ]
+        );
+    }
+
+    #[derive(Debug)]
+    struct TitleClassifier;
+
+    impl BlockClassifier for TitleClassifier {
+        fn classify(&self, _block: &Block, ctx: &ClassifyContext) -> BlockAttr {
+            assert!(ctx.font_size > 0.0);
+            return BlockAttr::Title;
+        }
+    }
+
+    #[test]
+    fn test_parse_html_str_classifier() {
+        let html = html_document(&[page_html(&[
+            &[&[("Whereas", (72.0, 100.0, 400.0, 110.0))]],
+            &[&[("x = y + 1 (1)", (72.0, 200.0, 400.0, 210.0))]],
+        ])]);
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        assert_eq!(pages[0].blocks[1].attr, BlockAttr::Equation);
+
+        config.classifier = Arc::new(TitleClassifier);
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        assert_eq!(pages[0].blocks.len(), 2);
+        assert!(pages[0].blocks.iter().all(|block| block.attr == BlockAttr::Title));
+    }
+
+    #[derive(Debug)]
+    struct DatasetTagger;
+
+    impl PostProcessor for DatasetTagger {
+        fn process(&self, page: &mut Page) {
+            let dataset = regex::Regex::new(r"\b(WMT|ImageNet|SQuAD)\b").unwrap();
+            for block in page.blocks.iter_mut() {
+                if dataset.is_match(&block.get_text()) {
+                    block.tags.push("dataset".to_string());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_html_str_post_processors() {
+        let html = html_document(&[page_html(&[
+            &[&[("We train on WMT 2014.", (72.0, 100.0, 400.0, 110.0))]],
+            &[&[("Results are shown below.", (72.0, 200.0, 400.0, 210.0))]],
+        ])]);
+        let mut config = ParserConfig::new();
+        config.post_processors.push(Arc::new(DatasetTagger));
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        assert_eq!(pages[0].blocks[0].tags, vec!["dataset"]);
+        assert!(pages[0].blocks[1].tags.is_empty());
+
+        let record: serde_json::Value =
+            serde_json::from_str(to_jsonl(&pages).lines().next().unwrap()).unwrap();
+        assert_eq!(record["tags"], serde_json::json!(["dataset"]));
+        let blocks: serde_json::Value = serde_json::from_str(&pages[0].to_blocks_json()).unwrap();
+        assert_eq!(blocks[0]["tags"], serde_json::json!(["dataset"]));
+        let text_block = serde_json::to_value(TextBlock::from_block(&pages[0].blocks[0])).unwrap();
+        assert_eq!(text_block["tags"], serde_json::json!(["dataset"]));
+    }
+
+    #[test]
+    fn test_parse_html_str_page_continuation() {
+        let body_page = |texts: &[&str]| {
+            let words = texts
+                .iter()
+                .enumerate()
+                .map(|(i, text)| {
+                    let y = 100.0 + 12.0 * i as f32;
+                    return [(*text, (72.0, y, 540.0, y + 10.0))];
+                })
+                .collect::<Vec<[FixtureWord; 1]>>();
+            let lines = words.iter().map(|line| &line[..]).collect::<Vec<&[FixtureWord]>>();
+            return page_html(&[&lines]);
+        };
+        let html = html_document(&[
+            body_page(&[
+                "The encoder maps an input sequence",
+                "of symbol representations to a sequence of contin-",
+            ]),
+            body_page(&[
+                "uous representations, which the decoder",
+                "turns into outputs.",
+            ]),
+            body_page(&[
+                "The decoder is also composed of a",
+                "stack of identical layers.",
+            ]),
+        ]);
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(&html, &mut config).unwrap();
+
+        assert!(!pages[0].blocks[0].continued);
+        assert!(pages[1].blocks[0].continued);
+        assert!(!pages[2].blocks[0].continued);
+
+        let document = Document::new(pages);
+        assert_eq!(
+            document.full_text(&FullTextOptions::default()),
+            "The encoder maps an input sequence of symbol representations to a sequence of \
+             continuous representations, which the decoder turns into outputs.\n\n\
+             The decoder is also composed of a stack of identical layers."
+        );
+        // A page marker keeps every page break
+        let text = document.full_text(&FullTextOptions::with_page_marker("\u{000C}"));
+        assert_eq!(text.matches('\u{000C}').count(), 2);
+    }
+
+    #[test]
+    fn test_parse_html_str_coordinate_origin() {
+        let html = html_document(&[page_html(&[&[&[("Page", (72.0, 100.0, 400.0, 110.0))]]])]);
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        let word = &pages[0].blocks[0].lines[0].words[0];
+        assert_eq!(word.y, 100.0);
+        // 792 - 110: the bottom edge of the word, measured from the bottom of the page
+        let flipped = Coordinate::from(word).to_origin(CoordinateOrigin::BottomLeft, 792.0);
+        assert_eq!(flipped.as_rect(), (72.0, 682.0, 400.0, 692.0));
+
+        let bbox = |json: &str| -> serde_json::Value {
+            let record: serde_json::Value = serde_json::from_str(json).unwrap();
+            return record["bbox"].clone();
+        };
+        let top_left = to_jsonl_with(&pages, CoordinateOrigin::TopLeft);
+        assert_eq!(top_left, to_jsonl(&pages));
+        assert_eq!(
+            bbox(&top_left),
+            serde_json::json!([72.0, 100.0, 400.0, 110.0])
+        );
+        let bottom_left = to_jsonl_with(&pages, CoordinateOrigin::BottomLeft);
+        assert_eq!(
+            bbox(&bottom_left),
+            serde_json::json!([72.0, 682.0, 400.0, 692.0])
+        );
+        let blocks: serde_json::Value =
+            serde_json::from_str(&pages[0].to_blocks_json_with(CoordinateOrigin::BottomLeft))
+                .unwrap();
+        assert_eq!(
+            blocks[0]["bbox"],
+            serde_json::json!([72.0, 682.0, 400.0, 692.0])
+        );
+        // The parsed pages themselves stay measured from the top-left corner
+        assert_eq!(pages[0].blocks[0].y, 100.0);
+    }
+
+    #[test]
+    fn test_parse_html_str_resolution() {
+        let html = html_document(&[page_html(&[&[&[("Page", (72.0, 100.0, 400.0, 110.0))]]])]);
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        assert_eq!(pages[0].resolution, 72);
+        assert_eq!(pages[0].scale(), 1.0);
+
+        config.resolution = 144;
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        assert_eq!(pages[0].resolution, 144);
+        assert_eq!(pages[0].scale(), 2.0);
+        let pixels = pages[0].to_pixels(&pages[0].blocks[0].coordinate());
+        assert_eq!(pixels.as_rect(), (144.0, 200.0, 800.0, 220.0));
+    }
+
+    #[test]
+    fn test_parse_html_str_stats() {
+        let html = html_document(&[page_html(&[&[
+            &[("Body", (72.0, 100.0, 540.0, 110.0))],
+            &[("Body", (72.0, 112.0, 540.0, 122.0))],
+        ]])]);
+        let html = scraper::Html::parse_document(&html);
+        let mut config = ParserConfig::new();
+        let (_, _, stats) = parse_html_with_warnings(html, &mut config).unwrap();
+
+        assert_eq!(stats.normal_font_size, 10.0);
+        assert_eq!(stats.text_area.as_rect(), (72.0, 100.0, 540.0, 122.0));
+    }
+
+    #[test]
+    fn test_parse_html_str_drop_cap() {
+        let html = html_document(&[page_html(&[
+            &[&[("T", (72.0, 100.0, 102.0, 134.0))]],
+            &[
+                &[
+                    ("he", (106.0, 100.0, 130.0, 110.0)),
+                    ("story", (134.0, 100.0, 540.0, 110.0)),
+                ],
+                &[("begins", (106.0, 112.0, 540.0, 122.0))],
+                &[("here.", (106.0, 124.0, 540.0, 134.0))],
+            ],
+        ])]);
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(&html, &mut config).unwrap();
+
+        assert_eq!(pages[0].blocks.len(), 1);
+        assert!(pages[0].blocks.iter().all(|block| block.attr != BlockAttr::Title));
+        assert_eq!(pages[0].blocks[0].get_text(), "The story begins here.");
+    }
+
+    #[test]
+    fn test_parse_html_str_merge_blocks() {
+        let html = html_document(&[page_html(&[
+            &[
+                &[(
+                    "Recurrent neural networks have been",
+                    (72.0, 100.0, 300.0, 110.0),
+                )],
+                &[(
+                    "firmly established as state of",
+                    (72.0, 112.0, 300.0, 122.0),
+                )],
+            ],
+            &[
+                &[(
+                    "the art approaches in sequence modeling",
+                    (72.0, 124.0, 300.0, 134.0),
+                )],
+                &[("and transduction problems.", (72.0, 136.0, 200.0, 146.0))],
+            ],
+        ])]);
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        assert_eq!(pages[0].blocks.len(), 2);
+
+        let mut config = ParserConfig::new();
+        config.merge_blocks = true;
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        assert_eq!(pages[0].blocks.len(), 1);
+        assert_eq!(pages[0].blocks[0].lines.len(), 4);
+    }
+
+    #[test]
+    fn test_parse_html_str_dedup_words() {
+        let line: &[FixtureWord] = &[
+            ("Recurrent", (72.0, 100.0, 180.0, 110.0)),
+            ("neural", (184.0, 100.0, 290.0, 110.0)),
+            ("networks", (294.0, 100.0, 400.0, 110.0)),
+        ];
+        let html = html_document(&[page_html(&[&[line, line]])]);
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        assert_eq!(pages[0].blocks[0].get_text().split_whitespace().count(), 6);
+
+        let mut config = ParserConfig::new();
+        config.dedup_words = true;
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        assert_eq!(pages[0].blocks[0].get_text(), "Recurrent neural networks");
+    }
+
+    #[test]
+    fn test_parse_html_str_ligatures() {
+        let html = html_document(&[page_html(&[&[&[("ﬁgure", (72.0, 100.0, 400.0, 110.0))]]])]);
+        let mut config = ParserConfig::new();
+        let word = |config: &mut ParserConfig| {
+            let pages = parse_html_str(&html, config).unwrap();
+            return pages[0].blocks[0].lines[0].words[0].clone();
+        };
+
+        let keep = word(&mut config);
+        assert_eq!(keep.text, "\u{FB01}gure");
+        assert_eq!(keep.normalized_text, None);
+
+        config.text_options.ligatures = LigatureMode::Normalize;
+        let normalize = word(&mut config);
+        assert_eq!(normalize.text, "figure");
+        assert_eq!(normalize.normalized_text, None);
+
+        config.text_options.ligatures = LigatureMode::Both;
+        let both = word(&mut config);
+        assert_eq!(both.text, "\u{FB01}gure");
+        assert_eq!(both.normalized_text.as_deref(), Some("figure"));
+        assert_eq!(both.normalized(), "figure");
+    }
+
+    #[test]
+    fn test_parse_html_str_mixed_page_sizes() {
+        // Two Letter pages and a foldout twice as wide, with margins scaled alike
+        let sized_page = |width: f32, left: f32, right: f32| {
+            let mut lines = String::new();
+            for i in 0..4 {
+                let y = 100.0 + 12.0 * i as f32;
+                lines.push_str(&format!(
+                    r#"<line xMin="{left}" yMin="{y}" xMax="{right}" yMax="{bottom}">
+<word xMin="{left}" yMin="{y}" xMax="{right}" yMax="{bottom}">Body</word>
+</line>"#,
+                    bottom = y + 10.0
+                ));
+            }
+            return format!(
+                r#"<page width="{width}" height="792"><flow>
+<block xMin="{left}" yMin="100" xMax="{right}" yMax="146">{lines}</block>
+</flow></page>"#
+            );
+        };
+        let html = html_document(&[
+            sized_page(612.0, 72.0, 540.0),
+            sized_page(612.0, 72.0, 540.0),
+            sized_page(1224.0, 144.0, 1080.0),
+        ]);
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(&html, &mut config).unwrap();
+
+        assert_eq!(pages.len(), 3);
+        assert_eq!(reference_page_size(&pages), (612.0, 792.0));
+        assert_eq!(get_text_area(&pages).as_rect(), (72.0, 100.0, 540.0, 146.0));
+        assert_eq!(
+            fit_text_area(&get_text_area(&pages), (612.0, 792.0), &pages[2]).as_rect(),
+            (144.0, 100.0, 1080.0, 146.0)
+        );
+        for page in pages.iter() {
+            assert_eq!(page.blocks.len(), 1, "page {}", page.page_number);
+            assert_eq!(
+                page.blocks[0].attr,
+                BlockAttr::Text,
+                "page {}",
+                page.page_number
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_html_str_drop_empty() {
+        let page = page_html(&[&[
+            &[("Recurrent", (72.0, 100.0, 400.0, 110.0))],
+            &[(" ", (72.0, 112.0, 400.0, 122.0))],
+            &[("networks", (72.0, 124.0, 400.0, 134.0))],
+        ]]);
+        // A block holding a single line without words
+        let page = page.replace(
+            "</flow>",
+            r#"<block xMin="72.0" yMin="140.0" xMax="400.0" yMax="150.0">
+<line xMin="72.0" yMin="140.0" xMax="400.0" yMax="150.0"></line>
+</block></flow>"#,
+        );
+        let html = html_document(&[page]);
+        let mut config = ParserConfig::new();
+        assert!(config.drop_empty);
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        assert_eq!(pages[0].blocks.len(), 1);
+        assert_eq!(pages[0].blocks[0].lines.len(), 2);
+        assert!(pages[0].blocks[0].lines.iter().all(|line| !line.words.is_empty()));
+        assert_eq!(pages[0].blocks[0].get_text(), "Recurrent networks");
+
+        config.drop_empty = false;
+        let mut warnings = Vec::new();
+        let html = scraper::Html::parse_document(&html);
+        let pages = parse_html2pages(&mut config, html, &mut warnings).unwrap();
+        assert_eq!(pages[0].blocks.len(), 2);
+        assert_eq!(pages[0].blocks[0].lines.len(), 3);
+        assert!(pages[0].blocks[1].lines[0].words.is_empty());
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn test_parse_html_file() {
+        let html = html_document(&[page_html(&[&[&[
+            ("Attention", (72.0, 100.0, 200.0, 110.0)),
+            ("Is", (210.0, 100.0, 400.0, 110.0)),
+        ]]])]);
+        let path = std::env::temp_dir().join(format!("rsrpp_html_{}.html", std::process::id()));
+        std::fs::write(&path, &html).unwrap();
+        let mut config = ParserConfig::new();
+        let pages = parse_html_file(&path, &mut config);
+        let _ = std::fs::remove_file(&path);
+
+        let pages = pages.unwrap();
+        assert_eq!(
+            pages,
+            parse_html_str(&html, &mut ParserConfig::new()).unwrap()
+        );
+        assert_eq!(pages[0].blocks[0].get_text(), "Attention Is");
+
+        let missing = parse_html_file(&path, &mut config).unwrap_err();
+        assert!(missing.to_string().contains("failed to read"));
+    }
+
+    #[test]
+    fn test_parse_html_str_keep_word_spaces() {
+        let html = html_document(&[page_html(&[&[&[
+            ("  let", (72.0, 100.0, 200.0, 110.0)),
+            ("x ", (210.0, 100.0, 400.0, 110.0)),
+        ]]])]);
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        let words = &pages[0].blocks[0].lines[0].words;
+        assert_eq!(
+            (words[0].text.as_str(), words[1].text.as_str()),
+            ("let", "x")
+        );
+
+        config.text_options.keep_word_spaces = true;
+        let pages = parse_html_str(&html, &mut config).unwrap();
+        let words = &pages[0].blocks[0].lines[0].words;
+        assert_eq!(
+            (words[0].text.as_str(), words[1].text.as_str()),
+            ("  let", "x ")
+        );
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("12.5px").unwrap(), 12.5);
+        assert_eq!(parse_number("612.000000").unwrap(), 612.0);
+        assert_eq!(parse_number(" 12,5 ").unwrap(), 12.5);
+        assert_eq!(parse_number("-3pt").unwrap(), -3.0);
+        assert_eq!(parse_number(".5").unwrap(), 0.5);
+        assert_eq!(parse_number("1,234.5").unwrap(), 1234.5);
+        assert_eq!(parse_number("612,000000").unwrap(), 612.0);
+        assert_eq!(parse_number("1.5e2pt").unwrap(), 150.0);
+        assert!(parse_number("px").is_err());
+        assert!(parse_number("").is_err());
+        for value in ["NaN", "inf", "-infinity", "1e999"] {
+            assert!(parse_number(value).is_err(), "{}", value);
+        }
+
+        let html = r#"<html><body><doc><page width="612.0pt" height="792,0">
+<flow><block xMin="72.0px" yMin="100,0" xMax="400.0px" yMax="110.0">
+<line xMin="72.0px" yMin="100,0" xMax="400.0px" yMax="110.0">
+<word xMin="72.0px" yMin="100,0" xMax="400.0px" yMax="110.0">Attention</word>
+</line>
+</block></flow>
+</page></doc></body></html>"#;
+        let mut config = ParserConfig::new();
+        let mut warnings = Vec::new();
+        let html = scraper::Html::parse_document(html);
+        let pages = parse_html2pages(&mut config, html, &mut warnings).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!((pages[0].width, pages[0].height), (612.0, 792.0));
+        let word = &pages[0].blocks[0].lines[0].words[0];
+        assert_eq!((word.x, word.y, word.width), (72.0, 100.0, 328.0));
+    }
+
+    #[test]
+    fn test_parse_html_str_single_page() {
+        let html = html_document(&[page_html(&[
+            &[&[("Poster", (72.0, 72.0, 540.0, 90.0))]],
+            &[
+                &[("Body", (72.0, 120.0, 540.0, 130.0))],
+                &[("text.", (72.0, 132.0, 540.0, 142.0))],
+            ],
+            &[&[("1", (300.0, 740.0, 306.0, 750.0))]],
+        ])]);
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(&html, &mut config).unwrap();
+
+        assert_eq!(pages.len(), 1);
+        let texts = pages[0].blocks.iter().map(|block| block.get_text()).collect::<Vec<String>>();
+        assert_eq!(texts, vec!["Poster", "Body text."]);
+        let attrs = pages[0].blocks.iter().map(|block| block.attr).collect::<Vec<BlockAttr>>();
+        assert_eq!(attrs, vec![BlockAttr::Title, BlockAttr::Text]);
+
+        let empty = vec![Page::new(612.0, 792.0, 1)];
+        assert_eq!(get_text_area(&empty).as_rect(), (0.0, 0.0, 612.0, 792.0));
+    }
+
+    #[test]
+    fn test_parse_html_str_without_pages() {
+        let html = r#"<html><head><title></title></head><body><doc></doc></body></html>"#;
+        let mut config = ParserConfig::new();
+        let pages = parse_html_str(html, &mut config).unwrap();
+        assert!(pages.is_empty());
+
+        let html = scraper::Html::parse_document(html);
+        let (pages, warnings, stats) = parse_html_with_warnings(html, &mut config).unwrap();
+        assert!(pages.is_empty());
+        assert!(warnings.is_empty());
+        assert_eq!(stats.normal_font_size, 0.0);
+        assert_eq!(get_text_area(&pages).get_area(), 0.0);
+    }
+
+    #[test]
+    fn test_parse_html_str_max_pages() {
+        let page = page_html(&[&[&[("Page", (72.0, 100.0, 400.0, 110.0))]]]);
+        let html = html_document(&vec![page; 3]);
+        let mut config = ParserConfig::new();
+        config.max_pages = Some(2);
+        let err = parse_html_str(&html, &mut config).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TooManyPages>(),
+            Some(&TooManyPages { pages: 3, limit: 2 })
+        );
+
+        config.max_pages = Some(3);
+        assert_eq!(parse_html_str(&html, &mut config).unwrap().len(), 3);
+    }
+
+    #[cfg(feature = "native")]
+    fn write_sample_pdf(path: &str) -> std::io::Result<()> {
+        let body = "Rust makes it possible to write fast and reliable research tooling that";
+        let mut content = String::new();
+        let mut y = 720;
+        for heading in ["Abstract", "1 Introduction", "2 Conclusion", "References"] {
+            content.push_str(&format!("BT /F2 14 Tf 72 {} Td ({}) Tj ET\n", y, heading));
+            y -= 24;
+            for _ in 0..3 {
+                content.push_str(&format!("BT /F1 10 Tf 72 {} Td ({}) Tj ET\n", y, body));
+                y -= 14;
+            }
+            y -= 20;
+        }
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Resources << /Font << /F1 4 0 R /F2 5 0 R >> >> /Contents 6 0 R >>".to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold >>".to_string(),
+            format!("<< /Length {} >>\nstream\n{}endstream", content.len(), content),
+        ];
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+        }
+        let xref = pdf.len();
+        pdf.push_str(&format!(
+            "xref\n0 {}\n0000000000 65535 f \n",
+            objects.len() + 1
+        ));
+        for offset in offsets {
+            pdf.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        pdf.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        ));
+        return std::fs::write(path, pdf);
+    }
+
+    #[cfg(all(feature = "native", not(feature = "download")))]
+    #[test_log::test(tokio::test)]
+    async fn test_parse_local_file_offline() {
+        let path = "/tmp/rsrpp_offline_sample.pdf";
+        write_sample_pdf(path).unwrap();
+        let mut config = ParserConfig::new();
+        let pages = parse(path, &mut config, true).await.expect("parse local file");
+        assert_eq!(pages.len(), 1);
+        let sections = Section::from_pages(&pages);
+        assert!(sections.iter().any(|section| section.title == "Introduction"));
+        let _ = config.clean_files();
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[cfg(feature = "native")]
+    #[test_log::test(tokio::test)]
+    async fn test_parse_file_in_place() {
+        let mut config = ParserConfig::new();
+        assert!(parse_file("/tmp/rsrpp_missing.pdf", &mut config, false).await.is_err());
+
+        let path = "/tmp/rsrpp_in_place_sample.pdf";
+        write_sample_pdf(path).unwrap();
+        let pages = parse_file(path, &mut config, true).await.expect("parse local file");
+        assert_eq!(pages.len(), 1);
+        assert_eq!(config.source_pdf_path(), path);
+        assert!(!std::path::Path::new(&config.pdf_path).exists());
+        let _ = config.clean_files();
+        assert!(std::path::Path::new(path).exists());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_parse_blocking() {
+        let mut config = ParserConfig::new();
+        assert!(parse_blocking("/tmp/rsrpp_missing.pdf", &mut config, false).is_err());
+
+        let path = format!("/tmp/rsrpp_blocking_sample_{}.pdf", std::process::id());
+        write_sample_pdf(&path).unwrap();
+        let mut config = ParserConfig::new();
+        let pages = parse_blocking(&path, &mut config, false).expect("parse without a runtime");
+        assert_eq!(pages.len(), 1);
+        let _ = config.clean_files();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(feature = "native")]
+    #[test_log::test(tokio::test)]
+    async fn test_parse_error_removes_intermediates() {
+        let path = format!("/tmp/rsrpp_not_a_pdf_{}.pdf", std::process::id());
+        std::fs::write(&path, "not a pdf").unwrap();
+        for keep_intermediates in [false, true] {
+            let mut config = ParserConfig::new();
+            config.keep_intermediates = keep_intermediates;
+            // Files left by the steps that ran before the failure
+            std::fs::write(&config.pdf_text_path, "").unwrap();
+            std::fs::write(&config.pdf_xml_path, "").unwrap();
+
+            assert!(parse(&path, &mut config, false).await.is_err());
+            let text_path = std::path::Path::new(&config.pdf_text_path);
+            let xml_path = std::path::Path::new(&config.pdf_xml_path);
+            assert_eq!(text_path.exists(), keep_intermediates);
+            assert_eq!(xml_path.exists(), keep_intermediates);
+            assert!(std::path::Path::new(&path).exists());
+            let _ = config.clean_files();
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(feature = "native")]
+    #[test_log::test(tokio::test)]
+    async fn test_parse_batch() {
+        let path = "/tmp/rsrpp_batch_sample.pdf";
+        let copy = "/tmp/rsrpp_batch_sample_copy.pdf";
+        write_sample_pdf(path).unwrap();
+        std::fs::copy(path, copy).unwrap();
+        let missing = "/tmp/rsrpp_batch_missing.pdf";
+
+        let results = parse_batch(&[path, missing, copy], false).await;
+        let inputs = results.iter().map(|(input, _)| input.as_str()).collect::<Vec<&str>>();
+        assert_eq!(inputs, vec![path, missing]);
+        // An unreadable input only fails its own entry
+        assert!(results[0].1.as_ref().is_ok_and(|pages| pages.len() == 1));
+        assert!(results[1].1.is_err());
+
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(copy);
+    }
+
+    #[cfg(feature = "native")]
+    #[test_log::test(tokio::test)]
+    async fn test_parse_to_format() {
+        let path = "/tmp/rsrpp_format_sample.pdf";
+        write_sample_pdf(path).unwrap();
+
+        let text = parse_to_format(path, OutputFormat::Text).await.unwrap();
+        assert!(text.contains("Rust makes it possible"));
+        let markdown = parse_to_format(path, OutputFormat::Markdown).await.unwrap();
+        assert!(markdown.contains("## Introduction"));
+        let json = parse_to_format(path, OutputFormat::Json).await.unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(json.as_array().unwrap().iter().any(|section| section["title"] == "Introduction"));
+        let hocr = parse_to_format(path, OutputFormat::Hocr).await.unwrap();
+        assert!(hocr.contains("class=\"ocr_page\""));
+
+        let _ = std::fs::remove_file(path);
+    }
 }